@@ -1,27 +1,125 @@
-use proc_macro2::TokenStream;
-use quote::{ToTokens, TokenStreamExt};
-use syn::{
-    parse::{Parse, ParseStream},
-    Attribute, Result,
-};
+use syn::{Attribute, Error, Expr, ExprLit, Lit, Path, Result};
 
+/// Options of the container attribute `#[surprise(...)]`
 #[derive(Default)]
-pub(crate) struct Attributes {
-    pub(crate) inner: Vec<Attribute>,
+pub(crate) struct SurpriseAttrs {
+    /// `#[surprise(recursive(max_depth = ...))]`
+    pub(crate) recursive: Option<Recursive>,
+    /// `#[surprise(filter = "...", max_retries = ...)]`
+    pub(crate) filter: Option<Filter>,
 }
 
-impl ToTokens for Attributes {
-    #[inline]
-    fn to_tokens(&self, tokens: &mut TokenStream) {
-        tokens.append_all(&self.inner);
+pub(crate) struct Recursive {
+    pub(crate) max_depth: Expr,
+    /// Factor by which weights of non-leaf variants are scaled at each depth
+    pub(crate) decay: Option<Expr>,
+}
+
+pub(crate) struct Filter {
+    /// Function that accepts valid values
+    pub(crate) predicate: Path,
+    /// Default of the factor's `max_retries` field
+    pub(crate) max_retries: Option<Expr>,
+}
+
+impl SurpriseAttrs {
+    pub(crate) fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut this = Self::default();
+        let mut max_retries = None;
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("surprise")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("filter") {
+                    let predicate = match meta.value()?.parse()? {
+                        Expr::Lit(ExprLit {
+                            lit: Lit::Str(lit), ..
+                        }) => lit.parse()?,
+                        Expr::Path(expr) if expr.qself.is_none() => expr.path,
+                        expr => {
+                            let msg = r#"expected `#[surprise(filter = "path::to::predicate")]`"#;
+
+                            return Err(Error::new_spanned(expr, msg));
+                        }
+                    };
+
+                    this.filter = Some(Filter {
+                        predicate,
+                        max_retries: None,
+                    });
+
+                    Ok(())
+                } else if meta.path.is_ident("max_retries") {
+                    max_retries = Some((meta.path.clone(), meta.value()?.parse()?));
+
+                    Ok(())
+                } else if meta.path.is_ident("recursive") {
+                    let mut max_depth = None;
+                    let mut decay = None;
+
+                    meta.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("max_depth") {
+                            max_depth = Some(meta.value()?.parse()?);
+
+                            Ok(())
+                        } else if meta.path.is_ident("decay") {
+                            decay = Some(meta.value()?.parse()?);
+
+                            Ok(())
+                        } else {
+                            Err(meta.error("expected `max_depth` or `decay`"))
+                        }
+                    })?;
+
+                    let max_depth = max_depth.ok_or_else(|| {
+                        meta.error("expected `#[surprise(recursive(max_depth = ...))]`")
+                    })?;
+
+                    this.recursive = Some(Recursive { max_depth, decay });
+
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `recursive(...)`, `filter` or `max_retries`"))
+                }
+            })?;
+        }
+
+        match (this.filter.as_mut(), max_retries) {
+            (Some(filter), max_retries) => filter.max_retries = max_retries.map(|(_, expr)| expr),
+            (None, Some((path, _))) => {
+                let msg = "`max_retries` requires `#[surprise(filter = ...)]`";
+
+                return Err(Error::new_spanned(path, msg));
+            }
+            (None, None) => {}
+        }
+
+        Ok(this)
     }
 }
 
-impl Parse for Attributes {
-    #[inline]
-    fn parse(input: ParseStream) -> Result<Self> {
-        input
-            .call(Attribute::parse_outer)
-            .map(|inner| Self { inner })
+/// Options of the variant attribute `#[surprise(...)]`
+#[derive(Default)]
+pub(crate) struct VariantAttrs {
+    /// `#[surprise(leaf)]`
+    pub(crate) leaf: Option<Path>,
+}
+
+impl VariantAttrs {
+    pub(crate) fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut this = Self::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("surprise")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("leaf") {
+                    this.leaf = Some(meta.path);
+
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `leaf`"))
+                }
+            })?;
+        }
+
+        Ok(this)
     }
 }
@@ -0,0 +1,222 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    parse_quote, Data, DeriveInput, Error, Fields, Generics, Ident, Path, Result, WhereClause,
+};
+
+use crate::{
+    attributes::SurpriseAttrs,
+    util::{
+        find_custom_factor, find_generator, members, validate_attrs, variant_factor_members,
+        CustomSurpriseFactor,
+    },
+};
+
+pub fn impl_enumerate(input: DeriveInput) -> Result<TokenStream> {
+    EnumerateImpl::new(input).map(|enumerate_impl| quote!(#enumerate_impl))
+}
+
+struct EnumerateImpl {
+    name: Ident,
+    generics: Generics,
+    where_clause: WhereClause,
+    body: TokenStream,
+    /// Predicate of `#[surprise(filter = ...)]` that the values must satisfy
+    filter: Option<Path>,
+}
+
+impl EnumerateImpl {
+    fn new(input: DeriveInput) -> Result<Self> {
+        let DeriveInput {
+            attrs,
+            ident,
+            generics,
+            data,
+            ..
+        } = input;
+
+        if let CustomSurpriseFactor(Some(factor_name)) = validate_attrs(&attrs, &data)? {
+            let msg = "Cannot derive `Enumerate` for types with a custom surprise factor";
+
+            return Err(Error::new_spanned(factor_name, msg));
+        }
+
+        let surprise_attrs = SurpriseAttrs::parse(&attrs)?;
+
+        if surprise_attrs.recursive.is_some() {
+            let msg = "Cannot derive `Enumerate` for recursive types";
+
+            return Err(Error::new_spanned(ident, msg));
+        }
+
+        let mut where_clause = generics
+            .where_clause
+            .clone()
+            .unwrap_or_else(|| WhereClause {
+                where_token: Default::default(),
+                predicates: Default::default(),
+            });
+
+        let body = match data {
+            Data::Struct(data) => {
+                let factor_members: Vec<_> = members(&data.fields)
+                    .map(|member| quote!(factor. #member))
+                    .collect();
+
+                enumerate_fields(
+                    &data.fields,
+                    &factor_members,
+                    quote!(Self),
+                    &mut where_clause,
+                )?
+            }
+            Data::Enum(data) => {
+                let mut variants = Vec::with_capacity(data.variants.len());
+
+                for (i, variant) in data.variants.iter().enumerate() {
+                    let variant_name = &variant.ident;
+
+                    let factor_members = variant_factor_members(variant_name, &variant.fields);
+
+                    let values = enumerate_fields(
+                        &variant.fields,
+                        &factor_members,
+                        quote!(Self:: #variant_name),
+                        &mut where_clause,
+                    )?;
+
+                    // Variants that are never generated are not enumerated either
+                    variants.push(quote! {
+                        if factor.variants_weight[#i] > 0.0 {
+                            values = Box::new(values.chain(#values));
+                        }
+                    });
+                }
+
+                quote! {
+                    let mut values: ::surprise_me::Enumerator<'a, Self> =
+                        Box::new(::std::iter::empty());
+
+                    #( #variants )*
+
+                    values
+                }
+            }
+            Data::Union(data) => {
+                return Err(Error::new_spanned(
+                    data.union_token,
+                    "Cannot derive `Enumerate` for unions",
+                ))
+            }
+        };
+
+        Ok(Self {
+            name: ident,
+            generics,
+            where_clause,
+            body,
+            filter: surprise_attrs.filter.map(|filter| filter.predicate),
+        })
+    }
+}
+
+impl ToTokens for EnumerateImpl {
+    #[inline]
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let (impl_generics, ty_generics, _) = self.generics.split_for_impl();
+        let name = &self.name;
+        let where_clause = &self.where_clause;
+        let body = &self.body;
+        let body = match self.filter {
+            Some(ref predicate) => quote! {
+                let values: ::surprise_me::Enumerator<'a, Self> = { #body };
+
+                Box::new(values.filter(|value| #predicate(value)))
+            },
+            None => body.to_token_stream(),
+        };
+
+        let enumerate = quote! {
+            impl #impl_generics ::surprise_me::Enumerate for #name #ty_generics #where_clause {
+                #[allow(unused_variables, clippy::clone_on_copy)]
+                fn enumerate<'a>(
+                    factor: &'a <Self as ::surprise_me::Surprise>::Factor,
+                ) -> ::surprise_me::Enumerator<'a, Self>
+                where
+                    Self: 'a,
+                {
+                    #body
+                }
+            }
+        };
+
+        tokens.extend(enumerate);
+    }
+}
+
+/// Enumerator over all combinations of the fields' values with the first field changing slowest.
+///
+/// The values of the fields are enumerated as nested pairs, e.g. `(field_0, (field_1, field_2))`,
+/// and then assembled through `path`.
+fn enumerate_fields(
+    fields: &Fields,
+    factor_members: &[TokenStream],
+    path: TokenStream,
+    where_clause: &mut WhereClause,
+) -> Result<TokenStream> {
+    let bindings: Vec<_> = (0..fields.len())
+        .map(|i| format_ident!("field_{i}"))
+        .collect();
+
+    let value = match fields {
+        Fields::Named(_) => {
+            let members = members(fields);
+
+            quote!(#path { #( #members: #bindings ),* })
+        }
+        Fields::Unnamed(_) => quote!(#path ( #( #bindings ),* )),
+        Fields::Unit => path,
+    };
+
+    // Enumerator over the nested pairs and the pattern to destructure them
+    let mut nested: Option<(TokenStream, TokenStream)> = None;
+
+    // Nest the pairs from the last field to the first one
+    for ((field, factor_member), binding) in fields.iter().zip(factor_members).zip(&bindings).rev()
+    {
+        if let Some(factor_name) = find_custom_factor(&field.attrs)? {
+            let msg = "Cannot derive `Enumerate` for fields with a custom surprise factor";
+
+            return Err(Error::new_spanned(factor_name, msg));
+        }
+
+        if find_generator(&field.attrs)?.is_some() {
+            let msg = "Cannot derive `Enumerate` for fields generated by `with` or `depends`";
+
+            return Err(Error::new_spanned(field, msg));
+        }
+
+        let ty = &field.ty;
+
+        where_clause
+            .predicates
+            .push(parse_quote!(#ty: ::surprise_me::Enumerate + ::std::clone::Clone));
+
+        let field_values = quote!(<#ty as ::surprise_me::Enumerate>::enumerate(&#factor_member));
+
+        nested = Some(match nested {
+            Some((rest, rest_pattern)) => (
+                quote!(::surprise_me::__private::product(#field_values, move || #rest)),
+                quote!((#binding, #rest_pattern)),
+            ),
+            None => (field_values, quote!(#binding)),
+        });
+    }
+
+    let tokens = match nested {
+        Some((values, pattern)) => quote!(Box::new(#values.map(|#pattern| #value))),
+        None => quote!(Box::new(::std::iter::once(#value))),
+    };
+
+    Ok(tokens)
+}
@@ -1,28 +1,38 @@
 use proc_macro2::{Ident, Literal, Span, TokenStream};
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::{
     parse_quote, parse_quote_spanned,
     punctuated::Punctuated,
     spanned::Spanned,
     token::{Comma, Semi},
-    Attribute, Data, DataEnum, DataStruct, Error, Expr, ExprAssign, ExprLit, Field,
-    FieldMutability, FieldValue, Fields, FieldsNamed, Index, Lit, LitFloat, Member, Meta, MetaList,
-    MetaNameValue, Result, Token, Type, TypeTuple, Visibility, WhereClause, WherePredicate,
+    Attribute, Data, DataEnum, DataStruct, Error, Expr, ExprAssign, ExprClosure, ExprLit, Field,
+    FieldMutability, Fields, FieldsNamed, Index, Lit, LitFloat, Member, Meta, MetaList,
+    MetaNameValue, Pat, PatType, Result, Token, Type, TypeTuple, Visibility, WhereClause,
+    WherePredicate,
 };
 
 use crate::{
+    attributes::{Filter, Recursive, SurpriseAttrs, VariantAttrs},
     match_arms::Arms,
-    util::{find_custom_factor, TokenResult, VariantValues},
+    util::{
+        find_custom_factor, find_generator, members, DependsParam, FactorArg, Generator,
+        RecursionPoint,
+    },
 };
 
 pub(crate) struct SurpriseFactorImpl {
     pub(crate) vis: Visibility,
     pub(crate) fields: Fields,
     pub(crate) semi_token: Option<Semi>,
+    /// Body of the `TryDistribution::try_sample` method
     pub(crate) distribution_body: TokenStream,
+    /// Body of the `Validate::problems` method
+    pub(crate) validate_body: TokenStream,
     pub(crate) default_struct: TokenStream,
     pub(crate) default_assigns: Punctuated<ExprAssign, Semi>,
     pub(crate) default_where_clause: Option<WhereClause>,
+    /// Whether the distribution body depends on a `depth` variable
+    pub(crate) recursive: bool,
 }
 
 impl SurpriseFactorImpl {
@@ -31,15 +41,23 @@ impl SurpriseFactorImpl {
         name: &Ident,
         where_clause: Option<WhereClause>,
         data: Data,
+        attrs: &SurpriseAttrs,
     ) -> Result<Self> {
         let where_clause = where_clause.unwrap_or_else(|| WhereClause {
             where_token: Default::default(),
             predicates: Default::default(),
         });
 
+        let recursive = attrs.recursive.as_ref();
+        let filter = attrs.filter.as_ref();
+
         match data {
-            Data::Struct(data) => Self::new_for_struct(vis, name, where_clause, data),
-            Data::Enum(data) => Self::new_for_enum(vis, name, where_clause, data),
+            Data::Struct(data) => {
+                Self::new_for_struct(vis, name, where_clause, data, recursive, filter)
+            }
+            Data::Enum(data) => {
+                Self::new_for_enum(vis, name, where_clause, data, recursive, filter)
+            }
             Data::Union(data) => Err(Error::new_spanned(
                 data.union_token,
                 "Cannot derive `Surprise` for unions",
@@ -52,96 +70,151 @@ impl SurpriseFactorImpl {
         name: &Ident,
         mut where_clause: WhereClause,
         mut data: DataStruct,
+        recursive: Option<&Recursive>,
+        filter: Option<&Filter>,
     ) -> Result<Self> {
+        let recursion_points = recursion_points(&data.fields, name, recursive)?;
+
+        if let (Some(filter), Fields::Unit) = (filter, &data.fields) {
+            let msg = "Unit structs have a single value, there is nothing to filter";
+
+            return Err(Error::new_spanned(&filter.predicate, msg));
+        }
+
+        if recursive.is_some() && recursion_points.iter().all(Option::is_none) {
+            return Err(no_recursion_point());
+        }
+
+        if let Some((field, _)) = data
+            .fields
+            .iter()
+            .zip(recursion_points.iter())
+            .find(|(_, point)| matches!(point, Some(RecursionPoint::Box)))
+        {
+            let msg = "`Box<Self>` cannot be generated in structs because it never terminates";
+
+            return Err(Error::new_spanned(&field.ty, msg));
+        }
+
+        // Member through which the maximum recursion depth is accessed
+        let max_depth = match data.fields {
+            Fields::Unnamed(ref fields) => Member::Unnamed(Index::from(fields.unnamed.len())),
+            Fields::Named(_) | Fields::Unit => Member::Named(Ident::new("max_depth", name.span())),
+        };
+
+        // Members through which the maximum amount of retries and the stats of the filter are accessed
+        let (max_retries, filter_stats) = match data.fields {
+            Fields::Unnamed(ref fields) => {
+                let idx = fields.unnamed.len() + recursive.iter().len();
+
+                (
+                    Member::Unnamed(Index::from(idx)),
+                    Member::Unnamed(Index::from(idx + 1)),
+                )
+            }
+            Fields::Named(_) | Fields::Unit => (
+                Member::Named(Ident::new("max_retries", name.span())),
+                Member::Named(Ident::new("filter_stats", name.span())),
+            ),
+        };
+
+        let factors: Vec<_> = members(&data.fields)
+            .map(|member| quote!(self. #member))
+            .collect();
+        let labels: Vec<_> = members(&data.fields)
+            .map(|member| match member {
+                Member::Named(ident) => ident.to_string(),
+                Member::Unnamed(idx) => idx.index.to_string(),
+            })
+            .collect();
+
+        let (samples, bindings) =
+            sample_fields(&data.fields, &factors, &recursion_points, &labels)?;
+        let field_members: Vec<_> = members(&data.fields).collect();
+
         let distribution_body = match data.fields {
-            Fields::Named(ref fields) => {
-                let fields = fields
-                    .named
-                    .iter()
-                    .map(|field| {
-                        let name = &field
-                            .ident
-                            .as_ref()
-                            .expect("missing field name on named fields");
-
-                        let ty = &field.ty;
-
-                        let tokens = if let Some(factor_name) = find_custom_factor(&field.attrs)? {
-                            quote! {
-                                #name: <
-                                    #factor_name as ::surprise_me::Distribution<#ty>
-                                >::sample(&self. #name, rng)
-                            }
-                        } else {
-                            quote! {
-                                #name: <
-                                    <#ty as Surprise>::Factor
-                                    as ::surprise_me::Distribution<#ty>
-                                >::sample(&self. #name, rng)
-                            }
-                        };
+            Fields::Named(_) => quote! {
+                #samples
 
-                        Ok(tokens)
-                    })
-                    .map(TokenResult::from);
+                Ok(#name { #( #field_members: #bindings ),* })
+            },
+            Fields::Unnamed(_) => quote! {
+                #samples
 
-                quote! {
-                    #[allow(clippy::needless_borrow)]
-                    #name { #( #fields ,)* }
-                }
+                Ok(#name ( #( #bindings ),* ))
+            },
+            Fields::Unit => quote!(Ok(#name)),
+        };
+
+        let distribution_body = if recursive.is_some() {
+            quote! {
+                let recurse = depth < self. #max_depth;
+
+                #distribution_body
             }
-            Fields::Unnamed(ref fields) => {
-                let fields = fields
-                    .unnamed
-                    .iter()
-                    .enumerate()
-                    .map(|(i, field)| {
-                        let tuple_idx = Index {
-                            index: i as u32,
-                            span: field.span(),
-                        };
-
-                        let ty = &field.ty;
-
-                        let tokens = if let Some(factor_name) = find_custom_factor(&field.attrs)? {
-                            quote! {
-                                <#factor_name as ::surprise_me::Distribution<#ty>>
-                                    ::sample(&self. #tuple_idx, rng)
-                            }
-                        } else {
-                            quote! {
-                                <<#ty as Surprise>::Factor as ::surprise_me::Distribution<#ty>>
-                                    ::sample(&self. #tuple_idx, rng)
-                            }
-                        };
+        } else {
+            distribution_body
+        };
 
-                        Ok(tokens)
-                    })
-                    .map(TokenResult::from);
+        let distribution_body = match filter {
+            Some(filter) => filter_body(distribution_body, filter, &max_retries, &filter_stats),
+            None => distribution_body,
+        };
 
-                quote! {
-                    #[allow(clippy::needless_borrow)]
-                    #name ( #( #fields ),* )
+        let mut field_checks = Vec::new();
+
+        for (i, (field, point)) in data.fields.iter().zip(recursion_points.iter()).enumerate() {
+            let (member, label) = match field.ident {
+                Some(ref ident) => (quote!(#ident), ident.to_string()),
+                None => {
+                    let idx = Index {
+                        index: i as u32,
+                        span: field.span(),
+                    };
+
+                    (quote!(#idx), i.to_string())
                 }
-            }
-            Fields::Unit => quote!(#name),
+            };
+
+            field_checks.extend(check_field(field, quote!(self. #member), *point, &label)?);
+        }
+
+        let validate_body = quote! {
+            #[allow(unused_mut)]
+            let mut problems = ::std::vec::Vec::new();
+
+            #( #field_checks )*
+
+            problems
         };
 
+        let max_depth_default = recursive.map(|recursive| &recursive.max_depth);
+        let max_retries_default = filter.map(max_retries_default);
+
         let default_struct = match data.fields {
             Fields::Named(ref fields) => {
                 let fields = fields.named.iter().map(|field| field.ident.as_ref());
+                let max_depth = max_depth_default.map(|expr| quote!(#max_depth: #expr,));
+                let max_retries = max_retries_default.map(|expr| {
+                    quote!(#max_retries: #expr, #filter_stats: ::surprise_me::filter::FilterStats::new(),)
+                });
 
                 quote! {
                     Self {
                         #( #fields: Default::default(), )*
+                        #max_depth
+                        #max_retries
                     }
                 }
             }
             Fields::Unnamed(ref fields) => {
                 let fields = fields.unnamed.iter().map(|_| quote!(Default::default()));
+                let max_depth = max_depth_default.map(|expr| quote!(#expr,));
+                let max_retries = max_retries_default
+                    .map(|expr| quote!(#expr, ::surprise_me::filter::FilterStats::new(),));
 
                 quote! {
-                    Self( #( #fields, )* )
+                    Self( #( #fields, )* #max_depth #max_retries )
                 }
             }
             Fields::Unit => quote!(Self),
@@ -175,16 +248,10 @@ impl SurpriseFactorImpl {
             default_assigns.push_punct(Default::default());
         }
 
-        for field in data.fields.iter_mut() {
+        for (field, point) in data.fields.iter_mut().zip(recursion_points) {
             field.vis = Visibility::Public(Default::default());
 
-            let ty = if let Some(factor_name) = find_custom_factor(&field.attrs)? {
-                parse_quote!(#factor_name)
-            } else {
-                let ty = &field.ty;
-
-                parse_quote!(<#ty as Surprise>::Factor)
-            };
+            let ty = field_factor_ty(field, point)?;
 
             where_clause
                 .predicates
@@ -202,14 +269,51 @@ impl SurpriseFactorImpl {
             }
         }
 
+        if recursive.is_some() {
+            let max_depth_field = Field {
+                attrs: vec![parse_quote!(#[doc = " The maximum recursion depth"])],
+                vis: Visibility::Public(Default::default()),
+                mutability: FieldMutability::None,
+                ident: None,
+                colon_token: None,
+                ty: parse_quote!(usize),
+            };
+
+            match data.fields {
+                Fields::Named(ref mut fields) => fields.named.push(Field {
+                    ident: Some(Ident::new("max_depth", name.span())),
+                    colon_token: Some(Default::default()),
+                    ..max_depth_field
+                }),
+                Fields::Unnamed(ref mut fields) => fields.unnamed.push(max_depth_field),
+                Fields::Unit => {}
+            }
+        }
+
+        if filter.is_some() {
+            for field in [max_retries_field(name), filter_stats_field(name)] {
+                match data.fields {
+                    Fields::Named(ref mut fields) => fields.named.push(field),
+                    Fields::Unnamed(ref mut fields) => fields.unnamed.push(Field {
+                        ident: None,
+                        colon_token: None,
+                        ..field
+                    }),
+                    Fields::Unit => {}
+                }
+            }
+        }
+
         Ok(Self {
             semi_token: data.semi_token,
             vis,
             fields: data.fields,
             distribution_body,
+            validate_body,
             default_struct,
             default_assigns,
             default_where_clause: (!where_clause.predicates.is_empty()).then_some(where_clause),
+            recursive: recursive.is_some(),
         })
     }
 
@@ -218,6 +322,8 @@ impl SurpriseFactorImpl {
         name: &Ident,
         mut where_clause: WhereClause,
         data: DataEnum,
+        recursive: Option<&Recursive>,
+        filter: Option<&Filter>,
     ) -> Result<Self> {
         // Fields of the new `{TypeName}Surprise` struct
         let mut named_fields = Punctuated::<Field, Comma>::new();
@@ -227,8 +333,18 @@ impl SurpriseFactorImpl {
 
         let variant_count = data.variants.len();
 
+        #[allow(unused_mut)]
+        let mut variants_weight_attrs: Vec<Attribute> =
+            vec![parse_quote_spanned! { name.span() => #[doc = #variants_weight_doc] }];
+
+        // serde only supports arrays of up to 32 elements
+        #[cfg(feature = "serde")]
+        variants_weight_attrs.push(parse_quote! {
+            #[serde(with = "::surprise_me::__private::variants_weight")]
+        });
+
         named_fields.push_value(Field {
-            attrs: vec![parse_quote_spanned! { name.span() => #[doc = #variants_weight_doc] }],
+            attrs: variants_weight_attrs,
             vis: Visibility::Public(Default::default()),
             mutability: FieldMutability::None,
             ident: Some(Ident::new("variants_weight", name.span())),
@@ -239,13 +355,46 @@ impl SurpriseFactorImpl {
         // Match arms of the `Distribution::sample` method
         let mut factor_match_arms = Arms::default();
 
+        // Statements of the `Validate::problems` method
+        let mut field_checks = vec![quote! {
+            if let Err(problem) = ::surprise_me::__private::check_weights(self.variants_weight.as_ref()) {
+                problems.push(problem);
+            }
+        }];
+
+        if recursive.is_some() {
+            field_checks.push(quote! {
+                if let Err(problem) = ::surprise_me::__private::check_weight_decay(self.weight_decay) {
+                    problems.push(problem);
+                }
+            });
+        }
+
         // Fields of the Default implementation
         let mut default_assigns = Punctuated::<ExprAssign, Semi>::new();
         let mut variants_weight = Punctuated::<Expr, Comma>::new();
         let mut found_non_zero_weight = false;
         let mut found_weight_attr = false;
 
+        // Variants explicitly marked with `#[surprise(leaf)]`
+        let mut marked_leaves = Vec::new();
+        // Variants that contain recursion points
+        let mut recursive_variants = Vec::new();
+        // Variants that recurse through `Box<Self>`
+        let mut boxed_variants = Vec::new();
+
         for (i, variant) in data.variants.iter().enumerate() {
+            if let Some(leaf) = VariantAttrs::parse(&variant.attrs)?.leaf {
+                if recursive.is_none() {
+                    let msg =
+                        "`#[surprise(leaf)]` requires `#[surprise(recursive(...))]` on the enum";
+
+                    return Err(Error::new_spanned(leaf, msg));
+                }
+
+                marked_leaves.push(i);
+            }
+
             let weight_lit = variant
                 .attrs
                 .iter()
@@ -292,142 +441,69 @@ impl SurpriseFactorImpl {
             variants_weight.push(variant_weight);
 
             let variant_name = &variant.ident;
-            let i = Literal::usize_unsuffixed(i);
-
-            match variant.fields {
-                Fields::Named(ref fields) => {
-                    if fields.named.is_empty() {
-                        let match_arm = parse_quote_spanned! {
-                            variant.span() => #i => #name :: #variant_name {}
-                        };
+            let recursion_points = recursion_points(&variant.fields, name, recursive)?;
 
-                        factor_match_arms.push(match_arm);
+            if recursion_points.iter().any(Option::is_some) {
+                recursive_variants.push(i);
+            }
 
-                        continue;
-                    }
+            if recursion_points
+                .iter()
+                .any(|point| matches!(point, Some(RecursionPoint::Box)))
+            {
+                boxed_variants.push(i);
+            }
 
-                    let values = fields
-                        .named
-                        .iter()
-                        .enumerate()
-                        .map(|(j, field)| {
-                            let field_span = field.span();
-                            let field_name = field.ident.clone();
-                            let ty = &field.ty;
-
-                            let idx = Index {
-                                index: j as u32,
-                                span: field_span,
-                            };
-
-                            let tokens: FieldValue =
-                                if let Some(factor_name) = find_custom_factor(&field.attrs)? {
-                                    parse_quote_spanned! { ty.span() =>
-                                        #field_name: <
-                                            #factor_name as ::surprise_me::Distribution<#ty>
-                                        >::sample(&self. #variant_name . #idx, rng)
-                                    }
-                                } else {
-                                    parse_quote_spanned! { ty.span() =>
-                                        #field_name: <
-                                            <#ty as Surprise>::Factor
-                                            as ::surprise_me::Distribution<#ty>
-                                        >::sample(&self. #variant_name . #idx, rng)
-                                    }
-                                };
-
-                            Ok(tokens)
-                        })
-                        .collect::<Result<_>>()?;
-
-                    let variant_values = VariantValues::Fields(values);
-
-                    let match_arm = parse_quote_spanned! { variant.span() =>
-                        #i => #name :: #variant_name #variant_values
-                    };
+            let i = Literal::usize_unsuffixed(i);
 
-                    factor_match_arms.push(match_arm);
+            let factors: Vec<_> = (0..variant.fields.len())
+                .map(Index::from)
+                .map(|idx| quote!(self. #variant_name . #idx))
+                .collect();
+            let labels: Vec<_> = (0..variant.fields.len())
+                .map(|j| format!("{variant_name}.{j}"))
+                .collect();
+
+            let (samples, bindings) =
+                sample_fields(&variant.fields, &factors, &recursion_points, &labels)?;
+            let field_members: Vec<_> = members(&variant.fields).collect();
+
+            let value = match variant.fields {
+                Fields::Named(_) => {
+                    quote!(#name :: #variant_name { #( #field_members: #bindings ),* })
                 }
-                Fields::Unnamed(ref fields) => {
-                    if fields.unnamed.is_empty() {
-                        let match_arm = parse_quote_spanned! {
-                            variant.span() => #i => #name :: #variant_name ()
-                        };
-
-                        factor_match_arms.push(match_arm);
-
-                        continue;
-                    }
+                Fields::Unnamed(_) => quote!(#name :: #variant_name ( #( #bindings ),* )),
+                Fields::Unit => quote!(#name :: #variant_name),
+            };
 
-                    let values = fields
-                        .unnamed
-                        .iter()
-                        .enumerate()
-                        .map(|(j, field)| {
-                            let idx = Index {
-                                index: j as u32,
-                                span: field.span(),
-                            };
-
-                            let ty = &field.ty;
-
-                            let tokens: Expr =
-                                if let Some(factor_name) = find_custom_factor(&field.attrs)? {
-                                    parse_quote_spanned! { field.span() =>
-                                        <#factor_name as ::surprise_me::Distribution<#ty>>
-                                            ::sample(&self. #variant_name . #idx, rng)
-                                    }
-                                } else {
-                                    parse_quote_spanned! { field.span() =>
-                                        <<#ty as Surprise>::Factor as
-                                            ::surprise_me::Distribution<#ty>
-                                        >::sample(&self. #variant_name . #idx, rng)
-                                    }
-                                };
-
-                            Ok(tokens)
-                        })
-                        .collect::<Result<_>>()?;
-
-                    let variant_values = VariantValues::Tuple(values);
-
-                    let match_arm = parse_quote_spanned! { variant.span() =>
-                        #i => #name :: #variant_name #variant_values
-                    };
+            factor_match_arms.push(parse_quote_spanned! { variant.span() =>
+                #i => {
+                    #samples
 
-                    factor_match_arms.push(match_arm);
+                    #value
                 }
-                Fields::Unit => {
-                    let match_arm = parse_quote_spanned! {
-                        variant.span() => #i => #name :: #variant_name
-                    };
-
-                    factor_match_arms.push(match_arm);
+            });
 
-                    continue;
-                }
+            if variant.fields.is_empty() {
+                continue;
             }
 
             let mut field_tuple_elems = Punctuated::<Type, Comma>::new();
 
             let mut field_chain = vec![Member::Named(variant_name.to_owned())];
 
-            for (i, field) in variant.fields.iter().enumerate() {
-                let ty_elem = if let Some(factor_name) = find_custom_factor(&field.attrs)? {
-                    parse_quote!(#factor_name)
-                } else {
-                    let ty = &field.ty;
-
-                    parse_quote_spanned!(ty.span() => <#ty as Surprise>::Factor)
-                };
-
-                field_tuple_elems.push(ty_elem);
+            for (i, (field, point)) in variant.fields.iter().zip(recursion_points).enumerate() {
+                field_tuple_elems.push(field_factor_ty(field, point)?);
 
                 let idx = Index {
                     index: i as u32,
                     span: field.span(),
                 };
 
+                let label = format!("{variant_name}.{i}");
+                let factor = quote!(self. #variant_name . #idx);
+                field_checks.extend(check_field(field, factor, point, &label)?);
+
                 field_chain.push(Member::Unnamed(idx));
                 parse_default_attrs(&field.attrs, &mut field_chain, &mut default_assigns)?;
                 field_chain.pop();
@@ -473,49 +549,513 @@ impl SurpriseFactorImpl {
             return Err(Error::new(Span::call_site(), msg));
         }
 
+        if recursive.is_some() && recursive_variants.is_empty() {
+            return Err(no_recursion_point());
+        }
+
+        // Unless specified explicitly, variants without recursion points are leaves
+        let non_leaf_variants: Vec<_> = if marked_leaves.is_empty() {
+            recursive_variants
+        } else {
+            (0..variant_count)
+                .filter(|i| !marked_leaves.contains(i))
+                .collect()
+        };
+
+        if let Some(i) = boxed_variants
+            .iter()
+            .find(|i| !non_leaf_variants.contains(i))
+        {
+            let msg = "Leaf variants cannot contain `Box<Self>` because it never terminates";
+
+            return Err(Error::new_spanned(&data.variants[*i], msg));
+        }
+
+        if recursive.is_some() && non_leaf_variants.len() == variant_count {
+            let msg = "At least one variant must be a leaf for the recursion to terminate, \
+                i.e. contain no `Box<Self>` or be denoted with `#[surprise(leaf)]`";
+
+            return Err(Error::new(Span::call_site(), msg));
+        }
+
         let default_fields = named_fields.iter().skip(1).map(|field| {
             let name = &field.ident;
 
             quote!(#name: Default::default())
         });
 
+        let max_depth_default = recursive.map(|recursive| {
+            let max_depth = &recursive.max_depth;
+
+            let weight_decay = match recursive.decay {
+                Some(ref decay) => quote!(#decay),
+                None => quote!(1.0),
+            };
+
+            quote! {
+                max_depth: #max_depth,
+                weight_decay: #weight_decay,
+            }
+        });
+
+        let max_retries_default = filter.map(|filter| {
+            let max_retries = max_retries_default(filter);
+
+            quote! {
+                max_retries: #max_retries,
+                filter_stats: ::surprise_me::filter::FilterStats::new(),
+            }
+        });
+
         let default_struct = quote! {
             Self {
                 variants_weight: Box::new([ #variants_weight ]),
                 #( #default_fields ,)*
+                #max_depth_default
+                #max_retries_default
             }
         };
 
+        if recursive.is_some() {
+            named_fields.push(Field {
+                attrs: vec![parse_quote!(#[doc = " The maximum recursion depth"])],
+                vis: Visibility::Public(Default::default()),
+                mutability: FieldMutability::None,
+                ident: Some(Ident::new("max_depth", name.span())),
+                colon_token: Some(Token![:](name.span())),
+                ty: parse_quote!(usize),
+            });
+
+            let weight_decay_doc = " Factor by which the weights of non-leaf variants \
+                are scaled with each level of depth";
+
+            named_fields.push(Field {
+                attrs: vec![parse_quote!(#[doc = #weight_decay_doc])],
+                vis: Visibility::Public(Default::default()),
+                mutability: FieldMutability::None,
+                ident: Some(Ident::new("weight_decay", name.span())),
+                colon_token: Some(Token![:](name.span())),
+                ty: parse_quote!(f64),
+            });
+        }
+
+        if filter.is_some() {
+            named_fields.push(max_retries_field(name));
+            named_fields.push(filter_stats_field(name));
+        }
+
         let named_fields = FieldsNamed {
             brace_token: Default::default(),
             named: named_fields,
         };
 
-        let distribution_body = quote! {
-            let weights = self.variants_weight.as_ref();
-            let weighted_idx = ::surprise_me::rand::distributions::WeightedIndex::new(weights).unwrap();
-            let idx = rng.sample(weighted_idx);
+        let distribution_body = if recursive.is_some() {
+            // Non-leaf variants become less likely with each level
+            // and are excluded once the maximum depth is reached
+            let decay_non_leaves = (!non_leaf_variants.is_empty()).then(|| {
+                quote! {
+                    let decay = if recurse {
+                        self.weight_decay.powi(depth as i32)
+                    } else {
+                        0.0
+                    };
 
-            #[allow(clippy::needless_borrow)]
-            match idx {
-                #factor_match_arms
+                    for i in [ #( #non_leaf_variants ),* ] {
+                        weights[i] *= decay;
+                    }
+                }
+            });
+
+            quote! {
+                ::surprise_me::__private::check_weight_decay(self.weight_decay)?;
+
+                let recurse = depth < self.max_depth;
+
+                #[allow(unused_mut)]
+                let mut weights = *self.variants_weight;
+
+                #decay_non_leaves
+
+                let weighted_idx = ::surprise_me::__private::weighted_index(weights.as_ref())?;
+                let idx = rng.sample(weighted_idx);
+
+                #[allow(clippy::needless_borrow)]
+                let value = match idx {
+                    #factor_match_arms
+                };
+
+                Ok(value)
+            }
+        } else {
+            quote! {
+                let weights = self.variants_weight.as_ref();
+                let weighted_idx = ::surprise_me::__private::weighted_index(weights)?;
+                let idx = rng.sample(weighted_idx);
+
+                #[allow(clippy::needless_borrow)]
+                let value = match idx {
+                    #factor_match_arms
+                };
+
+                Ok(value)
             }
         };
 
+        let distribution_body = match filter {
+            Some(filter) => {
+                let max_retries = Member::Named(Ident::new("max_retries", name.span()));
+                let filter_stats = Member::Named(Ident::new("filter_stats", name.span()));
+
+                filter_body(distribution_body, filter, &max_retries, &filter_stats)
+            }
+            None => distribution_body,
+        };
+
         if !default_assigns.is_empty() {
             default_assigns.push_punct(Token![;](Span::call_site()));
         }
 
+        let validate_body = quote! {
+            #[allow(unused_mut)]
+            let mut problems = ::std::vec::Vec::new();
+
+            #( #field_checks )*
+
+            problems
+        };
+
         Ok(Self {
             semi_token: None,
             vis,
             fields: Fields::Named(named_fields),
             distribution_body,
+            validate_body,
             default_struct,
             default_assigns,
             default_where_clause: (!where_clause.predicates.is_empty()).then_some(where_clause),
+            recursive: recursive.is_some(),
+        })
+    }
+}
+
+/// Wraps the distribution body so that it's repeated until the filter accepts the value
+fn filter_body(
+    body: TokenStream,
+    filter: &Filter,
+    max_retries: &Member,
+    filter_stats: &Member,
+) -> TokenStream {
+    let predicate = &filter.predicate;
+
+    quote! {
+        ::surprise_me::filter::try_filter(
+            rng,
+            self. #max_retries,
+            &self. #filter_stats,
+            |rng| { #body },
+            #predicate,
+        )
+    }
+}
+
+/// The factor field `max_retries` of filtered types
+fn max_retries_field(name: &Ident) -> Field {
+    let doc = " How many more values are generated after the filter rejects the first one";
+
+    Field {
+        attrs: vec![parse_quote!(#[doc = #doc])],
+        vis: Visibility::Public(Default::default()),
+        mutability: FieldMutability::None,
+        ident: Some(Ident::new("max_retries", name.span())),
+        colon_token: Some(Token![:](name.span())),
+        ty: parse_quote!(usize),
+    }
+}
+
+/// The factor field `filter_stats` of filtered types, which is not (de)serialized
+fn filter_stats_field(name: &Ident) -> Field {
+    let doc = " The values that the filter accepted and rejected through this surprise factor";
+
+    #[allow(unused_mut)]
+    let mut attrs: Vec<Attribute> = vec![parse_quote!(#[doc = #doc])];
+
+    #[cfg(feature = "serde")]
+    attrs.push(parse_quote!(#[serde(skip)]));
+
+    Field {
+        attrs,
+        vis: Visibility::Public(Default::default()),
+        mutability: FieldMutability::None,
+        ident: Some(Ident::new("filter_stats", name.span())),
+        colon_token: Some(Token![:](name.span())),
+        ty: parse_quote!(::surprise_me::filter::FilterStats),
+    }
+}
+
+/// The default of the factor field `max_retries`
+fn max_retries_default(filter: &Filter) -> TokenStream {
+    match filter.max_retries {
+        Some(ref max_retries) => quote!(#max_retries),
+        None => quote!(::surprise_me::filter::DEFAULT_MAX_RETRIES),
+    }
+}
+
+/// Determine the recursion point of each field, if any
+fn recursion_points(
+    fields: &Fields,
+    name: &Ident,
+    recursive: Option<&Recursive>,
+) -> Result<Vec<Option<RecursionPoint>>> {
+    fields
+        .iter()
+        .map(|field| {
+            if recursive.is_none()
+                || find_custom_factor(&field.attrs)?.is_some()
+                || find_generator(&field.attrs)?.is_some()
+            {
+                Ok(None)
+            } else {
+                Ok(RecursionPoint::new(&field.ty, name))
+            }
         })
+        .collect()
+}
+
+fn no_recursion_point() -> Error {
+    let msg =
+        "Expected at least one field of type `Box<Self>`, `Option<Box<Self>>`, or `Vec<Self>` \
+        for `#[surprise(recursive(...))]`";
+
+    Error::new(Span::call_site(), msg)
+}
+
+/// The type of a field's surprise factor
+fn field_factor_ty(field: &Field, recursion_point: Option<RecursionPoint>) -> Result<Type> {
+    let ty = if let Some(factor_name) = find_custom_factor(&field.attrs)? {
+        parse_quote!(#factor_name)
+    } else if find_generator(&field.attrs)?.is_some() {
+        parse_quote!(::surprise_me::factors::UnitSurprise)
+    } else if let Some(point) = recursion_point {
+        point.factor_ty()
+    } else {
+        let ty = &field.ty;
+
+        parse_quote_spanned!(ty.span() => <#ty as Surprise>::Factor)
+    };
+
+    Ok(ty)
+}
+
+/// Statements that sample the fields into `field_{i}` bindings, with fields of
+/// `#[factor(depends = ...)]` after the fields they depend on.
+///
+/// `factors` and `labels` are passed on to [`sample_field`] for each field.
+fn sample_fields(
+    fields: &Fields,
+    factors: &[TokenStream],
+    recursion_points: &[Option<RecursionPoint>],
+    labels: &[String],
+) -> Result<(TokenStream, Vec<Ident>)> {
+    let field_list: Vec<_> = fields.iter().collect();
+    let bindings: Vec<_> = (0..field_list.len())
+        .map(|i| format_ident!("field_{i}"))
+        .collect();
+
+    let mut generators = Vec::with_capacity(field_list.len());
+    let mut params = Vec::with_capacity(field_list.len());
+
+    for field in field_list.iter() {
+        let generator = find_generator(&field.attrs)?;
+
+        params.push(match generator {
+            Some(ref generator) => generator.depends_params(fields)?,
+            None => Vec::new(),
+        });
+        generators.push(generator);
+    }
+
+    // Fields in declaration order unless they depend on fields that aren't sampled yet
+    let mut order = Vec::with_capacity(field_list.len());
+    let mut sampled = vec![false; field_list.len()];
+
+    while order.len() < field_list.len() {
+        let next = (0..field_list.len()).find(|&i| {
+            !sampled[i]
+                && params[i].iter().all(|param| match param {
+                    DependsParam::Field(j) => sampled[*j],
+                    DependsParam::Rng => true,
+                })
+        });
+
+        let Some(i) = next else {
+            let field = sampled.iter().position(|sampled| !sampled).unwrap();
+            let msg = "`depends` forms a cycle that leads back to this field";
+
+            return Err(Error::new_spanned(field_list[field], msg));
+        };
+
+        sampled[i] = true;
+        order.push(i);
     }
+
+    let mut statements = TokenStream::new();
+
+    for i in order {
+        let field = field_list[i];
+        let binding = &bindings[i];
+
+        let value = if let Some(Generator::Depends(ref closure)) = generators[i] {
+            depends_call(closure, &params[i], &field_list, &bindings)
+        } else {
+            sample_field(field, factors[i].clone(), recursion_points[i], &labels[i])?
+        };
+
+        statements.extend(quote! {
+            #[allow(clippy::needless_borrow, clippy::redundant_closure_call)]
+            let #binding = #value;
+        });
+    }
+
+    Ok((statements, bindings))
+}
+
+/// Expression that calls the closure of `#[factor(depends = ...)]` with references
+/// to the fields it depends on and the random number generator.
+///
+/// The parameters are annotated with their types which closures can't infer from the call.
+fn depends_call(
+    closure: &ExprClosure,
+    params: &[DependsParam],
+    fields: &[&Field],
+    bindings: &[Ident],
+) -> TokenStream {
+    let mut closure = closure.clone();
+    let mut args = Vec::with_capacity(params.len());
+
+    for (input, param) in closure.inputs.iter_mut().zip(params) {
+        let ty: Type = match *param {
+            DependsParam::Rng => {
+                args.push(quote!(rng));
+
+                parse_quote!(&mut R)
+            }
+            DependsParam::Field(j) => {
+                let binding = &bindings[j];
+                let ty = &fields[j].ty;
+                args.push(quote!(&#binding));
+
+                parse_quote!(&#ty)
+            }
+        };
+
+        if !matches!(input, Pat::Type(_)) {
+            *input = Pat::Type(PatType {
+                attrs: Vec::new(),
+                pat: Box::new(input.clone()),
+                colon_token: Default::default(),
+                ty: Box::new(ty),
+            });
+        }
+    }
+
+    quote!((#closure)( #( #args ),* ))
+}
+
+/// Expression that samples a value for a field through its surprise factor `factor`.
+///
+/// Errors are propagated with `?` and prefixed with `label`, the factor's field.
+/// Custom surprise factors and generator functions are assumed to never fail.
+fn sample_field(
+    field: &Field,
+    factor: TokenStream,
+    recursion_point: Option<RecursionPoint>,
+    label: &str,
+) -> Result<TokenStream> {
+    let ty = &field.ty;
+    let in_field = quote!(map_err(|err| err.in_field(#label))?);
+
+    let tokens = if let Some(factor_name) = find_custom_factor(&field.attrs)? {
+        quote! {
+            <#factor_name as ::surprise_me::Distribution<#ty>>::sample(&#factor, rng)
+        }
+    } else if let Some(Generator::With(function)) = find_generator(&field.attrs)? {
+        quote!(#function(rng))
+    } else if let Some(point) = recursion_point {
+        // Errors of nested levels stem from the same factor and are passed on as they are
+        let nested = quote!(self.try_sample_at_depth(rng, depth + 1));
+
+        match point {
+            RecursionPoint::Box => quote! {{
+                let value = #nested?;
+                ::surprise_me::budget::spend_box(::std::mem::size_of_val(&value));
+
+                Box::new(value)
+            }},
+            RecursionPoint::OptionBox => quote! {
+                if recurse && ::surprise_me::__private::try_gen_bool(rng, #factor.chance).#in_field {
+                    let value = #nested?;
+                    ::surprise_me::budget::spend_box(::std::mem::size_of_val(&value));
+
+                    Some(Box::new(value))
+                } else {
+                    None
+                }
+            },
+            RecursionPoint::Vec => quote! {
+                if recurse {
+                    let len = ::surprise_me::budget::spend_len(
+                        #factor.try_len(rng).#in_field,
+                        #factor.min_len,
+                        ::std::mem::size_of::<<#ty as ::std::iter::IntoIterator>::Item>(),
+                    );
+
+                    (0..len)
+                        .map(|_| #nested)
+                        .collect::<Result<_, _>>()?
+                } else {
+                    Vec::new()
+                }
+            },
+        }
+    } else {
+        quote! {
+            <#ty as ::surprise_me::Surprise>::try_generate_with_factor(rng, &#factor).#in_field
+        }
+    };
+
+    Ok(tokens)
+}
+
+/// Statement that collects the problems of a field's surprise factor `factor`
+/// into `problems`, prefixed with `label`.
+///
+/// Custom surprise factors are assumed to be valid and generator functions have no factor.
+fn check_field(
+    field: &Field,
+    factor: TokenStream,
+    recursion_point: Option<RecursionPoint>,
+    label: &str,
+) -> Result<Option<TokenStream>> {
+    let ty = &field.ty;
+
+    let field_problems =
+        if find_custom_factor(&field.attrs)?.is_some() || find_generator(&field.attrs)?.is_some() {
+            return Ok(None);
+        } else if recursion_point.is_some() {
+            quote!(::surprise_me::Validate::problems(&#factor))
+        } else {
+            quote!(<#ty as ::surprise_me::Surprise>::factor_problems(&#factor))
+        };
+
+    let tokens = quote! {
+        problems.extend(
+            #field_problems
+                .into_iter()
+                .map(|problem| problem.in_field(#label)),
+        );
+    };
+
+    Ok(Some(tokens))
 }
 
 fn parse_default_attrs(
@@ -523,24 +1063,46 @@ fn parse_default_attrs(
     field_chain: &mut Vec<Member>,
     assigns: &mut Punctuated<ExprAssign, Semi>,
 ) -> Result<()> {
+    /// String literals may initialize more than `&'static str`, e.g. regexes
+    fn from_str_literal(value: Expr) -> Expr {
+        match value {
+            lit @ Expr::Lit(ExprLit {
+                lit: Lit::Str(_), ..
+            }) => parse_quote! {
+                ::surprise_me::__private::FromStrLiteral::from_str_literal(#lit)
+            },
+            value => value,
+        }
+    }
+
     fn parse_nested_attr(
         list: &MetaList,
         assigns: &mut Punctuated<ExprAssign, Semi>,
         field_chain: &mut Vec<Member>,
     ) -> Result<()> {
-        let nested = list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+        let nested = list.parse_args_with(Punctuated::<FactorArg, Token![,]>::parse_terminated)?;
 
-        for meta in nested {
-            match meta {
-                Meta::Path(path) => {
+        for arg in nested {
+            match arg {
+                FactorArg::Meta(Meta::Path(path)) => {
                     let assign = parse_quote! {
                         default. #( #field_chain ).* = #path
                     };
 
                     assigns.push(assign);
                 }
-                Meta::NameValue(value) => {
+                FactorArg::Expr(expr) => {
+                    let expr = from_str_literal(expr);
+
+                    let assign = parse_quote! {
+                        default. #( #field_chain ).* = #expr
+                    };
+
+                    assigns.push(assign);
+                }
+                FactorArg::Meta(Meta::NameValue(value)) => {
                     let MetaNameValue { path, value, .. } = value;
+                    let value = from_str_literal(value);
 
                     let assign = parse_quote! {
                         default. #( #field_chain .)* #path = #value
@@ -548,7 +1110,7 @@ fn parse_default_attrs(
 
                     assigns.push(assign);
                 }
-                Meta::List(inner_list) => {
+                FactorArg::Meta(Meta::List(inner_list)) => {
                     let member = Member::Named(inner_list.path.get_ident().unwrap().clone());
                     field_chain.push(member);
                     parse_nested_attr(&inner_list, assigns, field_chain)?;
@@ -560,6 +1122,11 @@ fn parse_default_attrs(
         Ok(())
     }
 
+    // Fields generated by functions have no surprise factor to set up
+    if find_generator(attrs)?.is_some() {
+        return Ok(());
+    }
+
     for attr in attrs {
         if !attr.path().is_ident("factor") {
             continue;
@@ -3,18 +3,20 @@ use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
 use syn::{parse_macro_input, DeriveInput, Error, Ident};
 
-use self::surprise::impl_surprise;
+use self::{enumerate::impl_enumerate, shrink::impl_shrink, surprise::impl_surprise};
 
 mod attributes;
+mod enumerate;
 mod factor;
 mod match_arms;
+mod shrink;
 mod surprise;
 mod util;
 
 /// Derive macro for the `Surprise` trait.
 ///
 /// Check the trait's description for more information.
-#[proc_macro_derive(Surprise, attributes(factor, weight))]
+#[proc_macro_derive(Surprise, attributes(factor, surprise, weight))]
 pub fn surprise(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let name = input.ident.clone();
@@ -25,6 +27,32 @@ pub fn surprise(input: TokenStream) -> TokenStream {
     }
 }
 
+/// Derive macro for the `Shrink` trait.
+///
+/// Check the trait's description for more information.
+#[proc_macro_derive(Shrink, attributes(factor, surprise, weight))]
+pub fn shrink(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match impl_shrink(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Derive macro for the `Enumerate` trait.
+///
+/// Check the trait's description for more information.
+#[proc_macro_derive(Enumerate, attributes(factor, surprise, weight))]
+pub fn enumerate(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match impl_enumerate(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
 fn dummy_surprise(err: Error, name: Ident) -> TokenStream2 {
     let err = err.to_compile_error();
 
@@ -0,0 +1,318 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    parse_quote, Data, DeriveInput, Error, Fields, Generics, Ident, Path, Result, WhereClause,
+};
+
+use crate::{
+    attributes::SurpriseAttrs,
+    util::{
+        find_custom_factor, find_generator, members, validate_attrs, variant_factor_members,
+        CustomSurpriseFactor, RecursionPoint,
+    },
+};
+
+pub fn impl_shrink(input: DeriveInput) -> Result<TokenStream> {
+    ShrinkImpl::new(input).map(|shrink_impl| quote!(#shrink_impl))
+}
+
+struct ShrinkImpl {
+    name: Ident,
+    generics: Generics,
+    where_clause: WhereClause,
+    body: TokenStream,
+    /// Predicate of `#[surprise(filter = ...)]` that the values must satisfy
+    filter: Option<Path>,
+}
+
+impl ShrinkImpl {
+    fn new(input: DeriveInput) -> Result<Self> {
+        let DeriveInput {
+            attrs,
+            ident,
+            generics,
+            data,
+            ..
+        } = input;
+
+        if let CustomSurpriseFactor(Some(factor_name)) = validate_attrs(&attrs, &data)? {
+            let msg = "Cannot derive `Shrink` for types with a custom surprise factor";
+
+            return Err(Error::new_spanned(factor_name, msg));
+        }
+
+        let surprise_attrs = SurpriseAttrs::parse(&attrs)?;
+
+        // Recursion points are only considered for recursive types
+        let recursive_name = surprise_attrs.recursive.as_ref().map(|_| ident.clone());
+
+        let mut where_clause = generics
+            .where_clause
+            .clone()
+            .unwrap_or_else(|| WhereClause {
+                where_token: Default::default(),
+                predicates: Default::default(),
+            });
+
+        let body = match data {
+            Data::Struct(data) => {
+                let factor_members: Vec<_> = members(&data.fields)
+                    .map(|member| quote!(factor. #member))
+                    .collect();
+
+                let fields = ShrinkFields::new(
+                    &data.fields,
+                    &factor_members,
+                    recursive_name.as_ref(),
+                    &mut where_clause,
+                )?;
+                let pattern = fields.pattern(quote!(Self));
+                let candidates = fields.candidates(quote!(Self));
+
+                quote! {
+                    let #pattern = self;
+
+                    Box::new(::std::iter::empty() #candidates)
+                }
+            }
+            Data::Enum(data) => {
+                let mut arms = Vec::with_capacity(data.variants.len());
+
+                // Indices and values of the unit variants
+                let unit_variants: Vec<_> = data
+                    .variants
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, variant)| variant.fields.is_empty())
+                    .map(|(i, variant)| {
+                        let variant_name = &variant.ident;
+
+                        quote!(#i => Some(Self:: #variant_name))
+                    })
+                    .collect();
+
+                for (i, variant) in data.variants.iter().enumerate() {
+                    let variant_name = &variant.ident;
+
+                    let factor_members = variant_factor_members(variant_name, &variant.fields);
+
+                    let fields = ShrinkFields::new(
+                        &variant.fields,
+                        &factor_members,
+                        recursive_name.as_ref(),
+                        &mut where_clause,
+                    )?;
+                    let pattern = fields.pattern(quote!(Self:: #variant_name));
+                    let candidates = fields.candidates(quote!(Self:: #variant_name));
+
+                    let simpler_variants = (!unit_variants.is_empty()).then(|| {
+                        let is_unit = variant.fields.is_empty();
+
+                        quote! {
+                            .chain(
+                                ::surprise_me::__private::simpler_variants(
+                                    &factor.variants_weight[..],
+                                    #i,
+                                    #is_unit,
+                                )
+                                .into_iter()
+                                .filter_map(unit_variant)
+                            )
+                        }
+                    });
+
+                    arms.push(quote! {
+                        #pattern => Box::new(
+                            ::std::iter::empty()
+                                #simpler_variants
+                                #candidates
+                        )
+                    });
+                }
+
+                let unit_variant = (!unit_variants.is_empty()).then(|| {
+                    quote! {
+                        let unit_variant = |i: usize| -> Option<Self> {
+                            match i {
+                                #( #unit_variants ,)*
+                                _ => None,
+                            }
+                        };
+                    }
+                });
+
+                quote! {
+                    #unit_variant
+
+                    match self {
+                        #( #arms ,)*
+                    }
+                }
+            }
+            Data::Union(data) => {
+                return Err(Error::new_spanned(
+                    data.union_token,
+                    "Cannot derive `Shrink` for unions",
+                ))
+            }
+        };
+
+        Ok(Self {
+            name: ident,
+            generics,
+            where_clause,
+            body,
+            filter: surprise_attrs.filter.map(|filter| filter.predicate),
+        })
+    }
+}
+
+impl ToTokens for ShrinkImpl {
+    #[inline]
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let (impl_generics, ty_generics, _) = self.generics.split_for_impl();
+        let name = &self.name;
+        let where_clause = &self.where_clause;
+        let body = &self.body;
+        let body = match self.filter {
+            Some(ref predicate) => quote! {
+                let values: ::surprise_me::Shrinker<'a, Self> = { #body };
+
+                Box::new(values.filter(|value| #predicate(value)))
+            },
+            None => body.to_token_stream(),
+        };
+
+        let shrink = quote! {
+            impl #impl_generics ::surprise_me::Shrink for #name #ty_generics #where_clause {
+                #[allow(unused_variables, clippy::clone_on_copy)]
+                fn shrink<'a>(
+                    &'a self,
+                    factor: &'a <Self as ::surprise_me::Surprise>::Factor,
+                ) -> ::surprise_me::Shrinker<'a, Self> {
+                    #body
+                }
+            }
+        };
+
+        tokens.extend(shrink);
+    }
+}
+
+/// The fields of a struct or variant that are shrunk one after the other
+struct ShrinkFields<'f> {
+    fields: &'f Fields,
+    /// Bindings of the fields when destructuring `self`
+    bindings: Vec<Ident>,
+    /// Iterators over shrunk values of shrinkable fields
+    shrinkers: Vec<Option<TokenStream>>,
+}
+
+impl<'f> ShrinkFields<'f> {
+    fn new(
+        fields: &'f Fields,
+        factor_members: &[TokenStream],
+        recursive_name: Option<&Ident>,
+        where_clause: &mut WhereClause,
+    ) -> Result<Self> {
+        let mut bindings = Vec::with_capacity(fields.len());
+        let mut shrinkers = Vec::with_capacity(fields.len());
+
+        for ((i, field), factor_member) in fields.iter().enumerate().zip(factor_members) {
+            let ty = &field.ty;
+            let binding = format_ident!("field_{i}");
+
+            where_clause
+                .predicates
+                .push(parse_quote!(#ty: ::std::clone::Clone));
+
+            let recursion_point =
+                recursive_name.and_then(|name| RecursionPoint::new(&field.ty, name));
+
+            // Fields with custom factors or generated by `with` or `depends` are not shrunk
+            let shrinker = if find_custom_factor(&field.attrs)?.is_some()
+                || find_generator(&field.attrs)?.is_some()
+            {
+                None
+            } else if let Some(point) = recursion_point {
+                // Recursion points are shrunk through the factor of the type itself
+                let shrinker = match point {
+                    RecursionPoint::Box => quote! {
+                        ::surprise_me::Shrink::shrink(&**#binding, factor).map(Box::new)
+                    },
+                    RecursionPoint::OptionBox => quote! {
+                        ::surprise_me::__private::shrink_recursive_option(#binding, factor)
+                    },
+                    RecursionPoint::Vec => quote! {
+                        ::surprise_me::__private::shrink_recursive_vec(#binding, factor)
+                    },
+                };
+
+                Some(shrinker)
+            } else {
+                where_clause
+                    .predicates
+                    .push(parse_quote!(#ty: ::surprise_me::Shrink));
+
+                Some(quote!(::surprise_me::Shrink::shrink(#binding, &#factor_member)))
+            };
+
+            shrinkers.push(shrinker);
+            bindings.push(binding);
+        }
+
+        Ok(Self {
+            fields,
+            bindings,
+            shrinkers,
+        })
+    }
+
+    /// Pattern to destructure the fields
+    fn pattern(&self, path: TokenStream) -> TokenStream {
+        let bindings = &self.bindings;
+
+        match self.fields {
+            Fields::Named(_) => {
+                let members = members(self.fields);
+
+                quote!(#path { #( #members: #bindings ),* })
+            }
+            Fields::Unnamed(_) => quote!(#path ( #( #bindings ),* )),
+            Fields::Unit => path,
+        }
+    }
+
+    /// Chained iterators over candidates with one field shrunk
+    fn candidates(&self, path: TokenStream) -> TokenStream {
+        let mut candidates = TokenStream::new();
+
+        for (i, shrinker) in self.shrinkers.iter().enumerate() {
+            let Some(shrinker) = shrinker else { continue };
+
+            let values = self.bindings.iter().enumerate().map(|(j, binding)| {
+                if i == j {
+                    quote!(shrunk)
+                } else {
+                    quote!(::std::clone::Clone::clone(#binding))
+                }
+            });
+
+            let candidate = match self.fields {
+                Fields::Named(_) => {
+                    let members = members(self.fields);
+
+                    quote!(#path { #( #members: #values ),* })
+                }
+                Fields::Unnamed(_) => quote!(#path ( #( #values ),* )),
+                Fields::Unit => unreachable!(),
+            };
+
+            candidates.extend(quote! {
+                .chain(#shrinker.map(move |shrunk| #candidate))
+            });
+        }
+
+        candidates
+    }
+}
@@ -1,8 +1,9 @@
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote, ToTokens};
-use syn::{DeriveInput, Generics, Ident, Result};
+use syn::{DeriveInput, Error, Fields, Generics, Ident, Result};
 
 use crate::{
+    attributes::SurpriseAttrs,
     factor::SurpriseFactorImpl,
     util::{validate_attrs, CustomSurpriseFactor},
 };
@@ -28,7 +29,24 @@ impl SurpriseImpl {
             data,
         } = input;
 
+        let surprise_attrs = SurpriseAttrs::parse(&attrs)?;
+
         let this = if let CustomSurpriseFactor(Some(factor_name)) = validate_attrs(&attrs, &data)? {
+            if surprise_attrs.recursive.is_some() {
+                let msg =
+                    "`#[surprise(recursive(...))]` cannot be used with a custom surprise factor";
+
+                return Err(Error::new_spanned(factor_name, msg));
+            }
+
+            if surprise_attrs.filter.is_some() {
+                let msg =
+                    "`#[surprise(filter = ...)]` cannot be used with a custom surprise factor, \
+                    use `Filter::filter` on the factor instead";
+
+                return Err(Error::new_spanned(factor_name, msg));
+            }
+
             Self {
                 name: ident,
                 factor_name,
@@ -36,7 +54,13 @@ impl SurpriseImpl {
                 generics,
             }
         } else {
-            let factor = SurpriseFactorImpl::new(vis, &ident, generics.where_clause.clone(), data)?;
+            let factor = SurpriseFactorImpl::new(
+                vis,
+                &ident,
+                generics.where_clause.clone(),
+                data,
+                &surprise_attrs,
+            )?;
 
             Self {
                 factor: Some(factor),
@@ -57,9 +81,29 @@ impl ToTokens for SurpriseImpl {
         let name = &self.name;
         let factor_name = &self.factor_name;
 
+        // Custom surprise factors are not required to implement `TryDistribution` or `Validate`
+        let try_generate = self.factor.as_ref().map(|_| {
+            quote! {
+                #[inline]
+                fn try_generate_with_factor<R: ::surprise_me::rand::Rng + ?Sized>(
+                    rng: &mut R,
+                    factor: &Self::Factor,
+                ) -> Result<Self, ::surprise_me::SurpriseError> {
+                    ::surprise_me::TryDistribution::try_sample(factor, rng)
+                }
+
+                #[inline]
+                fn factor_problems(factor: &Self::Factor) -> Vec<::surprise_me::SurpriseError> {
+                    ::surprise_me::Validate::problems(factor)
+                }
+            }
+        });
+
         let surprise = quote! {
             impl #impl_generics ::surprise_me::Surprise for #name #ty_generics #where_clause {
                 type Factor = #factor_name #ty_generics;
+
+                #try_generate
             }
         };
 
@@ -73,20 +117,77 @@ impl ToTokens for SurpriseImpl {
                 fields,
                 semi_token,
                 distribution_body,
+                validate_body,
                 default_struct,
                 default_assigns,
                 default_where_clause,
+                recursive,
             } = factor;
 
+            let distribution = if *recursive {
+                quote! {
+                    impl #impl_generics ::surprise_me::rand::distributions::Distribution<#name #ty_generics> for #factor_name #ty_generics #where_clause {
+                        #[inline]
+                        fn sample<R: ::surprise_me::rand::Rng + ?Sized>(&self, rng: &mut R) -> #name #ty_generics {
+                            self.sample_at_depth(rng, 0)
+                        }
+                    }
+
+                    impl #impl_generics ::surprise_me::TryDistribution<#name #ty_generics> for #factor_name #ty_generics #where_clause {
+                        #[inline]
+                        fn try_sample<R: ::surprise_me::rand::Rng + ?Sized>(&self, rng: &mut R) -> Result<#name #ty_generics, ::surprise_me::SurpriseError> {
+                            self.try_sample_at_depth(rng, 0)
+                        }
+                    }
+
+                    impl #impl_generics #factor_name #ty_generics #where_clause {
+                        /// Generate a random instance as if it was nested `depth` levels deep.
+                        ///
+                        /// Recursion stops once `depth` reaches `max_depth`.
+                        #vis fn sample_at_depth<R: ::surprise_me::rand::Rng + ?Sized>(&self, rng: &mut R, depth: usize) -> #name #ty_generics {
+                            self.try_sample_at_depth(rng, depth).unwrap_or_else(|err| panic!("{err}"))
+                        }
+
+                        /// Fallible version of `sample_at_depth`.
+                        #vis fn try_sample_at_depth<R: ::surprise_me::rand::Rng + ?Sized>(&self, rng: &mut R, depth: usize) -> Result<#name #ty_generics, ::surprise_me::SurpriseError> {
+                            #distribution_body
+                        }
+                    }
+                }
+            } else {
+                quote! {
+                    impl #impl_generics ::surprise_me::rand::distributions::Distribution<#name #ty_generics> for #factor_name #ty_generics #where_clause {
+                        #[inline]
+                        fn sample<R: ::surprise_me::rand::Rng + ?Sized>(&self, rng: &mut R) -> #name #ty_generics {
+                            ::surprise_me::TryDistribution::try_sample(self, rng).unwrap_or_else(|err| panic!("{err}"))
+                        }
+                    }
+
+                    impl #impl_generics ::surprise_me::TryDistribution<#name #ty_generics> for #factor_name #ty_generics #where_clause {
+                        #[inline]
+                        fn try_sample<R: ::surprise_me::rand::Rng + ?Sized>(&self, rng: &mut R) -> Result<#name #ty_generics, ::surprise_me::SurpriseError> {
+                            #distribution_body
+                        }
+                    }
+                }
+            };
+
+            let (serde_attrs, serde_impls) =
+                serde_tokens(fields, quote!(#factor_name #ty_generics));
+
             let factor = quote! {
                 #[allow(non_snake_case, clippy::type_complexity)]
                 #[doc = #surprise_doc]
+                #serde_attrs
                 #vis struct #factor_name #impl_generics #fields #semi_token
 
-                impl #impl_generics ::surprise_me::rand::distributions::Distribution<#name #ty_generics> for #factor_name #ty_generics #where_clause {
-                    #[inline]
-                    fn sample<R: ::surprise_me::rand::Rng + ?Sized>(&self, rng: &mut R) -> #name #ty_generics {
-                        #distribution_body
+                #serde_impls
+
+                #distribution
+
+                impl #impl_generics ::surprise_me::Validate for #factor_name #ty_generics #where_clause {
+                    fn problems(&self) -> Vec<::surprise_me::SurpriseError> {
+                        #validate_body
                     }
                 }
 
@@ -106,3 +207,103 @@ impl ToTokens for SurpriseImpl {
         }
     }
 }
+
+/// Attributes and impls that provide serde's traits for the surprise factor.
+///
+/// The bounds are specified manually since serde would otherwise require
+/// the generic types themselves to implement its traits instead of their factors.
+///
+/// Custom surprise factors are not required to implement serde's traits so the
+/// bounds are higher-ranked. That way, bounds on concrete types that don't hold
+/// leave the traits unimplemented instead of failing to compile.
+#[cfg(feature = "serde")]
+fn serde_tokens(fields: &Fields, factor: TokenStream) -> (TokenStream, TokenStream) {
+    // Unit structs are (de)serialized like `UnitSurprise` so that formats
+    // without a unit type, e.g. TOML, can handle them
+    if let Fields::Unit = fields {
+        let impls = quote! {
+            impl ::surprise_me::__private::serde::Serialize for #factor {
+                fn serialize<S: ::surprise_me::__private::serde::Serializer>(
+                    &self,
+                    serializer: S,
+                ) -> Result<S::Ok, S::Error> {
+                    ::surprise_me::__private::serde::Serialize::serialize(
+                        &::surprise_me::factors::UnitSurprise,
+                        serializer,
+                    )
+                }
+            }
+
+            impl<'de> ::surprise_me::__private::serde::Deserialize<'de> for #factor {
+                fn deserialize<D: ::surprise_me::__private::serde::Deserializer<'de>>(
+                    deserializer: D,
+                ) -> Result<Self, D::Error> {
+                    <::surprise_me::factors::UnitSurprise as ::surprise_me::__private::serde::Deserialize>::deserialize(deserializer)
+                        .map(|_| Self)
+                }
+            }
+        };
+
+        return (TokenStream::new(), impls);
+    }
+
+    // Skipped fields are not (de)serialized and need no bounds
+    let is_skipped = |field: &syn::Field| {
+        let mut serde_attrs = field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("serde"));
+
+        serde_attrs.any(|attr| {
+            let mut skip = false;
+
+            // Attributes with values like `with = "..."` fail to parse, they are not skipped
+            let _ = attr.parse_nested_meta(|meta| {
+                skip |= meta.path.is_ident("skip");
+
+                Ok(())
+            });
+
+            skip
+        })
+    };
+
+    let types: Vec<_> = fields
+        .iter()
+        .filter(|field| !matches!(field.ident, Some(ref ident) if ident == "variants_weight"))
+        .filter(|field| !is_skipped(field))
+        .map(|field| field.ty.to_token_stream().to_string())
+        .collect();
+
+    let serialize = types
+        .iter()
+        .map(|ty| format!("for<'__s> {ty}: ::surprise_me::__private::serde::Serialize"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let deserialize = types
+        .iter()
+        .map(|ty| format!("for<'__s> {ty}: ::surprise_me::__private::serde::Deserialize<'de>"))
+        .chain(Some(format!("for<'__s> {factor}: ::std::default::Default")))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let attrs = quote! {
+        #[derive(
+            ::surprise_me::__private::serde::Serialize,
+            ::surprise_me::__private::serde::Deserialize,
+        )]
+        #[serde(
+            crate = "::surprise_me::__private::serde",
+            default,
+            bound(serialize = #serialize, deserialize = #deserialize),
+        )]
+    };
+
+    (attrs, TokenStream::new())
+}
+
+#[cfg(not(feature = "serde"))]
+fn serde_tokens(_: &Fields, _: TokenStream) -> (TokenStream, TokenStream) {
+    (TokenStream::new(), TokenStream::new())
+}
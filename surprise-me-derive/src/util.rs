@@ -1,9 +1,13 @@
-use proc_macro2::{Ident, Span, TokenStream};
-use quote::{ToTokens, TokenStreamExt};
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
 use syn::{
+    parse::{Parse, ParseStream},
+    parse_quote,
     punctuated::Punctuated,
-    token::{Brace, Comma, Paren},
-    Attribute, Data, Error, Expr, ExprLit, Field, FieldValue, Fields, Lit, Meta, Result, Token,
+    spanned::Spanned,
+    token::Comma,
+    Attribute, Data, Error, Expr, ExprClosure, ExprLit, Field, Fields, GenericArgument, Index, Lit,
+    Member, Meta, MetaNameValue, Pat, PatIdent, Path, PathArguments, Result, Token, Type,
 };
 
 pub(crate) struct CustomSurpriseFactor(pub(crate) Option<Ident>);
@@ -74,6 +78,10 @@ pub(crate) fn validate_attrs(attrs: &[Attribute], data: &Data) -> Result<CustomS
         Data::Enum(data) => {
             for variant in data.variants.iter() {
                 for attr in variant.attrs.iter() {
+                    if attr.path().is_ident("surprise") {
+                        continue;
+                    }
+
                     if let Meta::Path(_) | Meta::List(_) = attr.meta {
                         return AttrError::NeedNameValue(attr).into();
                     }
@@ -115,45 +123,246 @@ pub(crate) fn find_custom_factor(attrs: &[Attribute]) -> Result<Option<Ident>> {
         .transpose()
 }
 
-pub(crate) enum TokenResult<T: ToTokens, E: ToTokens> {
-    Ok(T),
-    Err(E),
+/// A single argument of `#[factor(...)]`
+pub(crate) enum FactorArg {
+    /// `field = value`, `field(...)` or a path
+    Meta(Meta),
+    /// An expression for the whole surprise factor, e.g. `CharSurprise::Range { .. }`
+    Expr(Expr),
 }
 
-impl<T: ToTokens> From<Result<T>> for TokenResult<T, TokenStream> {
-    #[inline]
-    fn from(res: Result<T>) -> Self {
-        match res {
-            Ok(ok) => Self::Ok(ok),
-            Err(err) => Self::Err(err.to_compile_error()),
+impl Parse for FactorArg {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let fork = input.fork();
+
+        // Lists are only nested fields if their path is a field name
+        let is_meta = match fork.parse::<Meta>() {
+            Ok(Meta::List(list)) => list.path.get_ident().is_some(),
+            Ok(_) => true,
+            Err(_) => false,
+        };
+
+        if is_meta && (fork.is_empty() || fork.peek(Token![,])) {
+            input.parse().map(Self::Meta)
+        } else {
+            input.parse().map(Self::Expr)
         }
     }
 }
 
-impl<T: ToTokens, E: ToTokens> ToTokens for TokenResult<T, E> {
-    fn to_tokens(&self, tokens: &mut TokenStream) {
-        match self {
-            TokenResult::Ok(ok) => ok.to_tokens(tokens),
-            TokenResult::Err(err) => err.to_tokens(tokens),
+/// How a field is generated instead of through a surprise factor
+pub(crate) enum Generator {
+    /// `#[factor(with = "path::to::function")]`
+    With(Path),
+    /// `#[factor(depends = |field, rng| ...)]`
+    Depends(ExprClosure),
+}
+
+/// A parameter of a `depends` closure
+pub(crate) enum DependsParam {
+    /// The random number generator
+    Rng,
+    /// The index of a field of the same struct or variant
+    Field(usize),
+}
+
+impl Generator {
+    /// The parameters of a `depends` closure, which name fields of `fields`, or `rng`.
+    ///
+    /// Fields of tuple structs and variants are named by their index, e.g. `_0`.
+    pub(crate) fn depends_params(&self, fields: &Fields) -> Result<Vec<DependsParam>> {
+        let Generator::Depends(closure) = self else {
+            return Ok(Vec::new());
+        };
+
+        closure
+            .inputs
+            .iter()
+            .map(|input| {
+                let pat = match input {
+                    Pat::Type(pat) => &*pat.pat,
+                    pat => pat,
+                };
+
+                let Pat::Ident(PatIdent { ident, .. }) = pat else {
+                    let msg = "expected the name of a field or `rng`";
+
+                    return Err(Error::new_spanned(input, msg));
+                };
+
+                if ident == "rng" {
+                    return Ok(DependsParam::Rng);
+                }
+
+                members(fields)
+                    .position(|member| match member {
+                        Member::Named(name) => name == *ident,
+                        Member::Unnamed(idx) => *ident == format!("_{}", idx.index),
+                    })
+                    .map(DependsParam::Field)
+                    .ok_or_else(|| Error::new_spanned(ident, format!("no field named `{ident}`")))
+            })
+            .collect()
+    }
+}
+
+/// Finds the generator of `#[factor(with = ...)]` or `#[factor(depends = ...)]`,
+/// which must be the only argument for the field
+pub(crate) fn find_generator(attrs: &[Attribute]) -> Result<Option<Generator>> {
+    let mut generator = None;
+    let mut other_args = Vec::new();
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("factor")) {
+        let Meta::List(ref list) = attr.meta else {
+            other_args.push(attr.meta.span());
+
+            continue;
+        };
+
+        let args = list.parse_args_with(Punctuated::<FactorArg, Token![,]>::parse_terminated)?;
+
+        for arg in args {
+            let (path, value) = match arg {
+                FactorArg::Meta(Meta::NameValue(MetaNameValue { path, value, .. }))
+                    if path.is_ident("with") || path.is_ident("depends") =>
+                {
+                    (path, value)
+                }
+                FactorArg::Meta(meta) => {
+                    other_args.push(meta.span());
+
+                    continue;
+                }
+                FactorArg::Expr(expr) => {
+                    other_args.push(expr.span());
+
+                    continue;
+                }
+            };
+
+            let parsed = match value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(lit), ..
+                }) if path.is_ident("with") => Generator::With(lit.parse()?),
+                Expr::Path(expr) if path.is_ident("with") && expr.qself.is_none() => {
+                    Generator::With(expr.path)
+                }
+                Expr::Closure(closure) if path.is_ident("depends") => Generator::Depends(closure),
+                value if path.is_ident("with") => {
+                    let msg = r#"expected `#[factor(with = "path::to::function")]`"#;
+
+                    return Err(Error::new_spanned(value, msg));
+                }
+                value => {
+                    let msg = "expected `#[factor(depends = |field, rng| ...)]`";
+
+                    return Err(Error::new_spanned(value, msg));
+                }
+            };
+
+            if generator.replace(parsed).is_some() {
+                let msg = "a field can only have one `with` or `depends` argument";
+
+                return Err(Error::new_spanned(path, msg));
+            }
+        }
+    }
+
+    match (generator, other_args.first()) {
+        (Some(_), Some(span)) => {
+            let msg = "Argument does nothing when the field is generated by `with` or `depends`";
+
+            Err(Error::new(*span, msg))
         }
+        (generator, _) => Ok(generator),
     }
 }
 
-pub(crate) enum VariantValues {
-    Tuple(Punctuated<Expr, Comma>),
-    Fields(Punctuated<FieldValue, Comma>),
+/// The members to access the fields, i.e. their names or indices
+pub(crate) fn members(fields: &Fields) -> impl Iterator<Item = Member> + '_ {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| match field.ident {
+            Some(ref ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index {
+                index: i as u32,
+                span: field.span(),
+            }),
+        })
+}
+
+/// The factors of a variant's fields, which are tuple fields of the variant factor even for named fields
+pub(crate) fn variant_factor_members(variant_name: &Ident, fields: &Fields) -> Vec<TokenStream> {
+    (0..fields.len())
+        .map(Index::from)
+        .map(|index| quote!(factor. #variant_name . #index))
+        .collect()
+}
+
+/// A field of a recursive type through which the type recurses
+#[derive(Copy, Clone)]
+pub(crate) enum RecursionPoint {
+    /// `Box<Self>`
+    Box,
+    /// `Option<Box<Self>>`
+    OptionBox,
+    /// `Vec<Self>`
+    Vec,
 }
 
-impl ToTokens for VariantValues {
-    #[inline]
-    fn to_tokens(&self, tokens: &mut TokenStream) {
+impl RecursionPoint {
+    pub(crate) fn new(ty: &Type, name: &Ident) -> Option<Self> {
+        if let Some(inner) = generic_arg(ty, "Box") {
+            is_self(inner, name).then_some(Self::Box)
+        } else if let Some(inner) = generic_arg(ty, "Option") {
+            let inner = generic_arg(inner, "Box")?;
+
+            is_self(inner, name).then_some(Self::OptionBox)
+        } else if let Some(inner) = generic_arg(ty, "Vec") {
+            is_self(inner, name).then_some(Self::Vec)
+        } else {
+            None
+        }
+    }
+
+    /// The type of the field's surprise factor
+    pub(crate) fn factor_ty(self) -> Type {
         match self {
-            VariantValues::Tuple(values) => Paren::default().surround(tokens, |tokens| {
-                tokens.append_separated(values, Token![,](Span::call_site()))
-            }),
-            VariantValues::Fields(values) => Brace::default().surround(tokens, |tokens| {
-                tokens.append_separated(values, Token![,](Span::call_site()))
-            }),
+            RecursionPoint::Box => parse_quote!(::surprise_me::factors::UnitSurprise),
+            RecursionPoint::OptionBox => {
+                parse_quote!(::surprise_me::factors::RecursiveOptionSurprise)
+            }
+            RecursionPoint::Vec => parse_quote!(::surprise_me::factors::RecursiveVecSurprise),
         }
     }
 }
+
+/// If `ty` is of the form `wrapper<T>`, return `T`
+fn generic_arg<'t>(ty: &'t Type, wrapper: &str) -> Option<&'t Type> {
+    let Type::Path(path) = ty else { return None };
+    let segment = path.path.segments.last()?;
+
+    if segment.ident != wrapper {
+        return None;
+    }
+
+    let PathArguments::AngleBracketed(ref args) = segment.arguments else {
+        return None;
+    };
+
+    match args.args.first()? {
+        GenericArgument::Type(ty) if args.args.len() == 1 => Some(ty),
+        _ => None,
+    }
+}
+
+/// Whether `ty` is `Self` or the type with the given name
+fn is_self(ty: &Type, name: &Ident) -> bool {
+    let Type::Path(path) = ty else { return false };
+
+    path.path
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "Self" || segment.ident == *name)
+}
@@ -3,9 +3,10 @@ mod tests {
     use std::{collections::HashMap, marker::PhantomData, num::NonZeroI32};
 
     use surprise_me::{
+        budget::Budget,
         factors::NumberSurprise,
         rand::{thread_rng, Rng},
-        Distribution, Surprise,
+        Distribution, Enumerate, Shrink, Surprise, Validate,
     };
 
     #[test]
@@ -19,6 +20,7 @@ mod tests {
     #[test]
     fn tuple_struct() {
         #[derive(Surprise)]
+        #[allow(unused)]
         struct Tuple(u32, bool, #[factor(max_len = 10)] String);
 
         let _ = Tuple::generate(&mut thread_rng());
@@ -76,7 +78,11 @@ mod tests {
     #[test]
     #[should_panic]
     fn zero_non_zero_int() {
-        let factor = NumberSurprise::<i32> { min: 0, max: 0 };
+        let factor = NumberSurprise::<i32> {
+            min: 0,
+            max: 0,
+            ..Default::default()
+        };
 
         let _ = NonZeroI32::generate_with_factor(&mut thread_rng(), &factor);
     }
@@ -248,6 +254,297 @@ mod tests {
         let _ = RecursiveEnum::generate(&mut thread_rng());
     }
 
+    #[test]
+    fn generator_functions() {
+        mod tables {
+            use surprise_me::rand::{seq::SliceRandom, Rng};
+
+            pub fn currency<R: Rng + ?Sized>(rng: &mut R) -> &'static str {
+                ["EUR", "JPY", "USD"].choose(rng).unwrap()
+            }
+        }
+
+        fn digits<R: Rng + ?Sized>(rng: &mut R) -> Vec<u8> {
+            (0..rng.gen_range(1..=9))
+                .map(|_| rng.gen_range(0..10))
+                .collect()
+        }
+
+        #[derive(Clone, Debug, Surprise, Shrink)]
+        struct Account {
+            #[factor(with = "digits")]
+            number: Vec<u8>,
+            #[factor(with = tables::currency)]
+            currency: &'static str,
+            #[factor(max_len = 3)]
+            owners: Vec<u8>,
+        }
+
+        #[derive(Surprise)]
+        enum Payment {
+            Card(#[factor(with = "digits")] Vec<u8>),
+            Transfer { to: Account },
+        }
+
+        for _ in 0..20 {
+            let account = Account::generate(&mut thread_rng());
+            assert!((1..=9).contains(&account.number.len()));
+            assert!(account.number.iter().all(|&digit| digit < 10));
+            assert!(["EUR", "JPY", "USD"].contains(&account.currency));
+
+            let (Payment::Card(number)
+            | Payment::Transfer {
+                to: Account { number, .. },
+            }) = Payment::generate(&mut thread_rng());
+            assert!(!number.is_empty());
+        }
+
+        let factor = AccountSurprise::default();
+        assert!(factor.problems().is_empty());
+
+        let account = Account {
+            number: vec![9; 9],
+            currency: "JPY",
+            owners: vec![1, 2, 3],
+        };
+        let minimal = account.minimize(&factor, |account| !account.owners.is_empty());
+        assert_eq!(minimal.number, [9; 9]);
+        assert_eq!(minimal.owners.len(), 1);
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&factor).unwrap();
+            let factor: AccountSurprise = serde_json::from_str(&json).unwrap();
+            assert_eq!(factor.owners.max_len, 3);
+        }
+    }
+
+    #[test]
+    fn dependent_fields() {
+        #[derive(Debug, Surprise)]
+        struct Window {
+            // Declared before the fields it depends on
+            #[factor(depends = |start, len| start + *len as u32)]
+            end: u32,
+            #[factor(max = 1_000)]
+            start: u32,
+            #[factor(depends = |items: &Vec<u8>| items.len())]
+            len: usize,
+            #[factor(max_len = 10)]
+            items: Vec<u8>,
+            #[factor(depends = |end, rng| rng.gen_range(0..=*end))]
+            cursor: u32,
+        }
+
+        #[derive(Surprise)]
+        enum Price {
+            Range(
+                u16,
+                #[factor(depends = |_0, rng| u32::from(*_0) + rng.gen::<u8>() as u32)] u32,
+            ),
+            Currency {
+                #[factor(depends = |country: &&str| match *country {
+                    "JP" => "JPY",
+                    "US" => "USD",
+                    _ => "EUR",
+                })]
+                currency: &'static str,
+                #[factor(with = "country")]
+                country: &'static str,
+            },
+        }
+
+        fn country<R: Rng + ?Sized>(rng: &mut R) -> &'static str {
+            ["DE", "JP", "US"][rng.gen_range(0..3)]
+        }
+
+        for _ in 0..20 {
+            let window = Window::generate(&mut thread_rng());
+            assert_eq!(window.len, window.items.len());
+            assert_eq!(window.end, window.start + window.len as u32);
+            assert!(window.cursor <= window.end);
+
+            match Price::generate(&mut thread_rng()) {
+                Price::Range(min, max) => assert!(u32::from(min) <= max),
+                Price::Currency { currency, country } => {
+                    assert_eq!(currency == "JPY", country == "JP");
+                    assert_eq!(currency == "USD", country == "US");
+                }
+            }
+        }
+
+        let factor = WindowSurprise::default();
+        assert_eq!(factor.start.max, 1_000);
+        assert!(factor.problems().is_empty());
+    }
+
+    #[test]
+    fn filter_derive() {
+        #[derive(Clone, Debug, PartialEq, Surprise, Shrink, Enumerate)]
+        #[surprise(filter = "is_sorted")]
+        struct Range {
+            #[factor(min = 1, max = 3)]
+            start: u8,
+            #[factor(min = 1, max = 3)]
+            end: u8,
+        }
+
+        fn is_sorted(range: &Range) -> bool {
+            range.start <= range.end
+        }
+
+        #[derive(Debug, Surprise)]
+        #[surprise(filter = "is_odd", max_retries = 100)]
+        enum Odd {
+            Small(#[factor(max = 10)] u8),
+            Large(u64),
+        }
+
+        fn is_odd(number: &Odd) -> bool {
+            match number {
+                Odd::Small(n) => n % 2 == 1,
+                Odd::Large(n) => n % 2 == 1,
+            }
+        }
+
+        let factor = RangeSurprise::default();
+        for _ in 0..20 {
+            let range = Range::generate_with_factor(&mut thread_rng(), &factor);
+            assert!(range.start <= range.end);
+            assert!(is_odd(&Odd::generate(&mut thread_rng())));
+        }
+
+        assert_eq!(factor.filter_stats.accepted(), 20);
+        assert!(factor.filter_stats.rejection_rate() < 1.0);
+        assert_eq!(RangeSurprise::default().filter_stats.accepted(), 0);
+        assert_eq!(factor.max_retries, surprise_me::filter::DEFAULT_MAX_RETRIES);
+        let minimal = Range { start: 3, end: 3 }.minimize(&factor, |_| true);
+        assert_eq!(minimal, Range { start: 1, end: 1 });
+        assert_eq!(Range::enumerate_all().len(), 6);
+
+        let factor = NumberSurprise::<u8> {
+            min: 2,
+            max: 2,
+            ..Default::default()
+        };
+        let factor = OddSurprise {
+            variants_weight: Box::new([1.0, 0.0]),
+            Small: (factor,),
+            ..Default::default()
+        };
+        let err = Odd::try_generate_with_factor(&mut thread_rng(), &factor).unwrap_err();
+        assert_eq!(err.field(), "max_retries");
+
+        #[cfg(feature = "serde")]
+        {
+            let factor: RangeSurprise = serde_json::from_str(r#"{ "max_retries": 5 }"#).unwrap();
+            assert_eq!(factor.max_retries, 5);
+            assert!(!serde_json::to_string(&factor)
+                .unwrap()
+                .contains("filter_stats"));
+        }
+
+        // Generic and tuple types are filtered with stats per surprise factor as well
+        #[derive(Debug, Surprise)]
+        #[surprise(filter = "is_distinct")]
+        struct Distinct<T: Surprise + PartialEq>(T, T);
+
+        fn is_distinct<T: Surprise + PartialEq>(pair: &Distinct<T>) -> bool {
+            pair.0 != pair.1
+        }
+
+        let factor = DistinctSurprise::<bool>::default();
+        for _ in 0..20 {
+            let pair = Distinct::generate_with_factor(&mut thread_rng(), &factor);
+            assert_ne!(pair.0, pair.1);
+        }
+        assert_eq!(factor.3.accepted(), 20);
+        assert_eq!(DistinctSurprise::<u8>::default().3.accepted(), 0);
+    }
+
+    #[test]
+    fn recursive_attribute() {
+        #[derive(Clone, Surprise, Shrink)]
+        #[surprise(recursive(max_depth = 3))]
+        struct Tree {
+            value: u8,
+            #[factor(chance = 1.0)]
+            left: Option<Box<Tree>>,
+            #[factor(min_len = 1, max_len = 2)]
+            children: Vec<Tree>,
+        }
+
+        fn depth(tree: &Tree) -> usize {
+            let left = tree.left.as_deref().map_or(0, depth);
+            let children = tree.children.iter().map(depth).max().unwrap_or(0);
+
+            1 + left.max(children)
+        }
+
+        let factor = TreeSurprise::default();
+        let tree = Tree::generate_with_factor(&mut thread_rng(), &factor);
+        assert_eq!(depth(&tree), 4);
+
+        let minimal = tree.minimize(&factor, |_| true);
+        assert!(minimal.left.is_none());
+        assert!(minimal.children.is_empty());
+
+        #[derive(Surprise)]
+        #[surprise(recursive(max_depth = 2))]
+        #[allow(unused)]
+        enum Expr {
+            Literal(i32),
+            Neg(Box<Expr>),
+            Add(Box<Self>, Box<Self>),
+            Call(Vec<Expr>),
+        }
+
+        let _ = Expr::generate(&mut thread_rng());
+    }
+
+    #[test]
+    fn recursive_leaves() {
+        #[derive(Surprise)]
+        #[surprise(recursive(max_depth = 10, decay = 0.0))]
+        enum Expr {
+            Literal,
+            Neg(Box<Expr>),
+        }
+
+        fn depth(expr: &Expr) -> usize {
+            match expr {
+                Expr::Literal => 1,
+                Expr::Neg(inner) => 1 + depth(inner),
+            }
+        }
+
+        let factor = ExprSurprise::default();
+        assert_eq!(factor.weight_decay, 0.0);
+
+        // Non-leaf variants are only generated at the top level
+        for _ in 0..100 {
+            let expr = Expr::generate_with_factor(&mut thread_rng(), &factor);
+            assert!(depth(&expr) <= 2);
+        }
+
+        #[derive(Surprise)]
+        #[surprise(recursive(max_depth = 3))]
+        #[allow(unused)]
+        enum Node {
+            #[surprise(leaf)]
+            Group(Vec<Node>),
+            Wrap(Option<Box<Node>>),
+        }
+
+        let factor = NodeSurprise::default();
+        assert_eq!(factor.weight_decay, 1.0);
+
+        // `Wrap` is never generated at the maximum depth
+        for _ in 0..100 {
+            let _ = Node::generate_with_factor(&mut thread_rng(), &factor);
+        }
+    }
+
     #[test]
     fn recursive_custom() {
         #[derive(Surprise)]
@@ -317,6 +614,701 @@ mod tests {
         let _ = GenericEnum::<u8, bool, i8>::generate(&mut thread_rng());
     }
 
+    #[test]
+    fn shrink_struct() {
+        #[derive(Clone, Debug, PartialEq, Surprise, Shrink)]
+        struct Named {
+            #[factor(min = -20, max = -5)]
+            a: i32,
+            #[factor(min_len = 2, items(chance = 1.0))]
+            b: Vec<Option<u8>>,
+            #[factor = "ByteSurprise"]
+            c: u8,
+        }
+
+        type ByteSurprise = NumberSurprise<u8>;
+
+        let factor = NamedSurprise::default();
+        let value = Named::generate_with_factor(&mut thread_rng(), &factor);
+        let c = value.c;
+
+        let minimal = value.minimize(&factor, |value| value.b.iter().all(|n| n.is_some()));
+
+        let expected = Named {
+            a: -5,
+            b: vec![Some(0), Some(0)],
+            c,
+        };
+
+        assert_eq!(minimal, expected);
+    }
+
+    #[test]
+    fn shrink_enum() {
+        #[derive(Clone, Debug, PartialEq, Surprise, Shrink)]
+        enum MyEnum {
+            #[weight = 0]
+            A,
+            #[weight = 1]
+            B,
+            #[weight = 1]
+            C(u32, String),
+            #[weight = 1]
+            D { flag: bool },
+        }
+
+        let factor = MyEnumSurprise::default();
+        let value = MyEnum::C(42, "hello".to_owned());
+
+        assert_eq!(value.clone().minimize(&factor, |_| true), MyEnum::B);
+
+        let minimal = value.minimize(&factor, |value| matches!(value, MyEnum::C(n, _) if *n > 9));
+        assert_eq!(minimal, MyEnum::C(10, String::new()));
+
+        let minimal =
+            MyEnum::D { flag: true }.minimize(&factor, |value| matches!(value, MyEnum::D { .. }));
+        assert_eq!(minimal, MyEnum::D { flag: false });
+
+        #[derive(Clone, Debug, PartialEq, Surprise, Shrink)]
+        enum Level {
+            #[weight = 3]
+            Custom(u8),
+            #[weight = 2]
+            High,
+            #[weight = 1]
+            Low,
+        }
+
+        let factor = LevelSurprise::default();
+        let candidates: Vec<_> = Level::Custom(4).shrink(&factor).take(3).collect();
+        assert_eq!(candidates, [Level::Low, Level::High, Level::Custom(0)]);
+        assert_eq!(
+            Level::High.shrink(&factor).collect::<Vec<_>>(),
+            [Level::Low]
+        );
+        assert_eq!(Level::Low.shrink(&factor).count(), 0);
+    }
+
+    #[test]
+    fn fallible_generation() {
+        #[derive(Debug, Surprise)]
+        #[allow(unused)]
+        struct Named {
+            #[factor(items(chance = 1.3))]
+            a: Vec<Option<u8>>,
+            b: MyEnum,
+        }
+
+        #[derive(Debug, Surprise)]
+        #[allow(unused)]
+        enum MyEnum {
+            A,
+            B(u8, NonZeroI32),
+        }
+
+        let mut rng = thread_rng();
+
+        let err = Named::try_generate(&mut rng).unwrap_err();
+        assert_eq!(err.field(), "a.items.chance");
+        assert_eq!(
+            err.to_string(),
+            "invalid surprise factor field `a.items.chance`: 1.3 is not between 0.0 and 1.0"
+        );
+
+        let mut factor = NamedSurprise::default();
+        factor.a.items.chance = 0.5;
+        *factor.b.variants_weight = [0.0, 0.0];
+
+        let err = Named::try_generate_with_factor(&mut rng, &factor).unwrap_err();
+        assert_eq!(err.field(), "b.variants_weight");
+
+        *factor.b.variants_weight = [0.0, 1.0];
+        factor.b.B.1 = NumberSurprise {
+            min: 0,
+            max: 0,
+            ..Default::default()
+        };
+
+        let err = Named::try_generate_with_factor(&mut rng, &factor).unwrap_err();
+        assert_eq!(err.field(), "b.B.1.max");
+
+        factor.b.B.1 = NumberSurprise {
+            min: -5,
+            max: 5,
+            ..Default::default()
+        };
+        assert!(Named::try_generate_with_factor(&mut rng, &factor).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid surprise factor field `a.min_len`")]
+    fn invalid_factor_panics() {
+        #[derive(Surprise)]
+        #[allow(unused)]
+        struct Named {
+            #[factor(min_len = 5, max_len = 2)]
+            a: String,
+        }
+
+        let _ = Named::generate(&mut thread_rng());
+    }
+
+    #[test]
+    fn validate_derive() {
+        #[derive(Surprise)]
+        #[allow(unused)]
+        struct Named {
+            #[factor(max_len = 3)]
+            my_field: Vec<Option<bool>>,
+            b: Tuple,
+            #[factor = "ByteSurprise"]
+            c: u8,
+        }
+
+        #[derive(Surprise)]
+        #[allow(unused)]
+        struct Tuple(u8, MyEnum);
+
+        #[derive(Surprise)]
+        #[allow(unused)]
+        enum MyEnum {
+            A(String),
+            B { b: NonZeroI32 },
+        }
+
+        type ByteSurprise = NumberSurprise<u8>;
+
+        let mut factor = NamedSurprise::default();
+        assert!(factor.validate().is_ok());
+
+        factor.my_field.items.chance = 1.3;
+        factor.b.0 = NumberSurprise {
+            min: 5,
+            max: 1,
+            ..Default::default()
+        };
+        *factor.b.1.variants_weight = [-1.0, 1.0];
+        factor.b.1.A.0.min_len = 200;
+        factor.b.1.B.0 = NumberSurprise {
+            min: 0,
+            max: 0,
+            ..Default::default()
+        };
+        factor.c = NumberSurprise {
+            min: 5,
+            max: 1,
+            ..Default::default()
+        };
+
+        let problems = factor.validate().unwrap_err();
+        let fields: Vec<_> = problems.iter().map(|problem| problem.field()).collect();
+
+        assert_eq!(
+            fields,
+            [
+                "my_field.items.chance",
+                "b.0.min",
+                "b.1.variants_weight",
+                "b.1.A.0.min_len",
+                "b.1.B.0.max",
+            ]
+        );
+
+        #[derive(Surprise)]
+        #[surprise(recursive(max_depth = 3))]
+        #[allow(unused)]
+        struct Tree {
+            #[factor(chance = 2.0)]
+            left: Option<Box<Tree>>,
+        }
+
+        let problems = TreeSurprise::default().validate().unwrap_err();
+        assert_eq!(problems[0].field(), "left.chance");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_factors() {
+        #[derive(Surprise)]
+        #[allow(unused)]
+        struct Named<T: Surprise> {
+            #[factor(max_len = 3)]
+            a: Vec<Option<bool>>,
+            b: (T, MyEnum),
+            c: Unit,
+        }
+
+        #[derive(Surprise)]
+        #[allow(unused)]
+        enum MyEnum {
+            #[weight = 2.0]
+            A(String),
+            B {
+                b: NonZeroI32,
+            },
+        }
+
+        #[derive(Surprise)]
+        struct Unit;
+
+        let json = r#"{ "a": { "max_len": 10 }, "b": [{ "min": 5 }, { "B": [{ "max": 7 }] }] }"#;
+        let factor: NamedSurprise<u8> = serde_json::from_str(json).unwrap();
+
+        assert_eq!(factor.a.min_len, 0);
+        assert_eq!(factor.a.max_len, 10);
+        assert_eq!(factor.a.items.chance, 0.5);
+        assert_eq!(factor.b.0.min, 5);
+        assert_eq!(factor.b.0.max, u8::MAX);
+        assert_eq!(*factor.b.1.variants_weight, [2.0, 0.0]);
+        assert_eq!(factor.b.1.A.0.max_len, 100);
+        assert_eq!(factor.b.1.B.0.max, 7);
+
+        let toml = toml::to_string(&factor).unwrap();
+        let factor: NamedSurprise<u8> = toml::from_str(&toml).unwrap();
+
+        assert_eq!(factor.a.max_len, 10);
+        assert_eq!(factor.b.0.min, 5);
+        assert_eq!(factor.b.1.B.0.max, 7);
+        assert!(factor.validate().is_ok());
+
+        let _ = Named::<u8>::generate_with_factor(&mut thread_rng(), &factor);
+    }
+
+    #[test]
+    fn sized_derive() {
+        #[derive(Surprise)]
+        #[allow(unused)]
+        struct Named {
+            a: HashMap<u8, Vec<f32>>,
+            #[factor(max_len = 50)]
+            b: String,
+            c: MyEnum,
+        }
+
+        #[derive(Surprise)]
+        #[allow(unused)]
+        enum MyEnum {
+            A(Vec<u8>),
+            B { b: String },
+        }
+
+        let factor = surprise_me::size::with_size(4, NamedSurprise::default);
+
+        assert_eq!(factor.a.max_len, 4);
+        assert_eq!(factor.a.values.max_len, 4);
+        assert_eq!(factor.b.max_len, 50);
+        assert_eq!(factor.c.A.0.max_len, 4);
+        assert_eq!(factor.c.B.0.max_len, 4);
+
+        let value = Named::generate_sized(&mut thread_rng(), 2);
+        assert!(value.a.len() <= 2);
+    }
+
+    #[test]
+    fn budget_derive() {
+        #[derive(Surprise)]
+        #[surprise(recursive(max_depth = 5))]
+        struct Tree {
+            #[factor(max_len = 4)]
+            values: Vec<u8>,
+            #[factor(max_len = 10)]
+            children: Vec<Tree>,
+        }
+
+        fn count(tree: &Tree) -> usize {
+            tree.values.len() + tree.children.len() + tree.children.iter().map(count).sum::<usize>()
+        }
+
+        for _ in 0..20 {
+            let tree = Tree::generate_with_budget(&mut thread_rng(), Budget::Elements(50));
+            assert!(count(&tree) <= 50);
+        }
+    }
+
+    #[test]
+    fn enumerate_derive() {
+        #[derive(Clone, Debug, PartialEq, Surprise, Enumerate)]
+        enum Direction {
+            #[weight = 1]
+            North,
+            #[weight = 1]
+            East,
+            #[weight = 0]
+            South,
+            #[weight = 1]
+            West,
+        }
+
+        assert_eq!(
+            Direction::enumerate_all(),
+            [Direction::North, Direction::East, Direction::West]
+        );
+
+        #[derive(Clone, Debug, PartialEq, Surprise, Enumerate)]
+        struct Named {
+            a: bool,
+            #[factor(chance = 1.0)]
+            b: Option<bool>,
+            #[factor(min = 1, max = 2)]
+            c: u8,
+        }
+
+        let values = Named::enumerate_all();
+        assert_eq!(values.len(), 8);
+        assert_eq!(
+            values[..3],
+            [
+                Named {
+                    a: false,
+                    b: Some(false),
+                    c: 1
+                },
+                Named {
+                    a: false,
+                    b: Some(false),
+                    c: 2
+                },
+                Named {
+                    a: false,
+                    b: Some(true),
+                    c: 1
+                },
+            ]
+        );
+
+        #[derive(Clone, Debug, PartialEq, Surprise, Enumerate)]
+        enum Mixed<T: Surprise> {
+            Unit,
+            Tuple(T, bool),
+        }
+
+        let factor = MixedSurprise::<u8> {
+            Tuple: (
+                NumberSurprise {
+                    min: 0,
+                    max: 1,
+                    ..Default::default()
+                },
+                Default::default(),
+            ),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            Mixed::enumerate(&factor).collect::<Vec<_>>(),
+            [
+                Mixed::Unit,
+                Mixed::Tuple(0, false),
+                Mixed::Tuple(0, true),
+                Mixed::Tuple(1, false),
+                Mixed::Tuple(1, true),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "distributions")]
+    fn distribution_derive() {
+        use surprise_me::factors::NumberDistribution;
+
+        #[derive(Surprise)]
+        struct Named {
+            #[factor(edge_chance = 0.0, distribution = NumberDistribution::Normal { mean: 10.0, std_dev: 0.0 })]
+            a: u8,
+            #[factor(min = 0.01, max = 100.0, distribution = NumberDistribution::LogUniform)]
+            b: f64,
+        }
+
+        for _ in 0..20 {
+            let value = Named::generate(&mut thread_rng());
+            assert_eq!(value.a, 10);
+            assert!((0.01..=100.0).contains(&value.b));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "distributions")]
+    fn len_distribution_derive() {
+        use std::collections::HashMap;
+        use surprise_me::factors::LenDistribution;
+
+        #[derive(Surprise)]
+        struct Named {
+            #[factor(min_len = 1, len = LenDistribution::MostlyEmpty { chance: 1.0 })]
+            a: Vec<u8>,
+            #[factor(len = LenDistribution::Geometric { p: 1.0 })]
+            b: String,
+            #[factor(max_len = 3, len = LenDistribution::Geometric { p: 0.0 })]
+            c: HashMap<u16, bool>,
+        }
+
+        for _ in 0..20 {
+            let value = Named::generate(&mut thread_rng());
+            assert!((1..=2).contains(&value.a.len()));
+            assert!(value.b.is_empty());
+            assert!(value.c.len() <= 3);
+        }
+    }
+
+    #[test]
+    fn btree_derive() {
+        use std::collections::{BTreeMap, BTreeSet};
+        use surprise_me::SurpriseKey;
+
+        #[derive(Surprise, PartialEq, Eq, PartialOrd, Ord)]
+        struct Id(#[factor(max = 3)] u8);
+
+        impl SurpriseKey for Id {}
+
+        #[derive(Surprise)]
+        struct Named {
+            #[factor(max_len = 5, keys(max = 9), min_key = Some(3))]
+            a: BTreeMap<u8, String>,
+            #[factor(min_len = 4, max_len = 4, items(min = -2, max = 1))]
+            b: BTreeSet<i8>,
+            #[factor(max_len = 4, max_item = Some(Id(2)))]
+            c: BTreeSet<Id>,
+        }
+
+        for _ in 0..20 {
+            let value = Named::generate(&mut thread_rng());
+            assert!(value.a.len() <= 5);
+            assert!(value.a.keys().all(|key| (3..=9).contains(key)));
+            assert!(value.b.iter().copied().eq(-2..=1));
+            assert!(value.c.iter().all(|id| id.0 <= 2));
+        }
+    }
+
+    #[test]
+    fn item_order_derive() {
+        use std::collections::LinkedList;
+        use surprise_me::factors::{ItemOrder, PermutationSurprise};
+
+        #[derive(Surprise)]
+        struct Named {
+            #[factor(order = ItemOrder::Sorted(|a: &i16, b: &i16| b.cmp(a)))]
+            a: Vec<i16>,
+            #[factor(min_len = 10, max_len = 10, items(max = 9), order = ItemOrder::unique())]
+            b: LinkedList<u8>,
+            #[factor = "PermutationSurprise"]
+            #[factor(min_len = 1, max_len = 5)]
+            c: Vec<u32>,
+        }
+
+        for _ in 0..20 {
+            let mut value = Named::generate(&mut thread_rng());
+            assert!(value.a.windows(2).all(|pair| pair[0] >= pair[1]));
+
+            let mut b: Vec<_> = value.b.into_iter().collect();
+            b.sort_unstable();
+            assert!(b.into_iter().eq(0..10));
+
+            let len = value.c.len() as u32;
+            value.c.sort_unstable();
+            assert!((1..=5).contains(&len));
+            assert!(value.c.into_iter().eq(0..len));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn regex_derive() {
+        #[derive(Surprise)]
+        struct Named {
+            #[factor(regex = "[0-9a-f]{8}")]
+            hash: String,
+            #[factor(inner(regex = "(yes|no)"))]
+            answer: Option<String>,
+        }
+
+        for _ in 0..20 {
+            let value = Named::generate(&mut thread_rng());
+            assert_eq!(value.hash.len(), 8);
+            assert!(value.hash.bytes().all(|b| b.is_ascii_hexdigit()));
+            assert!(!matches!(value.answer, Some(answer) if answer != "yes" && answer != "no"));
+        }
+
+        #[cfg(feature = "serde")]
+        {
+            use surprise_me::factors::StringSurprise;
+
+            let factor = StringSurprise::regex("x{2,3}").unwrap();
+            let json = serde_json::to_string(&factor.regex).unwrap();
+            assert_eq!(json, r#""x{2,3}""#);
+            assert_eq!(
+                serde_json::from_str::<StringSurprise>(r#"{ "regex": "x{2,3}" }"#).unwrap(),
+                factor
+            );
+            assert!(serde_json::from_str::<StringSurprise>(r#"{ "regex": "$" }"#).is_err());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn grammar_derive() {
+        use surprise_me::factors::GrammarSurprise;
+
+        #[derive(Surprise)]
+        struct Config {
+            #[factor = "GrammarSurprise"]
+            #[factor(
+                max_repeat = 3,
+                grammar = r#"
+                config = { entry , "\n" } ;
+                entry  = key , " = " , value ;
+                key    = /[a-z]{1,8}/ ;
+                value  = /[0-9]{1,3}/ | '"' , /[a-z ]*/ , '"' | "true" | "false" ;
+            "#
+            )]
+            text: String,
+        }
+
+        for _ in 0..20 {
+            let config = Config::generate(&mut thread_rng());
+            assert!(config.text.lines().count() <= 3);
+            assert!(config.text.lines().all(|line| line.contains(" = ")));
+        }
+
+        #[cfg(feature = "serde")]
+        {
+            let factor = ConfigSurprise::default();
+            let json = serde_json::to_string(&factor.text).unwrap();
+            assert_eq!(
+                serde_json::from_str::<GrammarSurprise>(&json).unwrap(),
+                factor.text
+            );
+            assert!(serde_json::from_str::<GrammarSurprise>(r#"{ "grammar": "a = b" }"#).is_err());
+        }
+    }
+
+    #[test]
+    fn structured_text_derive() {
+        use surprise_me::factors::{CsvSurprise, JsonSurprise, TomlSurprise};
+
+        #[derive(Surprise)]
+        struct Fixtures {
+            #[factor = "JsonSurprise"]
+            #[factor(max_depth = 1, max_len = 3)]
+            json: String,
+            #[factor = "TomlSurprise"]
+            #[factor(strings(naughty_chance = 0.2))]
+            toml: String,
+            #[factor = "CsvSurprise"]
+            #[factor(min_len = 1, max_len = 1, header = false, max_columns = 1)]
+            csv: String,
+        }
+
+        for _ in 0..20 {
+            let fixtures = Fixtures::generate(&mut thread_rng());
+            let json: serde_json::Value = serde_json::from_str(&fixtures.json).unwrap();
+            assert!(json
+                .as_array()
+                .map(|array| array
+                    .iter()
+                    .all(|value| !value.is_array() && !value.is_object()))
+                .unwrap_or_else(|| json.as_object().unwrap().len() <= 3));
+            assert!(toml::from_str::<toml::Table>(&fixtures.toml).is_ok());
+            assert!(fixtures.csv.ends_with("\r\n"));
+        }
+
+        #[cfg(feature = "serde")]
+        {
+            let factor: FixturesSurprise =
+                serde_json::from_str(r#"{ "csv": { "delimiter": "\t" } }"#).unwrap();
+            assert_eq!(factor.csv.delimiter, '\t');
+            assert_eq!(factor.json.max_depth, 1);
+        }
+    }
+
+    #[test]
+    fn naughty_derive() {
+        use surprise_me::factors::NAUGHTY_STRINGS;
+
+        #[derive(Surprise)]
+        struct Form {
+            #[factor(min_len = 1, max_len = 1, naughty_chance = 1.0)]
+            name: String,
+            #[factor(items(naughty_chance = 0.05))]
+            comments: Vec<String>,
+        }
+
+        for _ in 0..20 {
+            let form = Form::generate(&mut thread_rng());
+            assert!(NAUGHTY_STRINGS.contains(&form.name.as_str()));
+            assert!(form.comments.len() <= 100);
+        }
+    }
+
+    #[test]
+    fn char_class_derive() {
+        use surprise_me::factors::{CharSetSurprise, CharSurprise, WeightedChars};
+
+        #[derive(Surprise)]
+        struct Text {
+            #[factor(CharSurprise::Range { min: 'a', max: 'f' })]
+            hex_letter: char,
+            #[factor = "CharSetSurprise"]
+            #[factor(CharSetSurprise::Alphabet(vec!['x', 'y']))]
+            letter: char,
+            #[factor(char_set = Some(CharSetSurprise::Mixture(vec![
+                WeightedChars { chars: CharSurprise::Punctuation.into(), weight: 1.0 },
+                WeightedChars { chars: CharSurprise::Whitespace.into(), weight: 1.0 },
+            ])))]
+            symbols: String,
+        }
+
+        let factor = TextSurprise::default();
+        assert_eq!(
+            factor.hex_letter,
+            CharSurprise::Range { min: 'a', max: 'f' }
+        );
+
+        for _ in 0..20 {
+            let value = Text::generate_with_factor(&mut thread_rng(), &factor);
+            assert!(('a'..='f').contains(&value.hex_letter));
+            assert!(value.letter == 'x' || value.letter == 'y');
+            assert!(value
+                .symbols
+                .chars()
+                .all(|c| c.is_ascii_punctuation() || c.is_whitespace()));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn char_class_regex_derive() {
+        use surprise_me::factors::CharSetSurprise;
+
+        #[derive(Surprise)]
+        struct Text {
+            #[factor(min_len = 1, char_set = r"\p{Cyrillic}")]
+            cyrillic: String,
+            #[factor = "CharSetSurprise"]
+            #[factor(r"\p{Emoji_Presentation}")]
+            emoji: char,
+        }
+
+        let factor = TextSurprise::default();
+        let cyrillic = factor.cyrillic.char_set.as_ref().unwrap();
+
+        for _ in 0..20 {
+            let value = Text::generate_with_factor(&mut thread_rng(), &factor);
+            assert!(value.cyrillic.chars().all(|c| cyrillic.contains(c)));
+            assert!(factor.emoji.contains(value.emoji));
+        }
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(cyrillic).unwrap();
+            assert_eq!(json, r#"{"Class":"\\p{Cyrillic}"}"#);
+            assert_eq!(
+                &serde_json::from_str::<CharSetSurprise>(&json).unwrap(),
+                cyrillic
+            );
+            assert!(serde_json::from_str::<CharSetSurprise>(r#"{ "Class": "[z-a]" }"#).is_err());
+        }
+    }
+
     // Currently won't compile since defaults for nested enums cannot be set
     // #[test]
     // fn nested() {
@@ -0,0 +1,97 @@
+//! A budget that limits the total size of one generated value.
+//!
+//! Limits like `max_len` apply to each collection on its own so nested
+//! collections can still grow large. While a budget is active, collections
+//! and strings spend it on their items and boxes on their value. Once it
+//! runs out, the remaining collections are generated at their minimum length.
+//!
+//! ```rust
+//! use surprise_me::{budget::Budget, rand, Surprise};
+//!
+//! let mut rng = rand::thread_rng();
+//! let value = Vec::<Vec<String>>::generate_with_budget(&mut rng, Budget::Elements(1000));
+//!
+//! let elements = value.len()
+//!     + value.iter().map(Vec::len).sum::<usize>()
+//!     + value.iter().flatten().map(String::len).sum::<usize>();
+//!
+//! assert!(elements <= 1000);
+//! ```
+
+use std::cell::Cell;
+
+/// The total size that one generated value may take up
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Budget {
+    /// The total amount of items in collections, characters in strings and boxed values
+    Elements(usize),
+    /// The total amount of bytes that collections, strings and boxes allocate
+    /// for their items, ignoring any excess capacity
+    Bytes(usize),
+}
+
+thread_local! {
+    static BUDGET: Cell<Option<Budget>> = const { Cell::new(None) };
+}
+
+/// The budget that is left on this thread, if any
+#[inline]
+pub fn remaining_budget() -> Option<Budget> {
+    BUDGET.with(Cell::get)
+}
+
+/// Runs `f` while the given budget is active, e.g. to generate a value.
+///
+/// The previous budget is restored afterwards, even if `f` panics.
+pub fn with_budget<T>(budget: Budget, f: impl FnOnce() -> T) -> T {
+    struct Restore(Option<Budget>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            BUDGET.with(|cell| cell.set(self.0));
+        }
+    }
+
+    let _restore = Restore(BUDGET.with(|cell| cell.replace(Some(budget))));
+
+    f()
+}
+
+/// Limits the length of a collection to what the active budget can afford,
+/// but not below `min_len`, and spends the budget on it.
+///
+/// `item_size` is the amount of bytes of each item.
+pub fn spend_len(len: usize, min_len: usize, item_size: usize) -> usize {
+    BUDGET.with(|cell| {
+        let Some(budget) = cell.get() else {
+            return len;
+        };
+
+        let (len, remaining) = match budget {
+            Budget::Elements(remaining) => {
+                let len = len.min(remaining).max(min_len);
+
+                (len, Budget::Elements(remaining.saturating_sub(len)))
+            }
+            Budget::Bytes(remaining) => {
+                let affordable = remaining.checked_div(item_size).unwrap_or(usize::MAX);
+                let len = len.min(affordable).max(min_len);
+                let spent = len.saturating_mul(item_size);
+
+                (len, Budget::Bytes(remaining.saturating_sub(spent)))
+            }
+        };
+
+        cell.set(Some(remaining));
+
+        len
+    })
+}
+
+/// Spends the active budget on a boxed value of `size` bytes.
+///
+/// Boxes can't be left out so the budget is only decreased.
+#[inline]
+pub fn spend_box(size: usize) {
+    spend_len(1, 1, size);
+}
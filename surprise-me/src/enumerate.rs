@@ -0,0 +1,128 @@
+use crate::{error::check_chance, Surprise, SurpriseFactor};
+
+/// Trait to iterate over all values that a surprise factor can generate.
+///
+/// Small types like `bool`, fieldless enums or `Option<u8>` only have a handful
+/// of values, so instead of sampling them randomly, every single one can be tested.
+///
+/// Values are enumerated in order: `false` before `true`, `None` before `Some`,
+/// numbers from `min` to `max` and shorter collections before longer ones.
+/// Enumeration is lazy, so bounds that allow too many values to ever finish,
+/// e.g. the default `u64` factor, are only a problem when the iterator is exhausted.
+/// Types without a meaningful order like floats, chars, strings or maps do not implement
+/// this trait.
+///
+/// # Derive macro
+///
+/// Structs enumerate all combinations of their fields' values with the first field
+/// changing slowest. Enums enumerate the values of every variant with non-zero weight
+/// in declaration order.
+///
+/// All fields must implement [`Clone`] and [`Enumerate`]. Fields with a custom
+/// surprise factor and recursive types are not supported.
+///
+/// ```rust
+/// # use surprise_me::{Enumerate, Surprise};
+/// #
+/// #[derive(Clone, Debug, PartialEq, Surprise, Enumerate)]
+/// enum Light {
+///     #[weight = 1]
+///     Off,
+///     #[weight = 2]
+///     On { #[factor(min = 1, max = 3)] level: u8 },
+///     #[weight = 0]
+///     Broken,
+/// }
+///
+/// assert_eq!(
+///     Light::enumerate_all(),
+///     [
+///         Light::Off,
+///         Light::On { level: 1 },
+///         Light::On { level: 2 },
+///         Light::On { level: 3 },
+///     ]
+/// );
+/// ```
+pub trait Enumerate: Surprise {
+    /// Returns an iterator over all values that can be generated by the given surprise factor.
+    fn enumerate<'a>(factor: &'a SurpriseFactor<Self>) -> Enumerator<'a, Self>
+    where
+        Self: 'a;
+
+    /// Returns all values that can be generated by the default surprise factor.
+    fn enumerate_all() -> Vec<Self>
+    where
+        SurpriseFactor<Self>: Default,
+    {
+        Self::enumerate(&SurpriseFactor::<Self>::default()).collect()
+    }
+}
+
+/// Iterator over enumerated values, see [`Enumerate::enumerate`].
+pub type Enumerator<'a, T> = Box<dyn Iterator<Item = T> + 'a>;
+
+/// All pairs of values of `first` and `rest` with the first value changing slowest.
+///
+/// `rest` is called again for every value of `first`.
+pub fn product<'a, A, B, F>(first: Enumerator<'a, A>, rest: F) -> Enumerator<'a, (A, B)>
+where
+    A: Clone + 'a,
+    B: 'a,
+    F: Fn() -> Enumerator<'a, B> + 'a,
+{
+    Box::new(first.flat_map(move |a| rest().map(move |b| (a.clone(), b))))
+}
+
+/// All sequences of exactly `len` items in lexicographic order.
+pub(crate) fn sequences<'a, T, F>(len: usize, items: F) -> Enumerator<'a, Vec<T>>
+where
+    T: Clone + 'a,
+    F: Fn() -> Enumerator<'a, T> + Clone + 'a,
+{
+    if len == 0 {
+        return Box::new(std::iter::once(Vec::new()));
+    }
+
+    let prefixes = sequences(len - 1, items.clone());
+
+    Box::new(prefixes.flat_map(move |prefix| {
+        items().map(move |item| {
+            let mut sequence = prefix.clone();
+            sequence.push(item);
+
+            sequence
+        })
+    }))
+}
+
+/// All sequences with a length between `min_len` and `max_len`, shorter ones first.
+pub(crate) fn sequences_between<'a, T>(
+    min_len: usize,
+    max_len: usize,
+    factor: &'a SurpriseFactor<T>,
+) -> Enumerator<'a, Vec<T>>
+where
+    T: Enumerate + Clone + 'a,
+{
+    Box::new((min_len..=max_len).flat_map(move |len| sequences(len, move || T::enumerate(factor))))
+}
+
+/// The outcomes of a coin flip with the given `chance` for `true`, `false` first.
+///
+/// Invalid chances have no outcomes.
+pub(crate) fn outcomes(chance: f64) -> Enumerator<'static, bool> {
+    if check_chance(chance).is_err() {
+        return Box::new(std::iter::empty());
+    }
+
+    Box::new([false, true].into_iter().filter(
+        move |&outcome| {
+            if outcome {
+                chance > 0.0
+            } else {
+                chance < 1.0
+            }
+        },
+    ))
+}
@@ -0,0 +1,115 @@
+use std::{
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+};
+
+use rand::{distributions::WeightedIndex, Rng};
+
+/// Error for surprise factors whose settings make the generation impossible,
+/// e.g. a `min` that is greater than the `max`.
+///
+/// The error names the invalid field of the surprise factor. For nested surprise
+/// factors, the field is a path separated by dots, e.g. `my_field.items.chance`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SurpriseError {
+    field: String,
+    reason: String,
+}
+
+impl SurpriseError {
+    /// Creates an error for the given field of a surprise factor
+    pub fn new(field: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Prepends the field of an outer surprise factor to the path of the invalid field
+    pub fn in_field(mut self, field: &str) -> Self {
+        self.field = format!("{field}.{}", self.field);
+
+        self
+    }
+
+    /// The path of the invalid field
+    #[inline]
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    /// Describes why the field is invalid
+    #[inline]
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl Display for SurpriseError {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "invalid surprise factor field `{}`: {}",
+            self.field, self.reason
+        )
+    }
+}
+
+impl Error for SurpriseError {}
+
+/// Checks that the field `chance` is a probability
+pub(crate) fn check_chance(chance: f64) -> Result<(), SurpriseError> {
+    check_probability("chance", chance)
+}
+
+/// Checks that the given field is a probability
+pub(crate) fn check_probability(field: &str, chance: f64) -> Result<(), SurpriseError> {
+    if (0.0..=1.0).contains(&chance) {
+        Ok(())
+    } else {
+        let reason = format!("{chance} is not between 0.0 and 1.0");
+
+        Err(SurpriseError::new(field, reason))
+    }
+}
+
+/// Checks that the field `min_len` is not greater than `max_len`
+pub(crate) fn check_len(min_len: usize, max_len: usize) -> Result<(), SurpriseError> {
+    if min_len <= max_len {
+        Ok(())
+    } else {
+        let reason = format!("{min_len} is greater than `max_len` ({max_len})");
+
+        Err(SurpriseError::new("min_len", reason))
+    }
+}
+
+/// Checks the field `variants_weight` of derived enum factors
+pub fn check_weights(weights: &[f64]) -> Result<(), SurpriseError> {
+    weighted_index(weights).map(drop)
+}
+
+/// Checks the field `weight_decay` of derived recursive enum factors
+pub fn check_weight_decay(weight_decay: f64) -> Result<(), SurpriseError> {
+    if weight_decay >= 0.0 {
+        Ok(())
+    } else {
+        let reason = format!("{weight_decay} is not a non-negative number");
+
+        Err(SurpriseError::new("weight_decay", reason))
+    }
+}
+
+/// Returns `true` with the probability `chance` which is read from a field named `chance`
+pub fn try_gen_bool<R: Rng + ?Sized>(rng: &mut R, chance: f64) -> Result<bool, SurpriseError> {
+    check_chance(chance)?;
+
+    Ok(rng.gen_bool(chance))
+}
+
+/// Creates the distribution over the field `variants_weight` of derived enum factors
+pub fn weighted_index(weights: &[f64]) -> Result<WeightedIndex<f64>, SurpriseError> {
+    WeightedIndex::new(weights)
+        .map_err(|err| SurpriseError::new("variants_weight", err.to_string()))
+}
@@ -1,10 +1,27 @@
 use std::fmt::{Debug, Formatter, Result as FmtResult};
 
-use rand::prelude::Distribution;
+use rand::{prelude::Distribution, Rng};
 
-use crate::{Surprise, SurpriseFactor};
+use crate::{
+    enumerate::sequences,
+    shrink::shrink_items,
+    validate::{check_nested, nested_problems},
+    Enumerate, Enumerator, Shrink, Shrinker, Surprise, SurpriseError, SurpriseFactor, SurpriseKey,
+    TryDistribution, Validate,
+};
 
 /// The surprise factor of arrays
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        default,
+        bound(
+            serialize = "SurpriseFactor<T>: serde::Serialize",
+            deserialize = "SurpriseFactor<T>: serde::Deserialize<'de> + Default"
+        )
+    )
+)]
 pub struct ArraySurprise<T: Surprise, const N: usize> {
     /// The surprise factor of items
     pub items: SurpriseFactor<T>,
@@ -12,12 +29,64 @@ pub struct ArraySurprise<T: Surprise, const N: usize> {
 
 impl<T: Surprise, const N: usize> Surprise for [T; N] {
     type Factor = ArraySurprise<T, N>;
+
+    delegate_to_factor!();
 }
 
+impl<T: Surprise + Ord, const N: usize> SurpriseKey for [T; N] {}
+
 impl<T: Surprise, const N: usize> Distribution<[T; N]> for ArraySurprise<T, N> {
     #[inline]
-    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> [T; N] {
-        [(); N].map(|_| self.items.sample(rng))
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> [T; N] {
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<T: Surprise, const N: usize> TryDistribution<[T; N]> for ArraySurprise<T, N> {
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<[T; N], SurpriseError> {
+        check_nested::<T>("items", &self.items)?;
+
+        let items: Vec<T> = (0..N)
+            .map(|_| {
+                T::try_generate_with_valid_factor(rng, &self.items)
+                    .map_err(|err| err.in_field("items"))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(items
+            .try_into()
+            .unwrap_or_else(|_| unreachable!("exactly `N` items are generated")))
+    }
+}
+
+impl<T: Surprise, const N: usize> Validate for ArraySurprise<T, N> {
+    #[inline]
+    fn problems(&self) -> Vec<SurpriseError> {
+        nested_problems::<T>("items", &self.items).collect()
+    }
+}
+
+impl<T: Shrink + Clone, const N: usize> Shrink for [T; N] {
+    /// Shrinks the items
+    #[inline]
+    fn shrink<'a>(&'a self, factor: &'a ArraySurprise<T, N>) -> Shrinker<'a, Self> {
+        let shrunk = shrink_items::<_, _, Vec<T>>(self.iter(), &factor.items);
+
+        Box::new(shrunk.filter_map(|items| items.try_into().ok()))
+    }
+}
+
+impl<T: Enumerate + Clone, const N: usize> Enumerate for [T; N] {
+    /// Enumerates all combinations of items in lexicographic order
+    #[inline]
+    fn enumerate<'a>(factor: &'a ArraySurprise<T, N>) -> Enumerator<'a, Self>
+    where
+        Self: 'a,
+    {
+        let arrays = sequences(N, move || T::enumerate(&factor.items));
+
+        Box::new(arrays.filter_map(|items| items.try_into().ok()))
     }
 }
 
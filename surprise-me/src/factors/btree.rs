@@ -0,0 +1,709 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{Debug, Formatter, Result as FmtResult},
+    mem::size_of,
+};
+
+use rand::{prelude::Distribution, seq::SliceRandom, Rng};
+
+use crate::{
+    budget::spend_len,
+    error::check_len,
+    shrink::shrink_len,
+    size::current_size,
+    validate::{check_nested, nested_problems},
+    Shrink, Shrinker, Surprise, SurpriseError, SurpriseFactor, SurpriseKey, TryDistribution,
+    Validate,
+};
+
+use super::{collections::try_fill_distinct, LenDistribution};
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+/// How the keys of ordered maps and sets are placed relative to each other
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KeyLayout {
+    /// Keys are generated independently of each other
+    #[default]
+    Scattered,
+    /// Keys cluster around a few random centers.
+    ///
+    /// For each key, `tightness` candidates are generated and the one that is
+    /// ordered closest to a random center, among those that are not taken yet,
+    /// is picked, so higher values result in tighter clusters.
+    Clustered {
+        /// How many centers the keys cluster around, must be at least `1`
+        clusters: usize,
+        /// How many candidates each key is picked from, must be at least `1`
+        tightness: usize,
+    },
+    /// Keys are consecutive, starting at a random key, e.g. `4, 5, 6` for integers,
+    /// which generates dense maps.
+    ///
+    /// Keys continue below the start once there is no further key above it within
+    /// the bounds. This requires keys with a [`successor`](SurpriseKey::successor) and
+    /// [`predecessor`](SurpriseKey::predecessor) such as integers, otherwise there is
+    /// only a single key.
+    Contiguous,
+}
+
+impl KeyLayout {
+    /// Checks that clusters and candidates are not zero
+    fn problem(self) -> Option<SurpriseError> {
+        match self {
+            Self::Clustered { clusters: 0, .. } => {
+                Some(SurpriseError::new("layout", "clusters must be at least 1"))
+            }
+            Self::Clustered { tightness: 0, .. } => {
+                Some(SurpriseError::new("layout", "tightness must be at least 1"))
+            }
+            Self::Scattered | Self::Clustered { .. } | Self::Contiguous => None,
+        }
+    }
+}
+
+/// Checks that the lower bound of keys is not greater than the upper bound
+fn check_key_range<K: Ord>(
+    field: &str,
+    min: Option<&K>,
+    max: Option<&K>,
+) -> Result<(), SurpriseError> {
+    match (min, max) {
+        (Some(min), Some(max)) if min > max => Err(SurpriseError::new(
+            field,
+            "the lower bound is greater than the upper bound",
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that the bounds are in order, and that the surprise factor of keys is valid
+/// and can generate keys between them
+fn check_keys<K: SurpriseKey>(
+    field: &str,
+    factor: &SurpriseFactor<K>,
+    min: Option<&K>,
+    max: Option<&K>,
+) -> Vec<SurpriseError> {
+    let problems: Vec<_> = nested_problems::<K>(field, factor)
+        .chain(check_key_range(field, min, max).err())
+        .collect();
+
+    if !problems.is_empty() {
+        return problems;
+    }
+
+    match K::bounded_factor(factor, min, max) {
+        Some(bounded) if !K::factor_problems(&bounded).is_empty() => {
+            let reason = "the surprise factor cannot generate any key between the bounds";
+
+            vec![SurpriseError::new(field, reason)]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Generates keys through their surprise factor within a range and according to a layout
+struct KeyGenerator<'a, K: SurpriseKey> {
+    field: &'static str,
+    factor: &'a SurpriseFactor<K>,
+    /// The surprise factor narrowed to the range, if the keys support it
+    bounded: Option<SurpriseFactor<K>>,
+    min: Option<&'a K>,
+    max: Option<&'a K>,
+    centers: Vec<K>,
+    tightness: usize,
+}
+
+impl<'a, K: SurpriseKey> KeyGenerator<'a, K> {
+    fn try_new<R: Rng + ?Sized>(
+        rng: &mut R,
+        field: &'static str,
+        factor: &'a SurpriseFactor<K>,
+        (min, max): (&'a Option<K>, &'a Option<K>),
+        layout: KeyLayout,
+    ) -> Result<Self, SurpriseError> {
+        if let Some(problem) = layout.problem() {
+            return Err(problem);
+        }
+
+        if let Some(problem) = check_keys(field, factor, min.as_ref(), max.as_ref()).pop() {
+            return Err(problem);
+        }
+
+        let mut generator = Self {
+            field,
+            factor,
+            bounded: K::bounded_factor(factor, min.as_ref(), max.as_ref()),
+            min: min.as_ref(),
+            max: max.as_ref(),
+            centers: Vec::new(),
+            tightness: 1,
+        };
+
+        if let KeyLayout::Clustered {
+            clusters,
+            tightness,
+        } = layout
+        {
+            generator.tightness = tightness;
+            generator.centers = (0..clusters)
+                .map(|_| generator.try_gen_unbounded(rng))
+                .collect::<Result<_, _>>()?;
+        }
+
+        Ok(generator)
+    }
+
+    /// Generates a key through the narrowed surprise factor if possible, which may still
+    /// be out of range otherwise
+    fn try_gen_unbounded<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<K, SurpriseError> {
+        let factor = self.bounded.as_ref().unwrap_or(self.factor);
+
+        K::try_generate_with_factor(rng, factor).map_err(|err| err.in_field(self.field))
+    }
+
+    fn contains(&self, key: &K) -> bool {
+        !matches!(self.min, Some(min) if key < min) && !matches!(self.max, Some(max) if key > max)
+    }
+
+    /// Generates a key or `None` if no candidate was within the range.
+    ///
+    /// Clustered keys skip candidates that are `taken` already, otherwise
+    /// frequent candidates such as edge cases would be picked over and over.
+    fn try_gen<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        taken: impl Fn(&K) -> bool,
+    ) -> Result<Option<K>, SurpriseError> {
+        let Some(center) = self.centers.choose(rng) else {
+            let key = self.try_gen_unbounded(rng)?;
+
+            return Ok(self.contains(&key).then_some(key));
+        };
+
+        // The closest candidates on either side of the center
+        let mut below: Option<K> = None;
+        let mut above: Option<K> = None;
+
+        for _ in 0..self.tightness {
+            let key = self.try_gen_unbounded(rng)?;
+
+            if !self.contains(&key) || taken(&key) {
+                continue;
+            }
+
+            if key < *center {
+                if !matches!(below, Some(ref below) if *below >= key) {
+                    below = Some(key);
+                }
+            } else if !matches!(above, Some(ref above) if *above <= key) {
+                above = Some(key);
+            }
+        }
+
+        let key = match (below, above) {
+            (Some(below), Some(above)) => Some(if rng.gen() { below } else { above }),
+            (below, above) => below.or(above),
+        };
+
+        Ok(key)
+    }
+
+    /// Generates up to `len` consecutive keys around a random start within the range
+    fn try_gen_contiguous<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        len: usize,
+    ) -> Result<Vec<K>, SurpriseError> {
+        let mut keys = Vec::with_capacity(len);
+
+        if len == 0 {
+            return Ok(keys);
+        }
+
+        let Some(start) = self.try_gen(rng, |_| false)? else {
+            return Ok(keys);
+        };
+
+        keys.push(start);
+
+        for step in [K::successor, K::predecessor] {
+            let mut last = 0;
+
+            while keys.len() < len {
+                let Some(key) = step(&keys[last]).filter(|key| self.contains(key)) else {
+                    break;
+                };
+
+                keys.push(key);
+                last = keys.len() - 1;
+            }
+        }
+
+        Ok(keys)
+    }
+}
+
+/// Fails if fewer than `min_len` contiguous `what` were generated
+fn check_contiguous(len: usize, min_len: usize, what: &str) -> Result<(), SurpriseError> {
+    if len >= min_len {
+        return Ok(());
+    }
+
+    let reason = format!(
+        "only {len} contiguous {what} could be generated, the bounds may be too narrow \
+        or the {what} may have no successors"
+    );
+
+    Err(SurpriseError::new("min_len", reason))
+}
+
+/// The surprise factor of [`BTreeMap`]
+///
+/// Keys are generated until the map contains the chosen amount of distinct keys,
+/// see [`HashMapSurprise`](super::HashMapSurprise). Keys such as integers narrow
+/// their surprise factor to `min_key` and `max_key`, see [`SurpriseKey::bounded_factor`].
+/// Keys of other types outside of the bounds are discarded so `keys` should generate
+/// keys within them often enough.
+///
+/// Dense maps are generated with [`KeyLayout::Contiguous`]:
+///
+/// ```rust
+/// # use surprise_me::{factors::KeyLayout, Surprise};
+/// # use std::collections::BTreeMap;
+/// #
+/// #[derive(Surprise)]
+/// struct Dense {
+///     #[factor(min_len = 10, max_len = 10, min_key = Some(0), layout = KeyLayout::Contiguous)]
+///     map: BTreeMap<u8, bool>,
+/// }
+///
+/// let dense = Dense::generate(&mut surprise_me::rand::thread_rng());
+/// let first = *dense.map.keys().next().unwrap();
+/// assert!(dense.map.keys().copied().eq(first..=first + 9));
+/// ```
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        default,
+        bound(
+            serialize = "K: serde::Serialize, SurpriseFactor<K>: serde::Serialize, SurpriseFactor<V>: serde::Serialize",
+            deserialize = "K: serde::Deserialize<'de>, SurpriseFactor<K>: serde::Deserialize<'de> + Default, SurpriseFactor<V>: serde::Deserialize<'de> + Default"
+        )
+    )
+)]
+pub struct BTreeMapSurprise<K: SurpriseKey, V: Surprise> {
+    /// The minimum amount of items in the generated map
+    pub min_len: usize,
+    /// The maximum amount of items in the generated map.
+    ///
+    /// Defaults to the [current size](crate::size::current_size).
+    pub max_len: usize,
+    /// How lengths are distributed between `min_len` and `max_len`, defaults to uniformly
+    pub len: LenDistribution,
+    /// The surprise factor for keys
+    pub keys: SurpriseFactor<K>,
+    /// The surprise factor for values
+    pub values: SurpriseFactor<V>,
+    /// The smallest key that may be generated, defaults to `None`
+    pub min_key: Option<K>,
+    /// The largest key that may be generated, defaults to `None`
+    pub max_key: Option<K>,
+    /// How keys are placed relative to each other, defaults to scattered
+    pub layout: KeyLayout,
+}
+
+impl<K, V> Surprise for BTreeMap<K, V>
+where
+    K: SurpriseKey,
+    V: Surprise,
+{
+    type Factor = BTreeMapSurprise<K, V>;
+
+    delegate_to_factor!();
+}
+
+impl<K: SurpriseKey, V: Surprise + Ord> SurpriseKey for BTreeMap<K, V> {}
+
+impl<K: SurpriseKey, V: Surprise> BTreeMapSurprise<K, V> {
+    #[allow(clippy::len_without_is_empty)]
+    /// Returns a random length within the `min_len` and `max_len` values, distributed by `len`
+    pub fn len<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
+        self.try_len(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns a random length within the `min_len` and `max_len` values, distributed by `len`,
+    /// or an error if `min_len` is greater than `max_len` or `len` is invalid
+    pub fn try_len<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<usize, SurpriseError> {
+        self.len.try_sample(rng, self.min_len, self.max_len)
+    }
+}
+
+impl<K, V> Distribution<BTreeMap<K, V>> for BTreeMapSurprise<K, V>
+where
+    K: SurpriseKey,
+    V: Surprise,
+{
+    #[inline]
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> BTreeMap<K, V> {
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<K, V> TryDistribution<BTreeMap<K, V>> for BTreeMapSurprise<K, V>
+where
+    K: SurpriseKey,
+    V: Surprise,
+{
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<BTreeMap<K, V>, SurpriseError> {
+        let len = spend_len(self.try_len(rng)?, self.min_len, size_of::<(K, V)>());
+        let range = (&self.min_key, &self.max_key);
+        let keys = KeyGenerator::try_new(rng, "keys", &self.keys, range, self.layout)?;
+        let mut map = BTreeMap::new();
+        check_nested::<V>("values", &self.values)?;
+
+        if let KeyLayout::Contiguous = self.layout {
+            let keys = keys.try_gen_contiguous(rng, len)?;
+            check_contiguous(keys.len(), self.min_len, "keys")?;
+
+            for key in keys {
+                let value = V::try_generate_with_valid_factor(rng, &self.values)
+                    .map_err(|err| err.in_field("values"))?;
+
+                map.insert(key, value);
+            }
+
+            return Ok(map);
+        }
+
+        try_fill_distinct(rng, len, self.min_len, "keys", |rng| {
+            let Some(key) = keys.try_gen(rng, |key| map.contains_key(key))? else {
+                return Ok(false);
+            };
+
+            let value = V::try_generate_with_valid_factor(rng, &self.values)
+                .map_err(|err| err.in_field("values"))?;
+
+            Ok(map.insert(key, value).is_none())
+        })?;
+
+        Ok(map)
+    }
+}
+
+impl<K: SurpriseKey, V: Surprise> Validate for BTreeMapSurprise<K, V> {
+    fn problems(&self) -> Vec<SurpriseError> {
+        check_len(self.min_len, self.max_len)
+            .err()
+            .into_iter()
+            .chain(self.len.problem())
+            .chain(check_keys::<K>(
+                "keys",
+                &self.keys,
+                self.min_key.as_ref(),
+                self.max_key.as_ref(),
+            ))
+            .chain(nested_problems::<V>("values", &self.values))
+            .chain(self.layout.problem())
+            .collect()
+    }
+}
+
+impl<K, V> Shrink for BTreeMap<K, V>
+where
+    K: SurpriseKey + Clone,
+    V: Shrink + Clone,
+{
+    /// Shrinks toward `min_len` entries, then shrinks the values.
+    ///
+    /// Keys are not shrunk because that could merge entries.
+    #[inline]
+    fn shrink<'a>(&'a self, factor: &'a BTreeMapSurprise<K, V>) -> Shrinker<'a, Self> {
+        let entries = self.iter().map(|(key, value)| (key.clone(), value.clone()));
+        let removed = shrink_len(entries, self.len(), factor.min_len);
+
+        let shrunk = self.iter().flat_map(move |(key, value)| {
+            value.shrink(&factor.values).map(move |value| {
+                let mut map = self.clone();
+                map.insert(key.clone(), value);
+
+                map
+            })
+        });
+
+        Box::new(removed.chain(shrunk))
+    }
+}
+
+impl<K, V> Clone for BTreeMapSurprise<K, V>
+where
+    K: SurpriseKey + Clone,
+    V: Surprise,
+    SurpriseFactor<K>: Clone,
+    SurpriseFactor<V>: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            min_len: self.min_len,
+            max_len: self.max_len,
+            len: self.len.clone(),
+            keys: self.keys.clone(),
+            values: self.values.clone(),
+            min_key: self.min_key.clone(),
+            max_key: self.max_key.clone(),
+            layout: self.layout,
+        }
+    }
+}
+
+impl<K, V> Debug for BTreeMapSurprise<K, V>
+where
+    K: SurpriseKey + Debug,
+    V: Surprise,
+    SurpriseFactor<K>: Debug,
+    SurpriseFactor<V>: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("BTreeMapSurprise")
+            .field("min_len", &self.min_len)
+            .field("max_len", &self.max_len)
+            .field("len", &self.len)
+            .field("keys", &self.keys)
+            .field("values", &self.values)
+            .field("min_key", &self.min_key)
+            .field("max_key", &self.max_key)
+            .field("layout", &self.layout)
+            .finish()
+    }
+}
+
+impl<K, V> Default for BTreeMapSurprise<K, V>
+where
+    K: SurpriseKey,
+    V: Surprise,
+    SurpriseFactor<K>: Default,
+    SurpriseFactor<V>: Default,
+{
+    #[inline]
+    fn default() -> Self {
+        Self {
+            min_len: 0,
+            max_len: current_size(),
+            len: LenDistribution::Uniform,
+            keys: Default::default(),
+            values: Default::default(),
+            min_key: None,
+            max_key: None,
+            layout: KeyLayout::Scattered,
+        }
+    }
+}
+
+impl<K, V> PartialEq for BTreeMapSurprise<K, V>
+where
+    K: SurpriseKey + PartialEq,
+    V: Surprise,
+    SurpriseFactor<K>: PartialEq,
+    SurpriseFactor<V>: PartialEq,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.min_len == other.min_len
+            && self.max_len == other.max_len
+            && self.len == other.len
+            && self.keys == other.keys
+            && self.values == other.values
+            && self.min_key == other.min_key
+            && self.max_key == other.max_key
+            && self.layout == other.layout
+    }
+}
+
+/// The surprise factor of [`BTreeSet`]
+///
+/// Items are generated like the keys of a [`BTreeMapSurprise`].
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        default,
+        bound(
+            serialize = "T: serde::Serialize, SurpriseFactor<T>: serde::Serialize",
+            deserialize = "T: serde::Deserialize<'de>, SurpriseFactor<T>: serde::Deserialize<'de> + Default"
+        )
+    )
+)]
+pub struct BTreeSetSurprise<T: SurpriseKey> {
+    /// The minimum amount of items in the generated set
+    pub min_len: usize,
+    /// The maximum amount of items in the generated set.
+    ///
+    /// Defaults to the [current size](crate::size::current_size).
+    pub max_len: usize,
+    /// How lengths are distributed between `min_len` and `max_len`, defaults to uniformly
+    pub len: LenDistribution,
+    /// The surprise factor for the items
+    pub items: SurpriseFactor<T>,
+    /// The smallest item that may be generated, defaults to `None`
+    pub min_item: Option<T>,
+    /// The largest item that may be generated, defaults to `None`
+    pub max_item: Option<T>,
+    /// How items are placed relative to each other, defaults to scattered
+    pub layout: KeyLayout,
+}
+
+impl<T: SurpriseKey> Surprise for BTreeSet<T> {
+    type Factor = BTreeSetSurprise<T>;
+
+    delegate_to_factor!();
+}
+
+impl<T: SurpriseKey> SurpriseKey for BTreeSet<T> {}
+
+impl<T: SurpriseKey> BTreeSetSurprise<T> {
+    #[allow(clippy::len_without_is_empty)]
+    /// Returns a random length within the `min_len` and `max_len` values, distributed by `len`
+    pub fn len<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
+        self.try_len(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns a random length within the `min_len` and `max_len` values, distributed by `len`,
+    /// or an error if `min_len` is greater than `max_len` or `len` is invalid
+    pub fn try_len<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<usize, SurpriseError> {
+        self.len.try_sample(rng, self.min_len, self.max_len)
+    }
+}
+
+impl<T: SurpriseKey> Distribution<BTreeSet<T>> for BTreeSetSurprise<T> {
+    #[inline]
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> BTreeSet<T> {
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<T: SurpriseKey> TryDistribution<BTreeSet<T>> for BTreeSetSurprise<T> {
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<BTreeSet<T>, SurpriseError> {
+        let len = spend_len(self.try_len(rng)?, self.min_len, size_of::<T>());
+        let range = (&self.min_item, &self.max_item);
+        let items = KeyGenerator::try_new(rng, "items", &self.items, range, self.layout)?;
+        let mut set = BTreeSet::new();
+
+        if let KeyLayout::Contiguous = self.layout {
+            let items = items.try_gen_contiguous(rng, len)?;
+            check_contiguous(items.len(), self.min_len, "items")?;
+            set.extend(items);
+
+            return Ok(set);
+        }
+
+        try_fill_distinct(rng, len, self.min_len, "items", |rng| {
+            let item = items.try_gen(rng, |item| set.contains(item))?;
+
+            Ok(item.is_some_and(|item| set.insert(item)))
+        })?;
+
+        Ok(set)
+    }
+}
+
+impl<T: SurpriseKey> Validate for BTreeSetSurprise<T> {
+    fn problems(&self) -> Vec<SurpriseError> {
+        check_len(self.min_len, self.max_len)
+            .err()
+            .into_iter()
+            .chain(self.len.problem())
+            .chain(check_keys::<T>(
+                "items",
+                &self.items,
+                self.min_item.as_ref(),
+                self.max_item.as_ref(),
+            ))
+            .chain(self.layout.problem())
+            .collect()
+    }
+}
+
+impl<T: SurpriseKey + Clone> Shrink for BTreeSet<T> {
+    /// Shrinks toward `min_len` items.
+    ///
+    /// Items are not shrunk because that could merge them.
+    #[inline]
+    fn shrink<'a>(&'a self, factor: &'a BTreeSetSurprise<T>) -> Shrinker<'a, Self> {
+        shrink_len(self.iter().cloned(), self.len(), factor.min_len)
+    }
+}
+
+impl<T> Clone for BTreeSetSurprise<T>
+where
+    T: SurpriseKey + Clone,
+    SurpriseFactor<T>: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            min_len: self.min_len,
+            max_len: self.max_len,
+            len: self.len.clone(),
+            items: self.items.clone(),
+            min_item: self.min_item.clone(),
+            max_item: self.max_item.clone(),
+            layout: self.layout,
+        }
+    }
+}
+
+impl<T> Debug for BTreeSetSurprise<T>
+where
+    T: SurpriseKey + Debug,
+    SurpriseFactor<T>: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("BTreeSetSurprise")
+            .field("min_len", &self.min_len)
+            .field("max_len", &self.max_len)
+            .field("len", &self.len)
+            .field("items", &self.items)
+            .field("min_item", &self.min_item)
+            .field("max_item", &self.max_item)
+            .field("layout", &self.layout)
+            .finish()
+    }
+}
+
+impl<T> Default for BTreeSetSurprise<T>
+where
+    T: SurpriseKey,
+    SurpriseFactor<T>: Default,
+{
+    #[inline]
+    fn default() -> Self {
+        Self {
+            min_len: 0,
+            max_len: current_size(),
+            len: LenDistribution::Uniform,
+            items: Default::default(),
+            min_item: None,
+            max_item: None,
+            layout: KeyLayout::Scattered,
+        }
+    }
+}
+
+impl<T> PartialEq for BTreeSetSurprise<T>
+where
+    T: SurpriseKey + PartialEq,
+    SurpriseFactor<T>: PartialEq,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.min_len == other.min_len
+            && self.max_len == other.max_len
+            && self.len == other.len
+            && self.items == other.items
+            && self.min_item == other.min_item
+            && self.max_item == other.max_item
+            && self.layout == other.layout
+    }
+}
@@ -0,0 +1,365 @@
+use rand::{
+    distributions::{Alphanumeric, Standard, WeightedIndex},
+    prelude::Distribution,
+    seq::SliceRandom,
+    Rng,
+};
+
+use crate::{Shrink, Shrinker, Surprise, SurpriseError, SurpriseKey, TryDistribution, Validate};
+
+use super::CharClass;
+
+/// Characters with the Unicode property `White_Space`
+const WHITESPACE: [char; 25] = [
+    '\t', '\n', '\u{b}', '\u{c}', '\r', ' ', '\u{85}', '\u{a0}', '\u{1680}', '\u{2000}',
+    '\u{2001}', '\u{2002}', '\u{2003}', '\u{2004}', '\u{2005}', '\u{2006}', '\u{2007}', '\u{2008}',
+    '\u{2009}', '\u{200a}', '\u{2028}', '\u{2029}', '\u{202f}', '\u{205f}', '\u{3000}',
+];
+
+/// ASCII punctuation and symbols
+const PUNCTUATION: &[u8; 32] = br##"!"#$%&'()*+,-./:;<=>?@[\]^_`{|}~"##;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+/// The surprise factor of [`char`], also used for the characters of strings
+/// through [`StringSurprise::chars`](super::StringSurprise::chars).
+///
+/// Alphabets, regex classes such as Unicode scripts and mixtures are picked
+/// through a [`CharSetSurprise`] instead.
+///
+/// ```rust
+/// # use surprise_me::{factors::CharSurprise, Surprise};
+/// #
+/// #[derive(Surprise)]
+/// struct Text {
+///     #[factor(chars = CharSurprise::Printable)]
+///     ascii: String,
+///     #[factor(CharSurprise::Range { min: 'a', max: 'f' })]
+///     hex_letter: char,
+/// }
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CharSurprise {
+    /// Only generates ASCII letters and digits
+    Ascii,
+    /// Generates any Unicode scalar value
+    Unicode,
+    /// Generates printable ASCII characters, i.e. from `' '` up to `'~'`
+    Printable,
+    /// Generates whitespace, including non-ASCII whitespace such as `'\u{a0}'`
+    Whitespace,
+    /// Generates ASCII punctuation and symbols such as `'!'` or `'{'`
+    Punctuation,
+    /// Generates characters between `min` and `max`, both inclusive
+    Range {
+        /// The smallest generated character
+        min: char,
+        /// The largest generated character
+        max: char,
+    },
+}
+
+impl CharSurprise {
+    /// Whether the surprise factor generates the character
+    pub fn contains(&self, c: char) -> bool {
+        match self {
+            Self::Ascii => c.is_ascii_alphanumeric(),
+            Self::Unicode => true,
+            Self::Printable => (' '..='~').contains(&c),
+            Self::Whitespace => WHITESPACE.contains(&c),
+            Self::Punctuation => c.is_ascii_punctuation(),
+            Self::Range { min, max } => (min..=max).contains(&&c),
+        }
+    }
+
+    /// The character that others shrink toward, i.e. `'a'` if it is generated
+    pub(crate) fn simplest(&self) -> char {
+        match self {
+            _ if self.contains('a') => 'a',
+            Self::Whitespace => ' ',
+            Self::Punctuation => '!',
+            Self::Range { min, .. } => *min,
+            Self::Ascii | Self::Unicode | Self::Printable => 'a',
+        }
+    }
+
+    /// The most bytes that a generated character takes up in UTF-8
+    pub(crate) fn max_len_utf8(&self) -> usize {
+        match self {
+            Self::Ascii | Self::Printable | Self::Punctuation => 1,
+            Self::Unicode => 4,
+            Self::Whitespace => 3,
+            Self::Range { max, .. } => max.len_utf8(),
+        }
+    }
+
+    /// Generates a character, assuming that the surprise factor has no problems
+    pub(crate) fn sample_valid<R: Rng + ?Sized>(&self, rng: &mut R) -> char {
+        match self {
+            Self::Ascii => rng.sample(Alphanumeric) as char,
+            Self::Unicode => rng.sample(Standard),
+            Self::Printable => rng.gen_range(' '..='~'),
+            Self::Whitespace => *WHITESPACE.choose(rng).unwrap(),
+            Self::Punctuation => *PUNCTUATION.choose(rng).unwrap() as char,
+            Self::Range { min, max } => rng.gen_range(*min..=*max),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// A surprise factor of [`char`] for sets of characters that a [`CharSurprise`] can't
+/// express, also used for the characters of strings through
+/// [`StringSurprise::char_set`](super::StringSurprise::char_set).
+///
+/// With the `regex` feature, Unicode general categories and scripts are picked through
+/// a [`CharClass`] which is also what string literals turn into in derived surprise factors.
+///
+/// ```rust
+/// # use surprise_me::{factors::{CharSetSurprise, CharSurprise, WeightedChars}, Surprise};
+/// #
+/// # #[cfg(feature = "regex")]
+/// #[derive(Surprise)]
+/// struct Text {
+///     #[factor(char_set = r"\p{Cyrillic}")]
+///     cyrillic: String,
+///     #[factor(char_set = r"[\p{Han}\p{Hiragana}\p{Katakana}]")]
+///     cjk: String,
+///     #[factor = "CharSetSurprise"]
+///     #[factor(r"\p{Emoji_Presentation}")]
+///     emoji: char,
+///     #[factor(char_set = Some(CharSetSurprise::Mixture(vec![
+///         WeightedChars { chars: CharSurprise::Ascii.into(), weight: 0.9 },
+///         WeightedChars { chars: CharSurprise::Whitespace.into(), weight: 0.1 },
+///     ])))]
+///     words: String,
+/// }
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CharSetSurprise {
+    /// Generates the characters of the [`CharSurprise`]
+    Chars(CharSurprise),
+    /// Picks one of the characters, must not be empty
+    Alphabet(Vec<char>),
+    /// Generates the characters of the class, e.g. a Unicode script.
+    /// Classes can only be parsed with the `regex` feature.
+    Class(CharClass),
+    /// Picks one of the character sets by weight, then a character from it
+    Mixture(Vec<WeightedChars>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// A set of characters for [`CharSetSurprise::Mixture`]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WeightedChars {
+    /// The characters to pick from
+    pub chars: CharSetSurprise,
+    /// How likely the characters are compared to the others
+    pub weight: f64,
+}
+
+impl CharSetSurprise {
+    /// Creates a surprise factor for the characters of a regex class such as
+    /// `[a-z]`, `\p{Greek}` or `\p{Lu}`, see [`CharClass::new`].
+    /// Always fails without the `regex` feature.
+    ///
+    /// ```rust
+    /// # use surprise_me::{factors::CharSetSurprise, rand, Distribution};
+    /// #
+    /// # #[cfg(feature = "regex")] {
+    /// let factor = CharSetSurprise::class(r"\p{Greek}").unwrap();
+    /// let c: char = factor.sample(&mut rand::thread_rng());
+    ///
+    /// assert!(factor.contains(c));
+    /// assert!(CharSetSurprise::class("abc").is_err());
+    /// # }
+    /// ```
+    pub fn class(pattern: &str) -> Result<Self, SurpriseError> {
+        CharClass::new(pattern).map(Self::Class)
+    }
+
+    /// Whether the surprise factor generates the character
+    pub fn contains(&self, c: char) -> bool {
+        match self {
+            Self::Chars(chars) => chars.contains(c),
+            Self::Alphabet(alphabet) => alphabet.contains(&c),
+            Self::Class(class) => class.contains(c),
+            Self::Mixture(mixture) => mixture
+                .iter()
+                .any(|weighted| weighted.weight > 0.0 && weighted.chars.contains(c)),
+        }
+    }
+
+    /// The character that others shrink toward, i.e. `'a'` if it is generated
+    pub(crate) fn simplest(&self) -> char {
+        if self.contains('a') {
+            return 'a';
+        }
+
+        let simplest = match self {
+            Self::Chars(chars) => Some(chars.simplest()),
+            Self::Alphabet(alphabet) => alphabet.first().copied(),
+            Self::Class(class) => class.ranges.first().map(|(start, _)| *start),
+            Self::Mixture(mixture) => mixture
+                .iter()
+                .find(|weighted| weighted.weight > 0.0)
+                .map(|weighted| weighted.chars.simplest()),
+        };
+
+        simplest.unwrap_or('a')
+    }
+
+    /// The most bytes that a generated character takes up in UTF-8
+    pub(crate) fn max_len_utf8(&self) -> usize {
+        match self {
+            Self::Chars(chars) => chars.max_len_utf8(),
+            Self::Alphabet(alphabet) => alphabet.iter().map(|c| c.len_utf8()).max().unwrap_or(1),
+            Self::Class(class) => class.ranges.last().map_or(1, |(_, end)| end.len_utf8()),
+            Self::Mixture(mixture) => mixture
+                .iter()
+                .map(|weighted| weighted.chars.max_len_utf8())
+                .max()
+                .unwrap_or(1),
+        }
+    }
+
+    /// Generates a character, assuming that the surprise factor has no problems
+    pub(crate) fn sample_valid<R: Rng + ?Sized>(&self, rng: &mut R) -> char {
+        match self {
+            Self::Chars(chars) => chars.sample_valid(rng),
+            Self::Alphabet(alphabet) => *alphabet.choose(rng).unwrap(),
+            Self::Class(class) => class.sample(rng),
+            Self::Mixture(mixture) => {
+                let total: f64 = mixture.iter().map(|weighted| weighted.weight).sum();
+                let mut pick = rng.gen_range(0.0..total);
+
+                // Float imprecision may leave `pick` slightly above the last weight
+                let weighted = mixture
+                    .iter()
+                    .find(|weighted| {
+                        pick -= weighted.weight;
+
+                        pick < 0.0
+                    })
+                    .or_else(|| mixture.iter().rfind(|weighted| weighted.weight > 0.0))
+                    .unwrap();
+
+                weighted.chars.sample_valid(rng)
+            }
+        }
+    }
+}
+
+impl Default for CharSurprise {
+    #[inline]
+    fn default() -> Self {
+        Self::Ascii
+    }
+}
+
+impl Surprise for char {
+    type Factor = CharSurprise;
+
+    delegate_to_factor!();
+}
+
+impl SurpriseKey for char {}
+
+impl Distribution<char> for CharSurprise {
+    #[inline]
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> char {
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl TryDistribution<char> for CharSurprise {
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<char, SurpriseError> {
+        match self.problems().into_iter().next() {
+            Some(problem) => Err(problem),
+            None => Ok(self.sample_valid(rng)),
+        }
+    }
+}
+
+impl Validate for CharSurprise {
+    fn problems(&self) -> Vec<SurpriseError> {
+        match self {
+            Self::Range { min, max } if min > max => {
+                let reason = format!("{min:?} is greater than `max` ({max:?})");
+
+                vec![SurpriseError::new("min", reason)]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl Default for CharSetSurprise {
+    #[inline]
+    fn default() -> Self {
+        Self::Chars(CharSurprise::default())
+    }
+}
+
+impl From<CharSurprise> for CharSetSurprise {
+    #[inline]
+    fn from(chars: CharSurprise) -> Self {
+        Self::Chars(chars)
+    }
+}
+
+impl Distribution<char> for CharSetSurprise {
+    #[inline]
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> char {
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl TryDistribution<char> for CharSetSurprise {
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<char, SurpriseError> {
+        match self.problems().into_iter().next() {
+            Some(problem) => Err(problem),
+            None => Ok(self.sample_valid(rng)),
+        }
+    }
+}
+
+impl Validate for CharSetSurprise {
+    fn problems(&self) -> Vec<SurpriseError> {
+        match self {
+            Self::Chars(chars) => chars.problems(),
+            Self::Class(_) => Vec::new(),
+            Self::Alphabet(alphabet) if alphabet.is_empty() => {
+                vec![SurpriseError::new("alphabet", "no characters to pick from")]
+            }
+            Self::Alphabet(_) => Vec::new(),
+            Self::Mixture(mixture) => {
+                let weights = mixture.iter().map(|weighted| weighted.weight);
+
+                WeightedIndex::new(weights)
+                    .err()
+                    .map(|err| SurpriseError::new("mixture", err.to_string()))
+                    .into_iter()
+                    .chain(mixture.iter().flat_map(|weighted| {
+                        weighted
+                            .chars
+                            .problems()
+                            .into_iter()
+                            .map(|problem| problem.in_field("mixture"))
+                    }))
+                    .collect()
+            }
+        }
+    }
+}
+
+impl Shrink for char {
+    /// Shrinks toward `'a'`, or the first character of the surprise factor if it doesn't
+    /// generate `'a'`
+    #[inline]
+    fn shrink<'a>(&'a self, factor: &'a CharSurprise) -> Shrinker<'a, Self> {
+        let simplest = factor.simplest();
+
+        Box::new((*self != simplest).then_some(simplest).into_iter())
+    }
+}
@@ -1,40 +1,219 @@
 use std::{
-    collections::{hash_map::RandomState, BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet},
+    collections::{hash_map::RandomState, BinaryHeap, HashMap, HashSet},
     collections::{LinkedList, VecDeque},
     fmt::{Debug, Formatter, Result as FmtResult},
     hash::{BuildHasher, Hash},
+    mem::size_of,
 };
 
-use rand::{prelude::Distribution, Rng};
+use rand::{prelude::Distribution, seq::SliceRandom, Rng};
 
-use crate::{Surprise, SurpriseFactor};
+use super::{ItemOrder, LenDistribution};
+
+use crate::{
+    budget::spend_len,
+    enumerate::sequences_between,
+    error::check_len,
+    shrink::{shrink_items, shrink_len},
+    size::current_size,
+    validate::{check_nested, nested_problems},
+    Enumerate, Enumerator, Shrink, Shrinker, Surprise, SurpriseError, SurpriseFactor, SurpriseKey,
+    TryDistribution, Validate,
+};
+
+/// How many duplicates in a row are generated at least before the remaining
+/// distinct keys are considered too unlikely or impossible to generate.
+///
+/// Larger collections tolerate ten duplicates in a row per item since the last
+/// few distinct keys of a small key space take many attempts to find.
+pub const MAX_DUPLICATES: usize = 100;
+
+/// Calls `try_insert` until it inserted `len` distinct `what`, i.e. returned `true`.
+///
+/// Stops early after too many duplicates in a row, see [`MAX_DUPLICATES`],
+/// and then only fails if fewer than `min_len` distinct ones could be inserted.
+pub(super) fn try_fill_distinct<R: Rng + ?Sized>(
+    rng: &mut R,
+    len: usize,
+    min_len: usize,
+    what: &str,
+    mut try_insert: impl FnMut(&mut R) -> Result<bool, SurpriseError>,
+) -> Result<(), SurpriseError> {
+    let max_duplicates = MAX_DUPLICATES.max(len.saturating_mul(10));
+    let mut distinct = 0;
+    let mut duplicates = 0;
+
+    while distinct < len && duplicates < max_duplicates {
+        if try_insert(rng)? {
+            distinct += 1;
+            duplicates = 0;
+        } else {
+            duplicates += 1;
+        }
+    }
+
+    if distinct >= min_len {
+        Ok(())
+    } else {
+        let reason = format!(
+            "only {distinct} distinct {what} could be generated, \
+            the surprise factor for them may not produce enough different values"
+        );
+
+        Err(SurpriseError::new("min_len", reason))
+    }
+}
 
 /// The surprise factor of [`Vec`]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        default,
+        bound(
+            serialize = "SurpriseFactor<T>: serde::Serialize",
+            deserialize = "SurpriseFactor<T>: serde::Deserialize<'de> + Default"
+        )
+    )
+)]
 pub struct VecSurprise<T: Surprise> {
     /// The minimum length of generated vecs
     pub min_len: usize,
-    /// The maximum length of generated vecs
+    /// The maximum length of generated vecs.
+    ///
+    /// Defaults to the [current size](crate::size::current_size).
     pub max_len: usize,
+    /// How lengths are distributed between `min_len` and `max_len`, defaults to uniformly
+    pub len: LenDistribution,
     /// The surprise factor for the items
     pub items: SurpriseFactor<T>,
+    /// How the items are ordered and whether they are distinct, defaults to any order.
+    ///
+    /// Not (de)serialized since it contains a comparison function.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub order: ItemOrder<T>,
 }
 
 impl<T: Surprise> Surprise for Vec<T> {
     type Factor = VecSurprise<T>;
+
+    delegate_to_factor!();
 }
 
+impl<T: Surprise + Ord> SurpriseKey for Vec<T> {}
+
 impl<T: Surprise> VecSurprise<T> {
     #[allow(clippy::len_without_is_empty)]
-    /// Returns a random length within the `min_len` and `max_len` values
+    /// Returns a random length within the `min_len` and `max_len` values, distributed by `len`
     pub fn len<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
-        rng.gen_range(self.min_len..=self.max_len)
+        self.try_len(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns a random length within the `min_len` and `max_len` values, distributed by `len`,
+    /// or an error if `min_len` is greater than `max_len` or `len` is invalid
+    pub fn try_len<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<usize, SurpriseError> {
+        self.len.try_sample(rng, self.min_len, self.max_len)
+    }
+
+    /// Generates `len` items through the surprise factor `items` in the given `order`
+    fn try_gen_items<C, R>(&self, rng: &mut R, len: usize) -> Result<C, SurpriseError>
+    where
+        C: FromIterator<T>,
+        R: Rng + ?Sized,
+    {
+        check_nested::<T>("items", &self.items)?;
+
+        let gen_item = |rng: &mut R| {
+            T::try_generate_with_valid_factor(rng, &self.items).map_err(|err| err.in_field("items"))
+        };
+
+        let compare = match self.order {
+            ItemOrder::Any => return (0..len).map(|_| gen_item(rng)).collect(),
+            ItemOrder::Sorted(compare) => {
+                let mut items = (0..len)
+                    .map(|_| gen_item(rng))
+                    .collect::<Result<Vec<_>, _>>()?;
+                items.sort_by(compare);
+
+                return Ok(items.into_iter().collect());
+            }
+            ItemOrder::StrictlySorted(compare) | ItemOrder::Unique(compare) => compare,
+        };
+
+        // Kept sorted to find duplicates through binary search
+        let mut items = Vec::with_capacity(len);
+
+        try_fill_distinct(rng, len, self.min_len, "items", |rng| {
+            let item = gen_item(rng)?;
+
+            match items.binary_search_by(|probe| compare(probe, &item)) {
+                Ok(_) => Ok(false),
+                Err(idx) => {
+                    items.insert(idx, item);
+
+                    Ok(true)
+                }
+            }
+        })?;
+
+        if let ItemOrder::Unique(_) = self.order {
+            items.shuffle(rng);
+        }
+
+        Ok(items.into_iter().collect())
     }
 }
 
 impl<T: Surprise> Distribution<Vec<T>> for VecSurprise<T> {
     #[inline]
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec<T> {
-        (0..self.len(rng)).map(|_| self.items.sample(rng)).collect()
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<T: Surprise> TryDistribution<Vec<T>> for VecSurprise<T> {
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<Vec<T>, SurpriseError> {
+        let len = spend_len(self.try_len(rng)?, self.min_len, size_of::<T>());
+
+        self.try_gen_items(rng, len)
+    }
+}
+
+impl<T: Surprise> Validate for VecSurprise<T> {
+    fn problems(&self) -> Vec<SurpriseError> {
+        check_len(self.min_len, self.max_len)
+            .err()
+            .into_iter()
+            .chain(self.len.problem())
+            .chain(nested_problems::<T>("items", &self.items))
+            .collect()
+    }
+}
+
+impl<T: Shrink + Clone> Shrink for Vec<T> {
+    /// Shrinks toward `min_len` items, then shrinks the items themselves
+    #[inline]
+    fn shrink<'a>(&'a self, factor: &'a VecSurprise<T>) -> Shrinker<'a, Self> {
+        let removed = shrink_len(self.iter().cloned(), self.len(), factor.min_len);
+        let shrunk = shrink_items(self.iter(), &factor.items)
+            .filter(|items: &Self| factor.order.is_satisfied_by(items));
+
+        Box::new(removed.chain(shrunk))
+    }
+}
+
+impl<T: Enumerate + Clone> Enumerate for Vec<T> {
+    /// Enumerates all sequences from `min_len` to `max_len` items, shorter ones first
+    #[inline]
+    fn enumerate<'a>(factor: &'a VecSurprise<T>) -> Enumerator<'a, Self>
+    where
+        Self: 'a,
+    {
+        Box::new(
+            sequences_between(factor.min_len, factor.max_len, &factor.items)
+                .filter(|items| factor.order.is_satisfied_by(items)),
+        )
     }
 }
 
@@ -47,8 +226,10 @@ where
     fn default() -> Self {
         Self {
             min_len: 0,
-            max_len: 100,
+            max_len: current_size(),
+            len: LenDistribution::Uniform,
             items: Default::default(),
+            order: ItemOrder::Any,
         }
     }
 }
@@ -63,7 +244,9 @@ where
         Self {
             min_len: self.min_len,
             max_len: self.max_len,
+            len: self.len.clone(),
             items: self.items.clone(),
+            order: self.order,
         }
     }
 }
@@ -78,7 +261,9 @@ where
         f.debug_struct("VecSurprise")
             .field("min_len", &self.min_len)
             .field("max_len", &self.max_len)
+            .field("len", &self.len)
             .field("items", &self.items)
+            .field("order", &self.order)
             .finish()
     }
 }
@@ -90,7 +275,11 @@ where
 {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
-        self.min_len == other.min_len && self.max_len == other.max_len && self.items == other.items
+        self.min_len == other.min_len
+            && self.max_len == other.max_len
+            && self.len == other.len
+            && self.items == other.items
+            && self.order == other.order
     }
 }
 
@@ -99,12 +288,52 @@ pub type VecDequeSurprise<T> = VecSurprise<T>;
 
 impl<T: Surprise> Surprise for VecDeque<T> {
     type Factor = VecDequeSurprise<T>;
+
+    delegate_to_factor!();
 }
 
+impl<T: Surprise + Ord> SurpriseKey for VecDeque<T> {}
+
 impl<T: Surprise> Distribution<VecDeque<T>> for VecDequeSurprise<T> {
     #[inline]
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> VecDeque<T> {
-        (0..self.len(rng)).map(|_| self.items.sample(rng)).collect()
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<T: Surprise> TryDistribution<VecDeque<T>> for VecDequeSurprise<T> {
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<VecDeque<T>, SurpriseError> {
+        let len = spend_len(self.try_len(rng)?, self.min_len, size_of::<T>());
+
+        self.try_gen_items(rng, len)
+    }
+}
+
+impl<T: Shrink + Clone> Shrink for VecDeque<T> {
+    /// Shrinks toward `min_len` items, then shrinks the items themselves
+    #[inline]
+    fn shrink<'a>(&'a self, factor: &'a VecDequeSurprise<T>) -> Shrinker<'a, Self> {
+        let removed = shrink_len(self.iter().cloned(), self.len(), factor.min_len);
+        let shrunk = shrink_items(self.iter(), &factor.items)
+            .filter(|items: &Self| factor.order.is_satisfied_by(items));
+
+        Box::new(removed.chain(shrunk))
+    }
+}
+
+impl<T: Enumerate + Clone> Enumerate for VecDeque<T> {
+    /// Enumerates all sequences from `min_len` to `max_len` items, shorter ones first
+    #[inline]
+    fn enumerate<'a>(factor: &'a VecDequeSurprise<T>) -> Enumerator<'a, Self>
+    where
+        Self: 'a,
+    {
+        Box::new(
+            sequences_between(factor.min_len, factor.max_len, &factor.items)
+                .filter(|items| factor.order.is_satisfied_by(items))
+                .map(VecDeque::from_iter),
+        )
     }
 }
 
@@ -113,12 +342,52 @@ pub type LinkedListSurprise<T> = VecSurprise<T>;
 
 impl<T: Surprise> Surprise for LinkedList<T> {
     type Factor = LinkedListSurprise<T>;
+
+    delegate_to_factor!();
 }
 
+impl<T: Surprise + Ord> SurpriseKey for LinkedList<T> {}
+
 impl<T: Surprise> Distribution<LinkedList<T>> for LinkedListSurprise<T> {
     #[inline]
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> LinkedList<T> {
-        (0..self.len(rng)).map(|_| self.items.sample(rng)).collect()
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<T: Surprise> TryDistribution<LinkedList<T>> for LinkedListSurprise<T> {
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<LinkedList<T>, SurpriseError> {
+        let len = spend_len(self.try_len(rng)?, self.min_len, size_of::<T>());
+
+        self.try_gen_items(rng, len)
+    }
+}
+
+impl<T: Shrink + Clone> Shrink for LinkedList<T> {
+    /// Shrinks toward `min_len` items, then shrinks the items themselves
+    #[inline]
+    fn shrink<'a>(&'a self, factor: &'a LinkedListSurprise<T>) -> Shrinker<'a, Self> {
+        let removed = shrink_len(self.iter().cloned(), self.len(), factor.min_len);
+        let shrunk = shrink_items(self.iter(), &factor.items)
+            .filter(|items: &Self| factor.order.is_satisfied_by(items));
+
+        Box::new(removed.chain(shrunk))
+    }
+}
+
+impl<T: Enumerate + Clone> Enumerate for LinkedList<T> {
+    /// Enumerates all sequences from `min_len` to `max_len` items, shorter ones first
+    #[inline]
+    fn enumerate<'a>(factor: &'a LinkedListSurprise<T>) -> Enumerator<'a, Self>
+    where
+        Self: 'a,
+    {
+        Box::new(
+            sequences_between(factor.min_len, factor.max_len, &factor.items)
+                .filter(|items| factor.order.is_satisfied_by(items))
+                .map(LinkedList::from_iter),
+        )
     }
 }
 
@@ -129,6 +398,8 @@ pub type RandomStateSurprise = UnitSurprise;
 
 impl Surprise for RandomState {
     type Factor = RandomStateSurprise;
+
+    delegate_to_factor!();
 }
 
 impl Distribution<RandomState> for RandomStateSurprise {
@@ -138,12 +409,45 @@ impl Distribution<RandomState> for RandomStateSurprise {
     }
 }
 
+impl TryDistribution<RandomState> for RandomStateSurprise {
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(&self, _: &mut R) -> Result<RandomState, SurpriseError> {
+        Ok(RandomState::new())
+    }
+}
+
+impl Shrink for RandomState {
+    #[inline]
+    fn shrink<'a>(&'a self, _: &'a RandomStateSurprise) -> Shrinker<'a, Self> {
+        Box::new(std::iter::empty())
+    }
+}
+
 /// The surprise factor of [`HashMap`]
+///
+/// Keys are generated until the map contains the chosen amount of distinct keys.
+/// If too many keys in a row are duplicates, see [`MAX_DUPLICATES`], the map is kept smaller
+/// as long as it reaches `min_len`, otherwise generation fails.
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        default,
+        bound(
+            serialize = "SurpriseFactor<K>: serde::Serialize, SurpriseFactor<V>: serde::Serialize, SurpriseFactor<S>: serde::Serialize",
+            deserialize = "SurpriseFactor<K>: serde::Deserialize<'de> + Default, SurpriseFactor<V>: serde::Deserialize<'de> + Default, SurpriseFactor<S>: serde::Deserialize<'de> + Default"
+        )
+    )
+)]
 pub struct HashMapSurprise<K: Surprise, V: Surprise, S: Surprise = RandomState> {
     /// The minimum amount of items in the generated map
     pub min_len: usize,
-    /// The maximum amount of items in the generated map
+    /// The maximum amount of items in the generated map.
+    ///
+    /// Defaults to the [current size](crate::size::current_size).
     pub max_len: usize,
+    /// How lengths are distributed between `min_len` and `max_len`, defaults to uniformly
+    pub len: LenDistribution,
     /// The surprise factor for keys
     pub keys: SurpriseFactor<K>,
     /// The surprise factor for values
@@ -159,13 +463,42 @@ where
     S: Surprise + BuildHasher + Default,
 {
     type Factor = HashMapSurprise<K, V, S>;
+
+    delegate_to_factor!();
 }
 
 impl<K: Surprise, V: Surprise, S: Surprise> HashMapSurprise<K, V, S> {
     #[allow(clippy::len_without_is_empty)]
-    /// Returns a random length within the `min_len` and `max_len` values
+    /// Returns a random length within the `min_len` and `max_len` values, distributed by `len`
     pub fn len<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
-        rng.gen_range(self.min_len..=self.max_len)
+        self.try_len(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns a random length within the `min_len` and `max_len` values, distributed by `len`,
+    /// or an error if `min_len` is greater than `max_len` or `len` is invalid
+    pub fn try_len<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<usize, SurpriseError> {
+        self.len.try_sample(rng, self.min_len, self.max_len)
+    }
+
+    /// Generates entries through the `keys` and `values` surprise factors until
+    /// `len` of them were inserted, i.e. until `insert` returned `true` that often
+    fn try_gen_entries<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        len: usize,
+        mut insert: impl FnMut(K, V) -> bool,
+    ) -> Result<(), SurpriseError> {
+        check_nested::<K>("keys", &self.keys)?;
+        check_nested::<V>("values", &self.values)?;
+
+        try_fill_distinct(rng, len, self.min_len, "keys", |rng| {
+            let key = K::try_generate_with_valid_factor(rng, &self.keys)
+                .map_err(|err| err.in_field("keys"))?;
+            let value = V::try_generate_with_valid_factor(rng, &self.values)
+                .map_err(|err| err.in_field("values"))?;
+
+            Ok(insert(key, value))
+        })
     }
 }
 
@@ -177,12 +510,66 @@ where
 {
     #[inline]
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> HashMap<K, V, S> {
-        (0..self.len(rng))
-            .map(|_| (self.keys.sample(rng), self.values.sample(rng)))
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<K, V, S> TryDistribution<HashMap<K, V, S>> for HashMapSurprise<K, V, S>
+where
+    K: Surprise + Eq + Hash,
+    V: Surprise,
+    S: Surprise + BuildHasher + Default,
+{
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<HashMap<K, V, S>, SurpriseError> {
+        let len = spend_len(self.try_len(rng)?, self.min_len, size_of::<(K, V)>());
+        let mut map = HashMap::with_capacity_and_hasher(len, S::default());
+        self.try_gen_entries(rng, len, |key, value| map.insert(key, value).is_none())?;
+
+        Ok(map)
+    }
+}
+
+impl<K: Surprise, V: Surprise, S: Surprise> Validate for HashMapSurprise<K, V, S> {
+    fn problems(&self) -> Vec<SurpriseError> {
+        check_len(self.min_len, self.max_len)
+            .err()
+            .into_iter()
+            .chain(self.len.problem())
+            .chain(nested_problems::<K>("keys", &self.keys))
+            .chain(nested_problems::<V>("values", &self.values))
+            .chain(nested_problems::<S>("hasher", &self.hasher))
             .collect()
     }
 }
 
+impl<K, V, S> Shrink for HashMap<K, V, S>
+where
+    K: Surprise + Eq + Hash + Clone,
+    V: Shrink + Clone,
+    S: Surprise + BuildHasher + Default + Clone,
+{
+    /// Shrinks toward `min_len` entries, then shrinks the values.
+    ///
+    /// Keys are not shrunk because that could merge entries.
+    #[inline]
+    fn shrink<'a>(&'a self, factor: &'a HashMapSurprise<K, V, S>) -> Shrinker<'a, Self> {
+        let entries = self.iter().map(|(key, value)| (key.clone(), value.clone()));
+        let removed = shrink_len(entries, self.len(), factor.min_len);
+
+        let shrunk = self.iter().flat_map(move |(key, value)| {
+            value.shrink(&factor.values).map(move |value| {
+                let mut map = self.clone();
+                map.insert(key.clone(), value);
+
+                map
+            })
+        });
+
+        Box::new(removed.chain(shrunk))
+    }
+}
+
 impl<K, V, S> Clone for HashMapSurprise<K, V, S>
 where
     K: Surprise,
@@ -197,6 +584,7 @@ where
         Self {
             min_len: self.min_len,
             max_len: self.max_len,
+            len: self.len.clone(),
             keys: self.keys.clone(),
             values: self.values.clone(),
             hasher: self.hasher.clone(),
@@ -217,6 +605,7 @@ where
         f.debug_struct("HashMapSurprise")
             .field("min_len", &self.min_len)
             .field("max_len", &self.max_len)
+            .field("len", &self.len)
             .field("keys", &self.keys)
             .field("values", &self.values)
             .field("hasher", &self.hasher)
@@ -237,7 +626,8 @@ where
     fn default() -> Self {
         Self {
             min_len: 0,
-            max_len: 100,
+            max_len: current_size(),
+            len: LenDistribution::Uniform,
             keys: Default::default(),
             values: Default::default(),
             hasher: Default::default(),
@@ -258,6 +648,7 @@ where
     fn eq(&self, other: &Self) -> bool {
         self.min_len == other.min_len
             && self.max_len == other.max_len
+            && self.len == other.len
             && self.keys == other.keys
             && self.values == other.values
             && self.hasher == other.hasher
@@ -273,6 +664,8 @@ where
     S: Surprise + BuildHasher + Default,
 {
     type Factor = HashSetSurprise<T, S>;
+
+    delegate_to_factor!();
 }
 
 impl<T, S> Distribution<HashSet<T, S>> for HashSetSurprise<T, S>
@@ -282,45 +675,43 @@ where
 {
     #[inline]
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> HashSet<T, S> {
-        (0..self.len(rng)).map(|_| self.keys.sample(rng)).collect()
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
     }
 }
 
-/// The surprise factor of [`BTreeMap`]
-pub type BTreeMapSurprise<K, V> = HashMapSurprise<K, V, ()>;
-
-impl<K, V> Surprise for BTreeMap<K, V>
+impl<T, S> TryDistribution<HashSet<T, S>> for HashSetSurprise<T, S>
 where
-    K: Surprise + Ord,
-    V: Surprise,
-{
-    type Factor = BTreeMapSurprise<K, V>;
-}
-
-impl<K, V> Distribution<BTreeMap<K, V>> for BTreeMapSurprise<K, V>
-where
-    K: Surprise + Ord,
-    V: Surprise,
+    T: Surprise + Eq + Hash,
+    S: Surprise + BuildHasher + Default,
 {
     #[inline]
-    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> BTreeMap<K, V> {
-        (0..self.len(rng))
-            .map(|_| (self.keys.sample(rng), self.values.sample(rng)))
-            .collect()
-    }
-}
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<HashSet<T, S>, SurpriseError> {
+        let len = spend_len(self.try_len(rng)?, self.min_len, size_of::<T>());
+        let mut set = HashSet::with_capacity_and_hasher(len, S::default());
 
-/// The surprise factor of [`BTreeSet`]
-pub type BTreeSetSurprise<T> = VecSurprise<T>;
+        check_nested::<T>("keys", &self.keys)?;
 
-impl<T: Surprise + Ord> Surprise for BTreeSet<T> {
-    type Factor = BTreeSetSurprise<T>;
+        try_fill_distinct(rng, len, self.min_len, "keys", |rng| {
+            T::try_generate_with_valid_factor(rng, &self.keys)
+                .map(|item| set.insert(item))
+                .map_err(|err| err.in_field("keys"))
+        })?;
+
+        Ok(set)
+    }
 }
 
-impl<T: Surprise + Ord> Distribution<BTreeSet<T>> for BTreeSetSurprise<T> {
+impl<T, S> Shrink for HashSet<T, S>
+where
+    T: Surprise + Eq + Hash + Clone,
+    S: Surprise + BuildHasher + Default,
+{
+    /// Shrinks toward `min_len` items.
+    ///
+    /// Items are not shrunk because that could merge them.
     #[inline]
-    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> BTreeSet<T> {
-        (0..self.len(rng)).map(|_| self.items.sample(rng)).collect()
+    fn shrink<'a>(&'a self, factor: &'a HashSetSurprise<T, S>) -> Shrinker<'a, Self> {
+        shrink_len(self.iter().cloned(), self.len(), factor.min_len)
     }
 }
 
@@ -329,11 +720,35 @@ pub type BinaryHeadSurprise<T> = VecSurprise<T>;
 
 impl<T: Surprise + Ord> Surprise for BinaryHeap<T> {
     type Factor = BinaryHeadSurprise<T>;
+
+    delegate_to_factor!();
 }
 
 impl<T: Surprise + Ord> Distribution<BinaryHeap<T>> for BinaryHeadSurprise<T> {
     #[inline]
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> BinaryHeap<T> {
-        (0..self.len(rng)).map(|_| self.items.sample(rng)).collect()
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<T: Surprise + Ord> TryDistribution<BinaryHeap<T>> for BinaryHeadSurprise<T> {
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<BinaryHeap<T>, SurpriseError> {
+        let len = spend_len(self.try_len(rng)?, self.min_len, size_of::<T>());
+
+        self.try_gen_items(rng, len)
+    }
+}
+
+impl<T: Shrink + Ord + Clone> Shrink for BinaryHeap<T> {
+    /// Shrinks toward `min_len` items, then shrinks the items themselves
+    #[inline]
+    fn shrink<'a>(&'a self, factor: &'a BinaryHeadSurprise<T>) -> Shrinker<'a, Self> {
+        let removed = shrink_len(self.iter().cloned(), self.len(), factor.min_len);
+        // The heap's iteration order is arbitrary, so only the distinctness of items is kept
+        let shrunk = shrink_items(self.iter(), &factor.items)
+            .filter(|items: &Self| factor.order.is_satisfiable_by(items));
+
+        Box::new(removed.chain(shrunk))
     }
 }
@@ -5,11 +5,25 @@ use std::{
 
 use rand::{prelude::Distribution, Rng};
 
-use crate::{Surprise, SurpriseFactor};
+use crate::{
+    validate::nested_problems, Shrink, Shrinker, Surprise, SurpriseError, SurpriseFactor,
+    SurpriseKey, TryDistribution, Validate,
+};
 
 /// The surprise factor of [`Cow`].
 ///
 /// Always generates an owned version.
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        default,
+        bound(
+            serialize = "SurpriseFactor<<T as ToOwned>::Owned>: serde::Serialize",
+            deserialize = "SurpriseFactor<<T as ToOwned>::Owned>: serde::Deserialize<'de> + Default"
+        )
+    )
+)]
 pub struct CowSurprise<T>
 where
     T: ToOwned + ?Sized,
@@ -25,6 +39,15 @@ where
     <T as ToOwned>::Owned: Surprise,
 {
     type Factor = CowSurprise<T>;
+
+    delegate_to_factor!();
+}
+
+impl<'a, T> SurpriseKey for Cow<'a, T>
+where
+    T: ToOwned + Ord + ?Sized,
+    <T as ToOwned>::Owned: Surprise,
+{
 }
 
 impl<'a, T> Distribution<Cow<'a, T>> for CowSurprise<T>
@@ -34,10 +57,46 @@ where
 {
     #[inline]
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Cow<'a, T> {
-        Cow::Owned(<<T as ToOwned>::Owned as Surprise>::generate_with_factor(
-            rng,
-            &self.inner,
-        ))
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<'a, T> TryDistribution<Cow<'a, T>> for CowSurprise<T>
+where
+    T: ToOwned + ?Sized,
+    <T as ToOwned>::Owned: Surprise,
+{
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<Cow<'a, T>, SurpriseError> {
+        <<T as ToOwned>::Owned as Surprise>::try_generate_with_factor(rng, &self.inner)
+            .map(Cow::Owned)
+            .map_err(|err| err.in_field("inner"))
+    }
+}
+
+impl<T> Validate for CowSurprise<T>
+where
+    T: ToOwned + ?Sized,
+    <T as ToOwned>::Owned: Surprise,
+{
+    #[inline]
+    fn problems(&self) -> Vec<SurpriseError> {
+        nested_problems::<<T as ToOwned>::Owned>("inner", &self.inner).collect()
+    }
+}
+
+impl<'b, T> Shrink for Cow<'b, T>
+where
+    T: ToOwned + ?Sized,
+    <T as ToOwned>::Owned: Shrink,
+{
+    /// Shrinks the owned value, borrowed values are not shrunk
+    #[inline]
+    fn shrink<'a>(&'a self, factor: &'a CowSurprise<T>) -> Shrinker<'a, Self> {
+        match self {
+            Cow::Owned(owned) => Box::new(owned.shrink(&factor.inner).map(Cow::Owned)),
+            Cow::Borrowed(_) => Box::new(std::iter::empty()),
+        }
     }
 }
 
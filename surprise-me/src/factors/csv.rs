@@ -0,0 +1,178 @@
+use rand::{prelude::Distribution, Rng};
+
+use crate::{
+    budget::spend_len,
+    error::{check_len, check_probability},
+    seed::gen_len,
+    size::current_size,
+    validate::nested_problems,
+    Surprise, SurpriseError, TryDistribution, Validate,
+};
+
+use super::{CharSurprise, StringSurprise};
+
+#[derive(Clone, Debug, PartialEq)]
+/// A surprise factor for CSV documents.
+///
+/// Fields that contain the delimiter, quotes or line breaks are quoted as in RFC 4180
+/// and every record ends with `\r\n`.
+///
+/// Since strings already have a surprise factor, fields use this one through
+/// `#[factor = "CsvSurprise"]`.
+///
+/// ```rust
+/// # use surprise_me::{factors::CsvSurprise, Surprise};
+/// #
+/// #[derive(Surprise)]
+/// struct Upload {
+///     #[factor = "CsvSurprise"]
+///     #[factor(max_len = 20, min_columns = 3, max_columns = 3, delimiter = ';')]
+///     csv: String,
+/// }
+/// ```
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct CsvSurprise {
+    /// The minimum amount of records, not counting the header, defaults to `0`
+    pub min_len: usize,
+    /// The maximum amount of records, not counting the header, defaults to the current size
+    pub max_len: usize,
+    /// The minimum amount of fields per record, defaults to `1`
+    pub min_columns: usize,
+    /// The maximum amount of fields per record, defaults to `5`
+    pub max_columns: usize,
+    /// Whether the first record is a header, defaults to `true`
+    pub header: bool,
+    /// The surprise factor for fields, defaults to at most 10 printable characters
+    pub fields: StringSurprise,
+    /// The character that separates fields, defaults to `','`
+    pub delimiter: char,
+    /// The probability that a ragged record or an unterminated quote is injected into the
+    /// document, defaults to `0.0`
+    pub corruption_chance: f64,
+}
+
+impl Distribution<String> for CsvSurprise {
+    #[inline]
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> String {
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl TryDistribution<String> for CsvSurprise {
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<String, SurpriseError> {
+        if let Some(problem) = self.problems().into_iter().next() {
+            return Err(problem);
+        }
+
+        let columns = gen_len(rng, self.min_columns..=self.max_columns);
+        let len = spend_len(
+            gen_len(rng, self.min_len..=self.max_len),
+            self.min_len,
+            columns,
+        );
+
+        let mut records: Vec<_> = (0..len + usize::from(self.header))
+            .map(|_| self.record(rng, columns))
+            .collect();
+
+        if rng.gen_bool(self.corruption_chance) {
+            // A single record can't be ragged, there is nothing to compare it with
+            if records.len() >= 2 && rng.gen() {
+                let idx = gen_len(rng, 0..=records.len() - 1);
+                records[idx].push(self.delimiter);
+                records[idx].push_str("extra");
+            } else {
+                records.push("\"unterminated".to_owned());
+            }
+        }
+
+        let mut csv = String::new();
+
+        for record in records {
+            csv.push_str(&record);
+            csv.push_str("\r\n");
+        }
+
+        Ok(csv)
+    }
+}
+
+impl CsvSurprise {
+    fn record<R: Rng + ?Sized>(&self, rng: &mut R, columns: usize) -> String {
+        let mut record = String::new();
+
+        for i in 0..columns {
+            if i > 0 {
+                record.push(self.delimiter);
+            }
+
+            let field = String::generate_with_factor(rng, &self.fields);
+            let special = [self.delimiter, '"', '\r', '\n'];
+
+            // An empty record would be an empty line, which readers tend to skip
+            if field.contains(special) || (field.is_empty() && columns == 1) {
+                record.push('"');
+                record.push_str(&field.replace('"', "\"\""));
+                record.push('"');
+            } else {
+                record.push_str(&field);
+            }
+        }
+
+        record
+    }
+}
+
+impl Validate for CsvSurprise {
+    fn problems(&self) -> Vec<SurpriseError> {
+        let mut problems = Vec::new();
+
+        problems.extend(check_len(self.min_len, self.max_len).err());
+
+        if self.min_columns == 0 {
+            problems.push(SurpriseError::new("min_columns", "must be at least 1"));
+        } else if self.min_columns > self.max_columns {
+            let reason = format!(
+                "{} is greater than `max_columns` ({})",
+                self.min_columns, self.max_columns
+            );
+
+            problems.push(SurpriseError::new("min_columns", reason));
+        }
+
+        if ['"', '\r', '\n'].contains(&self.delimiter) {
+            let reason = format!("{:?} can't separate fields", self.delimiter);
+
+            problems.push(SurpriseError::new("delimiter", reason));
+        }
+
+        problems.extend(check_probability("corruption_chance", self.corruption_chance).err());
+        problems.extend(nested_problems::<String>("fields", &self.fields));
+
+        problems
+    }
+}
+
+impl Default for CsvSurprise {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            min_len: 0,
+            max_len: current_size(),
+            min_columns: 1,
+            max_columns: 5,
+            header: true,
+            fields: StringSurprise {
+                max_len: 10,
+                chars: CharSurprise::Printable,
+                ..Default::default()
+            },
+            delimiter: ',',
+            corruption_chance: 0.0,
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use std::fmt::Display;
+
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    Rng,
+};
+#[cfg(feature = "distributions")]
+use rand_distr::{Exp, Geometric, LogNormal, Normal, Poisson, Zipf};
+
+use crate::{error::check_len, seed::gen_len, SurpriseError};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+/// How numbers are distributed between the `min` and `max` of their surprise factor.
+///
+/// Distributions of non-negative numbers, i.e. log-normal, exponential, Poisson and Zipf,
+/// are shifted to start at `min`. Samples outside of the bounds are clamped to them and
+/// integers are rounded to the nearest value. Beyond 2^53, where `f64` can't represent every
+/// integer, the digits below its precision are filled in uniformly.
+///
+/// All distributions but the uniform and log-uniform ones require the `distributions` feature,
+/// without it they are reported as problems of the surprise factor.
+///
+/// ```rust
+/// # use surprise_me::{factors::NumberDistribution, Surprise};
+/// #
+/// # #[cfg(feature = "distributions")]
+/// #[derive(Surprise)]
+/// struct Request {
+///     #[factor(max = 10_000, distribution = NumberDistribution::Exponential { lambda: 0.01 })]
+///     latency_ms: u32,
+///     #[factor(min = 1, max = 1_000_000, distribution = NumberDistribution::LogUniform)]
+///     payload_size: usize,
+/// }
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NumberDistribution {
+    /// Every number between `min` and `max` is equally likely
+    #[default]
+    Uniform,
+    /// Every order of magnitude between `min` and `max` is equally likely,
+    /// e.g. numbers between 1 and 10 are as likely as numbers between 100 and 1000.
+    ///
+    /// For negative ranges the magnitude is distributed the same way. Since 0 has no
+    /// logarithm, ranges that contain it distribute the magnitude plus one instead, e.g.
+    /// numbers between 0 and 9 are as likely as numbers between 10 and 99.
+    LogUniform,
+    /// Normal distribution around `mean`
+    Normal {
+        /// The mean of the distribution
+        mean: f64,
+        /// The standard deviation, must not be negative
+        std_dev: f64,
+    },
+    /// Log-normal distribution, i.e. the logarithm of numbers above `min` is normally distributed
+    LogNormal {
+        /// The mean of the underlying normal distribution
+        mu: f64,
+        /// The standard deviation of the underlying normal distribution, must not be negative
+        sigma: f64,
+    },
+    /// Exponential distribution with mean `min + 1 / lambda`
+    Exponential {
+        /// The rate, must not be negative
+        lambda: f64,
+    },
+    /// Poisson distribution for counts of events above `min` with mean `min + lambda`
+    Poisson {
+        /// The mean count, must be positive
+        lambda: f64,
+    },
+    /// Zipf distribution over the numbers from `min` upward, i.e. `min` is the
+    /// most likely number, then `min + 1`, and so on.
+    Zipf {
+        /// How quickly the probabilities decrease, must not be negative
+        exponent: f64,
+    },
+}
+
+impl NumberDistribution {
+    /// Samples a number between `min` and `max`, assuming that the distribution has no
+    /// [problem](Self::problem) and that `min` is not greater than `max`
+    pub(crate) fn sample_valid<R: Rng + ?Sized>(self, rng: &mut R, min: f64, max: f64) -> f64 {
+        let n = match self {
+            Self::Uniform => rng.gen_range(min..=max),
+            Self::LogUniform if min > 0.0 => rng.gen_range(min.ln()..=max.ln()).exp(),
+            Self::LogUniform if max < 0.0 => -rng.gen_range((-max).ln()..=(-min).ln()).exp(),
+            Self::LogUniform => {
+                // Each sign is as likely as the orders of magnitude it spans
+                let below = (-min).ln_1p();
+                let above = max.ln_1p();
+
+                if rng.gen::<f64>() * (below + above) < below {
+                    -rng.gen_range(0.0..=below).exp_m1()
+                } else {
+                    rng.gen_range(0.0..=above).exp_m1()
+                }
+            }
+            #[cfg(feature = "distributions")]
+            Self::Normal { mean, std_dev } => Normal::new(mean, std_dev).unwrap().sample(rng),
+            #[cfg(feature = "distributions")]
+            Self::LogNormal { mu, sigma } => min + LogNormal::new(mu, sigma).unwrap().sample(rng),
+            #[cfg(feature = "distributions")]
+            Self::Exponential { lambda } => min + Exp::new(lambda).unwrap().sample(rng),
+            #[cfg(feature = "distributions")]
+            Self::Poisson { lambda } => min + Poisson::new(lambda).unwrap().sample(rng),
+            #[cfg(feature = "distributions")]
+            Self::Zipf { exponent } => {
+                let ranks = ((max - min).floor() + 1.0).min(u64::MAX as f64) as u64;
+                let rank: f64 = Zipf::new(ranks, exponent).unwrap().sample(rng);
+
+                min + (rank - 1.0)
+            }
+            #[cfg(not(feature = "distributions"))]
+            _ => unreachable!("distributions without the feature are problems"),
+        };
+
+        n.clamp(min, max)
+    }
+
+    /// Checks the parameters of the distribution
+    pub(crate) fn problem(self) -> Option<SurpriseError> {
+        let res: Result<(), SurpriseError> = match self {
+            Self::Uniform => return None,
+            Self::LogUniform => return None,
+            #[cfg(not(feature = "distributions"))]
+            _ => Err(invalid(format_args!(
+                "{self:?} requires the `distributions` feature"
+            ))),
+            #[cfg(feature = "distributions")]
+            Self::Normal { mean, .. } | Self::LogNormal { mu: mean, .. } if !mean.is_finite() => {
+                Err(invalid(format_args!("mean {mean} is not finite")))
+            }
+            #[cfg(feature = "distributions")]
+            Self::Normal { std_dev: sigma, .. } | Self::LogNormal { sigma, .. } if sigma < 0.0 => {
+                Err(invalid(format_args!(
+                    "standard deviation {sigma} is negative"
+                )))
+            }
+            #[cfg(feature = "distributions")]
+            Self::Normal { mean, std_dev } => Normal::new(mean, std_dev).map(drop).map_err(invalid),
+            #[cfg(feature = "distributions")]
+            Self::LogNormal { mu, sigma } => LogNormal::new(mu, sigma).map(drop).map_err(invalid),
+            #[cfg(feature = "distributions")]
+            Self::Exponential { lambda } => Exp::new(lambda).map(drop).map_err(invalid),
+            #[cfg(feature = "distributions")]
+            Self::Poisson { lambda } => Poisson::new(lambda).map(drop).map_err(invalid),
+            #[cfg(feature = "distributions")]
+            Self::Zipf { exponent } => Zipf::new(1, exponent).map(drop).map_err(invalid),
+        };
+
+        res.err()
+    }
+}
+
+fn invalid(err: impl Display) -> SurpriseError {
+    SurpriseError::new("distribution", err.to_string())
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// How lengths of collections and strings are distributed between the
+/// `min_len` and `max_len` of their surprise factor.
+///
+/// Lengths outside of the bounds are clamped to them.
+///
+/// ```rust
+/// # use surprise_me::{factors::{LenBucket, LenDistribution}, Surprise};
+/// #
+/// # #[cfg(feature = "distributions")]
+/// #[derive(Surprise)]
+/// struct Order {
+///     #[factor(len = LenDistribution::Geometric { p: 0.3 })]
+///     items: Vec<u32>,
+///     #[factor(len = LenDistribution::MostlyEmpty { chance: 0.9 })]
+///     note: String,
+///     #[factor(len = LenDistribution::Buckets(vec![
+///         LenBucket { min_len: 0, max_len: 2, weight: 3.0 },
+///         LenBucket { min_len: 50, max_len: 100, weight: 1.0 },
+///     ]))]
+///     tags: Vec<String>,
+/// }
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LenDistribution {
+    /// Every length between `min_len` and `max_len` is equally likely
+    #[default]
+    Uniform,
+    /// Favours short lengths: starting at `min_len`, each additional item
+    /// is added with a probability of `1 - p`. Requires the `distributions` feature.
+    Geometric {
+        /// The probability to stop adding items, must be between `0.0` and `1.0`
+        p: f64,
+    },
+    /// Picks one of the buckets by weight, then a uniform length within it
+    Buckets(Vec<LenBucket>),
+    /// With a probability of `chance`, the length is `min_len` or one more,
+    /// i.e. mostly empty or a single item by default. Otherwise it's uniform.
+    MostlyEmpty {
+        /// Must be between `0.0` and `1.0`
+        chance: f64,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// A range of lengths for [`LenDistribution::Buckets`]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LenBucket {
+    /// The minimum length within the bucket
+    pub min_len: usize,
+    /// The maximum length within the bucket
+    pub max_len: usize,
+    /// How likely the bucket is compared to the others
+    pub weight: f64,
+}
+
+impl LenDistribution {
+    /// Returns a random length between `min_len` and `max_len`
+    pub(crate) fn try_sample<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        min_len: usize,
+        max_len: usize,
+    ) -> Result<usize, SurpriseError> {
+        check_len(min_len, max_len)?;
+
+        if let Some(err) = self.problem() {
+            return Err(err);
+        }
+
+        let len = match self {
+            Self::Uniform => gen_len(rng, min_len..=max_len),
+            #[cfg(feature = "distributions")]
+            Self::Geometric { p } => {
+                let extra = Geometric::new(*p).map_err(invalid_len)?.sample(rng);
+
+                min_len.saturating_add(usize::try_from(extra).unwrap_or(usize::MAX))
+            }
+            #[cfg(not(feature = "distributions"))]
+            Self::Geometric { .. } => {
+                unreachable!("distributions without the feature are problems")
+            }
+            Self::Buckets(buckets) => {
+                let weights = buckets.iter().map(|bucket| bucket.weight);
+                let idx = WeightedIndex::new(weights).map_err(invalid_len)?;
+                let bucket = &buckets[idx.sample(rng)];
+
+                gen_len(rng, bucket.min_len..=bucket.max_len)
+            }
+            Self::MostlyEmpty { chance } => {
+                if rng.gen_bool(*chance) {
+                    gen_len(rng, min_len..=min_len.saturating_add(1).min(max_len))
+                } else {
+                    gen_len(rng, min_len..=max_len)
+                }
+            }
+        };
+
+        Ok(len.clamp(min_len, max_len))
+    }
+
+    /// Checks the parameters of the distribution
+    pub(crate) fn problem(&self) -> Option<SurpriseError> {
+        let res = match self {
+            Self::Uniform => return None,
+            #[cfg(feature = "distributions")]
+            Self::Geometric { p } => Geometric::new(*p).map(drop).map_err(invalid_len),
+            #[cfg(not(feature = "distributions"))]
+            Self::Geometric { .. } => Err(invalid_len(
+                "the geometric distribution requires the `distributions` feature",
+            )),
+            Self::Buckets(buckets) => {
+                if let Some(bucket) = buckets.iter().find(|b| b.min_len > b.max_len) {
+                    let LenBucket {
+                        min_len, max_len, ..
+                    } = bucket;
+
+                    Err(invalid_len(format_args!(
+                        "bucket {min_len}..={max_len} is empty"
+                    )))
+                } else {
+                    let weights = buckets.iter().map(|bucket| bucket.weight);
+
+                    WeightedIndex::new(weights).map(drop).map_err(invalid_len)
+                }
+            }
+            Self::MostlyEmpty { chance } if (0.0..=1.0).contains(chance) => return None,
+            Self::MostlyEmpty { chance } => Err(invalid_len(format_args!(
+                "chance {chance} is not between 0.0 and 1.0"
+            ))),
+        };
+
+        res.err()
+    }
+}
+
+fn invalid_len(err: impl Display) -> SurpriseError {
+    SurpriseError::new("len", err.to_string())
+}
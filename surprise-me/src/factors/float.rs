@@ -0,0 +1,226 @@
+use rand::{prelude::Distribution, Rng};
+
+use crate::{
+    error::check_probability, Shrink, Shrinker, Surprise, SurpriseError, TryDistribution, Validate,
+};
+
+use super::{NumberDistribution, NumberSurprise};
+
+#[derive(Clone, Debug, PartialEq)]
+/// The surprise factor of [`f32`] and [`f64`].
+///
+/// Besides uniformly distributed numbers between `min` and `max`, floats can
+/// be special values that are easy to mishandle. Each special class has its own
+/// probability and is considered regardless of `min` and `max`. The classes are
+/// tried one after the other in the order of the fields, e.g. a `nan_chance` of
+/// `1.0` never generates infinities.
+///
+/// Floats used to be generated through [`NumberSurprise`], which
+/// [converts](FloatSurprise::from) into a float factor without special values.
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct FloatSurprise<F> {
+    /// The minimum value of generated numbers, defaults to `0.0`
+    pub min: F,
+    /// The maximum value of generated numbers, defaults to `1.0`
+    pub max: F,
+    /// Probability to generate one of the [edge cases](NumberSurprise::edge_cases)
+    /// within `min` and `max` instead of a uniformly distributed number.
+    ///
+    /// Should be between `0.0` and `1.0`, the default is `0.0` so that edge cases are opt-in.
+    pub edge_chance: f64,
+    /// How numbers are distributed between `min` and `max`, defaults to uniformly
+    pub distribution: NumberDistribution,
+    /// Probability to generate NaN with a random sign and payload, defaults to `0.0`
+    pub nan_chance: f64,
+    /// Probability to generate positive or negative infinity, defaults to `0.0`
+    pub infinity_chance: f64,
+    /// Probability to generate `-0.0`, defaults to `0.0`
+    pub neg_zero_chance: f64,
+    /// Probability to generate a random positive or negative subnormal number, defaults to `0.0`
+    pub subnormal_chance: f64,
+    /// Generates floats from random bit patterns instead, ignoring all other fields.
+    ///
+    /// This covers every finite and non-finite value. Defaults to `false`.
+    pub any_bits: bool,
+}
+
+impl<F> FloatSurprise<F> {
+    /// The settings for generating numbers that are not special values
+    fn number(&self) -> NumberSurprise<F>
+    where
+        F: Copy,
+    {
+        NumberSurprise {
+            min: self.min,
+            max: self.max,
+            edge_chance: self.edge_chance,
+            distribution: self.distribution,
+        }
+    }
+}
+
+impl<F> From<NumberSurprise<F>> for FloatSurprise<F>
+where
+    FloatSurprise<F>: Default,
+{
+    /// Uses the settings of the number factor without special values
+    #[inline]
+    fn from(number: NumberSurprise<F>) -> Self {
+        Self {
+            min: number.min,
+            max: number.max,
+            edge_chance: number.edge_chance,
+            distribution: number.distribution,
+            ..Default::default()
+        }
+    }
+}
+
+macro_rules! surprise_float {
+    ( $( $ty:ident ($bits:ty) $(,)? )* ) => {
+        $(
+            impl Surprise for $ty {
+                type Factor = FloatSurprise<Self>;
+
+                delegate_to_factor!();
+
+                #[inline]
+                fn try_generate_with_valid_factor<R: Rng + ?Sized>(
+                    rng: &mut R,
+                    factor: &FloatSurprise<Self>,
+                ) -> Result<Self, SurpriseError> {
+                    Ok(factor.sample_valid(rng))
+                }
+            }
+
+            impl Default for FloatSurprise<$ty> {
+                #[inline]
+                fn default() -> Self {
+                    let NumberSurprise {
+                        min,
+                        max,
+                        edge_chance,
+                        distribution,
+                    } = NumberSurprise::<$ty>::default();
+
+                    Self {
+                        min,
+                        max,
+                        edge_chance,
+                        distribution,
+                        nan_chance: 0.0,
+                        infinity_chance: 0.0,
+                        neg_zero_chance: 0.0,
+                        subnormal_chance: 0.0,
+                        any_bits: false,
+                    }
+                }
+            }
+
+            impl Distribution<$ty> for FloatSurprise<$ty> {
+                #[inline]
+                fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> $ty {
+                    self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+                }
+            }
+
+            impl TryDistribution<$ty> for FloatSurprise<$ty> {
+                fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<$ty, SurpriseError> {
+                    match self.problems().into_iter().next() {
+                        Some(problem) => Err(problem),
+                        None => Ok(self.sample_valid(rng)),
+                    }
+                }
+            }
+
+            impl FloatSurprise<$ty> {
+                /// Generates a float, assuming that the surprise factor has no problems
+                fn sample_valid<R: Rng + ?Sized>(&self, rng: &mut R) -> $ty {
+                    const SIGN: $bits = 1 << (<$bits>::BITS - 1);
+                    const MANTISSA: $bits = (1 << ($ty::MANTISSA_DIGITS - 1)) - 1;
+                    const EXPONENT: $bits = !(SIGN | MANTISSA);
+
+                    if self.any_bits {
+                        return $ty::from_bits(rng.gen());
+                    }
+
+                    // Only drawn for special values so that zero chances don't consume randomness
+                    let gen_sign = |rng: &mut R| if rng.gen() { SIGN } else { 0 };
+
+                    if self.nan_chance > 0.0 && rng.gen_bool(self.nan_chance) {
+                        let mantissa = rng.gen_range(1..=MANTISSA);
+
+                        return $ty::from_bits(gen_sign(rng) | EXPONENT | mantissa);
+                    }
+
+                    if self.infinity_chance > 0.0 && rng.gen_bool(self.infinity_chance) {
+                        return $ty::from_bits(gen_sign(rng) | EXPONENT);
+                    }
+
+                    if self.neg_zero_chance > 0.0 && rng.gen_bool(self.neg_zero_chance) {
+                        return -0.0;
+                    }
+
+                    if self.subnormal_chance > 0.0 && rng.gen_bool(self.subnormal_chance) {
+                        let mantissa = rng.gen_range(1..=MANTISSA);
+
+                        return $ty::from_bits(gen_sign(rng) | mantissa);
+                    }
+
+                    self.number().sample_valid(rng)
+                }
+            }
+
+            impl Validate for FloatSurprise<$ty> {
+                fn problems(&self) -> Vec<SurpriseError> {
+                    let chances = [
+                        ("nan_chance", self.nan_chance),
+                        ("infinity_chance", self.infinity_chance),
+                        ("neg_zero_chance", self.neg_zero_chance),
+                        ("subnormal_chance", self.subnormal_chance),
+                    ];
+
+                    let special = chances
+                        .into_iter()
+                        .filter_map(|(field, chance)| check_probability(field, chance).err());
+
+                    self.number().problems().into_iter().chain(special).collect()
+                }
+            }
+
+            impl Shrink for $ty {
+                /// Shrinks toward `0.0` or, if `0.0` is out of bounds, toward whichever bound is closer to it.
+                ///
+                /// NaN and infinities only shrink to that target directly.
+                fn shrink<'a>(&'a self, factor: &'a FloatSurprise<$ty>) -> Shrinker<'a, Self> {
+                    // Floats can be halved for a long time so we stop at some point
+                    const MAX_HALVES: usize = 64;
+
+                    let n = *self;
+                    let target = (0.0 as $ty).max(factor.min).min(factor.max);
+
+                    if n == target {
+                        return Box::new(std::iter::empty());
+                    } else if !n.is_finite() {
+                        return Box::new(std::iter::once(target));
+                    }
+
+                    let diff = n - target;
+                    let trunc = Some(n.trunc()).filter(|trunc| (trunc - target).abs() < diff.abs());
+                    let halves = std::iter::successors(Some(diff / 2.0), |diff| Some(diff / 2.0))
+                        .take(MAX_HALVES)
+                        .map(move |diff| n - diff)
+                        .take_while(move |candidate| *candidate != n);
+
+                    Box::new(std::iter::once(target).chain(trunc).chain(halves))
+                }
+            }
+        )*
+    };
+}
+
+surprise_float!(f32(u32), f64(u64));
@@ -0,0 +1,685 @@
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Formatter, Result as FmtResult},
+    fs,
+    path::Path,
+};
+
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng};
+
+use crate::{budget::spend_len, seed::gen_len, SurpriseError, TryDistribution, Validate};
+
+#[cfg(feature = "regex")]
+use super::StringRegex;
+
+#[derive(Clone, Debug, PartialEq)]
+/// A surprise factor for strings that are derived from a [`Grammar`], e.g. to fuzz parsers.
+///
+/// Since strings already have a surprise factor, fields use this one through
+/// `#[factor = "GrammarSurprise"]` and set the grammar through `#[factor(grammar = "...")]`.
+///
+/// ```rust
+/// # use surprise_me::{factors::GrammarSurprise, Surprise};
+/// #
+/// # #[cfg(feature = "regex")] {
+/// #[derive(Surprise)]
+/// struct Calculation {
+///     #[factor = "GrammarSurprise"]
+///     #[factor(max_depth = 6, grammar = r#"
+///         expr   = term , { ( "+" | "-" ) , term } ;
+///         term   = factor , { ( "*" | "/" ) , factor } ;
+///         factor = number @ 3 | "(" , expr , ")" ;
+///         number = /[1-9][0-9]{0,3}/ ;
+///     "#)]
+///     input: String,
+/// }
+///
+/// let input = Calculation::generate(&mut surprise_me::rand::thread_rng()).input;
+/// assert!(input.bytes().all(|b| b"0123456789+-*/()".contains(&b)));
+/// # }
+/// ```
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct GrammarSurprise {
+    /// The grammar that generated strings are derived from, defaults to an empty grammar
+    pub grammar: Grammar,
+    /// The rule that strings are derived from, defaults to the first rule of the grammar
+    pub start: Option<String>,
+    /// How deep rules may nest, defaults to `16`.
+    ///
+    /// Once a rule would exceed the depth, only alternatives that finish the
+    /// quickest are picked, and options and repetitions are left out.
+    pub max_depth: usize,
+    /// How often `{ ... }`, `*` and `+` repeat at most, defaults to `5`
+    pub max_repeat: usize,
+}
+
+impl GrammarSurprise {
+    /// The index of the start rule
+    fn try_start(&self) -> Result<usize, SurpriseError> {
+        if self.grammar.names.is_empty() {
+            return Err(SurpriseError::new("grammar", "the grammar has no rules"));
+        }
+
+        match self.start {
+            Some(ref start) => self.grammar.rule(start).ok_or_else(|| {
+                SurpriseError::new("start", format!("`{start}` is not a rule of the grammar"))
+            }),
+            None => Ok(0),
+        }
+    }
+}
+
+impl Distribution<String> for GrammarSurprise {
+    #[inline]
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> String {
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl TryDistribution<String> for GrammarSurprise {
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<String, SurpriseError> {
+        let start = self.try_start()?;
+        let mut string = String::new();
+        self.expand(rng, &Node::Rule(start), 0, &mut string);
+
+        let len = string.chars().count();
+        spend_len(len, len, if string.is_ascii() { 1 } else { 4 });
+
+        Ok(string)
+    }
+}
+
+impl GrammarSurprise {
+    /// Appends a string that is derived from the node at the given depth
+    fn expand<R: Rng + ?Sized>(&self, rng: &mut R, node: &Node, depth: usize, string: &mut String) {
+        let fits = |node: &Node| depth + self.grammar.depth(node) <= self.max_depth;
+
+        match node {
+            Node::Literal(literal) => string.push_str(literal),
+            #[cfg(feature = "regex")]
+            Node::Regex(regex) => string.push_str(&regex.sample(rng)),
+            Node::Rule(idx) => self.expand(rng, &self.grammar.rules[*idx], depth + 1, string),
+            Node::Sequence(nodes) => {
+                for node in nodes {
+                    self.expand(rng, node, depth, string);
+                }
+            }
+            Node::Choice(alternatives) => {
+                let mut weights: Vec<_> = alternatives
+                    .iter()
+                    .map(|(node, weight)| if fits(node) { *weight } else { 0.0 })
+                    .collect();
+
+                // Past the depth, alternatives that finish the quickest are picked
+                if weights.iter().all(|weight| *weight == 0.0) {
+                    let quickest = alternatives
+                        .iter()
+                        .filter(|(_, weight)| *weight > 0.0)
+                        .map(|(node, _)| self.grammar.depth(node))
+                        .min()
+                        .unwrap_or(0);
+
+                    for ((node, weight), quick) in alternatives.iter().zip(weights.iter_mut()) {
+                        if self.grammar.depth(node) == quickest {
+                            *quick = *weight;
+                        }
+                    }
+                }
+
+                let idx = WeightedIndex::new(weights)
+                    .expect("alternatives have a positive weight")
+                    .sample(rng);
+
+                self.expand(rng, &alternatives[idx].0, depth, string);
+            }
+            Node::Optional(node) => {
+                if fits(node) && rng.gen() {
+                    self.expand(rng, node, depth, string);
+                }
+            }
+            Node::Repeat(node, min) => {
+                let count = if fits(node) {
+                    gen_len(rng, *min..=self.max_repeat.max(*min))
+                } else {
+                    *min
+                };
+
+                for _ in 0..count {
+                    self.expand(rng, node, depth, string);
+                }
+            }
+        }
+    }
+}
+
+impl Validate for GrammarSurprise {
+    #[inline]
+    fn problems(&self) -> Vec<SurpriseError> {
+        self.try_start().err().into_iter().collect()
+    }
+}
+
+impl Default for GrammarSurprise {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            grammar: Grammar::default(),
+            start: None,
+            max_depth: 16,
+            max_repeat: 5,
+        }
+    }
+}
+
+/// A grammar in EBNF or BNF notation for [`GrammarSurprise`].
+///
+/// Rules are written as `name = ... ;` or `<name> ::= ...` where the terminating `;`
+/// or `.` is optional. A rule that is defined multiple times has the alternatives of
+/// all definitions. Rules consist of
+///
+/// - terminals in double or single quotes, e.g. `"while"` or `'\n'`, which support
+///   the escapes `\n`, `\r`, `\t`, `\\`, `\"` and `\'`
+/// - regexes between slashes, e.g. `/[a-z]+/`, which require the `regex` feature
+/// - references to other rules, e.g. `expr` or `<expr>`
+/// - sequences, separated by `,` or whitespace
+/// - alternatives, separated by `|`, each with an optional weight such as `@ 2.5`
+///   that defaults to `1`
+/// - groups `( ... )`, options `[ ... ]` or `...?`, and repetitions `{ ... }` or `...*`
+///   as well as `...+` for at least one repetition
+/// - comments `(* ... *)` or from `#` to the end of the line
+///
+/// In derived surprise factors, it is set through `#[factor(grammar = "...")]` which
+/// panics when the default surprise factor is created if the grammar is invalid.
+#[derive(Clone, Default)]
+pub struct Grammar {
+    source: String,
+    /// Rule names in order of their definition
+    names: Vec<String>,
+    rules: Vec<Node>,
+    /// The fewest nested rules that each rule needs to be derived
+    depths: Vec<usize>,
+}
+
+impl Grammar {
+    /// Parses the grammar `source`.
+    ///
+    /// Fails if the grammar is malformed, references undefined rules, or contains
+    /// rules that never finish, e.g. `a = "a" , a ;`.
+    pub fn new(source: &str) -> Result<Self, SurpriseError> {
+        let mut parser = Parser {
+            source,
+            pos: 0,
+            names: Vec::new(),
+            indices: HashMap::new(),
+            rules: Vec::new(),
+        };
+
+        parser.parse_rules()?;
+
+        let Parser { names, rules, .. } = parser;
+
+        let rules = names
+            .iter()
+            .zip(rules)
+            .map(|(name, rule)| {
+                rule.ok_or_else(|| invalid_grammar(format!("rule `{name}` is not defined")))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // The depths decrease until they are the fewest possible
+        let mut depths: Vec<Option<usize>> = vec![None; rules.len()];
+        let mut changed = true;
+
+        while changed {
+            changed = false;
+
+            for (idx, rule) in rules.iter().enumerate() {
+                let depth = min_depth(rule, &|idx| depths[idx]);
+
+                if depth != depths[idx] {
+                    depths[idx] = depth;
+                    changed = true;
+                }
+            }
+        }
+
+        let depths = names
+            .iter()
+            .zip(depths)
+            .map(|(name, depth)| {
+                depth.ok_or_else(|| invalid_grammar(format!("rule `{name}` never finishes")))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            source: source.to_owned(),
+            names,
+            rules,
+            depths,
+        })
+    }
+
+    /// Reads and parses the grammar of a file, see [`Grammar::new`]
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, SurpriseError> {
+        let path = path.as_ref();
+
+        let source = fs::read_to_string(path)
+            .map_err(|err| invalid_grammar(format!("{}: {err}", path.display())))?;
+
+        Self::new(&source)
+    }
+
+    /// The source that the grammar was parsed from
+    #[inline]
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The names of the rules in order of their definition
+    pub fn rules(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(String::as_str)
+    }
+
+    fn rule(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|rule| rule == name)
+    }
+
+    /// The fewest nested rules that the node needs to be derived
+    fn depth(&self, node: &Node) -> usize {
+        min_depth(node, &|idx| Some(self.depths[idx])).unwrap_or(0)
+    }
+}
+
+impl Debug for Grammar {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_tuple("Grammar").field(&self.source).finish()
+    }
+}
+
+impl PartialEq for Grammar {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Grammar {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.source)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Grammar {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let source = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+
+        Self::new(&source).map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Clone)]
+enum Node {
+    Literal(String),
+    #[cfg(feature = "regex")]
+    Regex(StringRegex),
+    Rule(usize),
+    Sequence(Vec<Node>),
+    /// Alternatives with their weight
+    Choice(Vec<(Node, f64)>),
+    Optional(Box<Node>),
+    /// Repetition with its minimum count
+    Repeat(Box<Node>, usize),
+}
+
+/// The fewest nested rules that the node needs to be derived or `None` if it never finishes
+fn min_depth(node: &Node, rule_depth: &dyn Fn(usize) -> Option<usize>) -> Option<usize> {
+    match node {
+        Node::Literal(_) | Node::Optional(_) | Node::Repeat(_, 0) => Some(0),
+        #[cfg(feature = "regex")]
+        Node::Regex(_) => Some(0),
+        Node::Rule(idx) => rule_depth(*idx).map(|depth| depth + 1),
+        Node::Sequence(nodes) => nodes.iter().try_fold(0, |max, node| {
+            min_depth(node, rule_depth).map(|depth| depth.max(max))
+        }),
+        Node::Choice(alternatives) => alternatives
+            .iter()
+            .filter(|(_, weight)| *weight > 0.0)
+            .filter_map(|(node, _)| min_depth(node, rule_depth))
+            .min(),
+        Node::Repeat(node, _) => min_depth(node, rule_depth),
+    }
+}
+
+fn invalid_grammar(reason: impl Into<String>) -> SurpriseError {
+    SurpriseError::new("grammar", reason)
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    pos: usize,
+    names: Vec<String>,
+    indices: HashMap<String, usize>,
+    /// Rules by index, `None` if only referenced so far
+    rules: Vec<Option<Node>>,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, reason: &str) -> SurpriseError {
+        let line = self.source[..self.pos].matches('\n').count() + 1;
+
+        invalid_grammar(format!("line {line}: {reason}"))
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.source[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn skip_whitespace(&mut self) -> Result<(), SurpriseError> {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+
+            if trimmed.starts_with("(*") {
+                let end = trimmed
+                    .find("*)")
+                    .ok_or_else(|| self.error("comment is not closed"))?;
+                self.pos += end + 2;
+            } else if trimmed.starts_with('#') {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn index(&mut self, name: String) -> usize {
+        *self.indices.entry(name).or_insert_with_key(|name| {
+            self.names.push(name.clone());
+            self.rules.push(None);
+
+            self.rules.len() - 1
+        })
+    }
+
+    fn parse_rules(&mut self) -> Result<(), SurpriseError> {
+        loop {
+            self.skip_whitespace()?;
+
+            if self.rest().is_empty() {
+                return Ok(());
+            }
+
+            let name = self
+                .parse_name()?
+                .ok_or_else(|| self.error("expected a rule name"))?;
+
+            self.skip_whitespace()?;
+
+            if self.rest().starts_with("::=") {
+                self.pos += 3;
+            } else if self.rest().starts_with('=') {
+                self.pos += 1;
+            } else {
+                return Err(self.error(&format!("expected `=` or `::=` after `{name}`")));
+            }
+
+            let idx = self.index(name);
+            let node = self.parse_choice()?;
+            self.skip_whitespace()?;
+
+            if let Some(';' | '.') = self.peek() {
+                self.pos += 1;
+            }
+
+            // Alternatives of repeated definitions are merged
+            let mut alternatives = match self.rules[idx].take() {
+                Some(Node::Choice(alternatives)) => alternatives,
+                Some(prev) => vec![(prev, 1.0)],
+                None => {
+                    self.rules[idx] = Some(node);
+
+                    continue;
+                }
+            };
+
+            match node {
+                Node::Choice(new) => alternatives.extend(new),
+                node => alternatives.push((node, 1.0)),
+            }
+
+            self.rules[idx] = Some(Node::Choice(alternatives));
+        }
+    }
+
+    fn parse_name(&mut self) -> Result<Option<String>, SurpriseError> {
+        let rest = self.rest();
+
+        if let Some(bracketed) = rest.strip_prefix('<') {
+            let end = bracketed
+                .find('>')
+                .ok_or_else(|| self.error("`<` is not closed"))?;
+            let name = bracketed[..end].trim().to_owned();
+            self.pos += end + 2;
+
+            return Ok(Some(name));
+        }
+
+        let is_name_char = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+
+        if !rest.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+            return Ok(None);
+        }
+
+        let len = rest.find(|c| !is_name_char(c)).unwrap_or(rest.len());
+        self.pos += len;
+
+        Ok(Some(rest[..len].to_owned()))
+    }
+
+    /// Whether a new rule starts at the current position
+    fn at_rule_start(&mut self) -> Result<bool, SurpriseError> {
+        let pos = self.pos;
+        let is_start = self.parse_name()?.is_some() && {
+            self.skip_whitespace()?;
+
+            self.rest().starts_with('=') || self.rest().starts_with("::=")
+        };
+        self.pos = pos;
+
+        Ok(is_start)
+    }
+
+    fn parse_choice(&mut self) -> Result<Node, SurpriseError> {
+        let mut alternatives = vec![self.parse_alternative()?];
+
+        while let Some('|') = self.peek() {
+            self.pos += 1;
+            alternatives.push(self.parse_alternative()?);
+        }
+
+        if alternatives.iter().all(|(_, weight)| *weight == 0.0) {
+            return Err(self.error("all alternatives have a weight of 0"));
+        }
+
+        if alternatives.len() == 1 {
+            Ok(alternatives.pop().unwrap().0)
+        } else {
+            Ok(Node::Choice(alternatives))
+        }
+    }
+
+    /// Parses a sequence and its weight
+    fn parse_alternative(&mut self) -> Result<(Node, f64), SurpriseError> {
+        let mut nodes = Vec::new();
+
+        loop {
+            self.skip_whitespace()?;
+
+            match self.peek() {
+                None | Some(';' | '.' | '|' | ')' | ']' | '}' | '@') => break,
+                Some(',') => self.pos += 1,
+                Some(_) if self.at_rule_start()? => break,
+                Some(_) => nodes.push(self.parse_item()?),
+            }
+        }
+
+        let mut weight = 1.0;
+
+        if let Some('@') = self.peek() {
+            self.pos += 1;
+            self.skip_whitespace()?;
+
+            let rest = self.rest();
+            let len = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(rest.len());
+
+            weight = rest[..len]
+                .parse()
+                .map_err(|_| self.error("expected a weight after `@`"))?;
+            self.pos += len;
+            self.skip_whitespace()?;
+        }
+
+        let node = if nodes.len() == 1 {
+            nodes.pop().unwrap()
+        } else {
+            Node::Sequence(nodes)
+        };
+
+        Ok((node, weight))
+    }
+
+    fn parse_item(&mut self) -> Result<Node, SurpriseError> {
+        let mut node = self.parse_primary()?;
+
+        loop {
+            match self.peek() {
+                Some('?') => node = Node::Optional(Box::new(node)),
+                Some('*') => node = Node::Repeat(Box::new(node), 0),
+                Some('+') => node = Node::Repeat(Box::new(node), 1),
+                _ => return Ok(node),
+            }
+
+            self.pos += 1;
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Node, SurpriseError> {
+        let Some(c) = self.peek() else {
+            return Err(self.error("unexpected end of the grammar"));
+        };
+
+        let node = match c {
+            '"' | '\'' => Node::Literal(self.parse_literal(c)?),
+            #[cfg(feature = "regex")]
+            '/' => {
+                let pattern = self.parse_regex()?;
+                let regex = StringRegex::new(&pattern)
+                    .map_err(|err| self.error(&format!("invalid regex: {}", err.reason())))?;
+
+                Node::Regex(regex)
+            }
+            #[cfg(not(feature = "regex"))]
+            '/' => return Err(self.error("regexes require the `regex` feature")),
+            '(' | '[' | '{' => {
+                self.pos += 1;
+                let node = self.parse_choice()?;
+                let close = match c {
+                    '(' => ')',
+                    '[' => ']',
+                    _ => '}',
+                };
+
+                if self.peek() != Some(close) {
+                    return Err(self.error(&format!("expected `{close}`")));
+                }
+
+                self.pos += 1;
+
+                match c {
+                    '(' => node,
+                    '[' => Node::Optional(Box::new(node)),
+                    _ => Node::Repeat(Box::new(node), 0),
+                }
+            }
+            _ => match self.parse_name()? {
+                Some(name) => Node::Rule(self.index(name)),
+                None => return Err(self.error(&format!("unexpected `{c}`"))),
+            },
+        };
+
+        Ok(node)
+    }
+
+    fn parse_literal(&mut self, quote: char) -> Result<String, SurpriseError> {
+        let mut literal = String::new();
+        let mut chars = self.rest().char_indices().skip(1);
+
+        while let Some((i, c)) = chars.next() {
+            let c = match c {
+                c if c == quote => {
+                    self.pos += i + 1;
+
+                    return Ok(literal);
+                }
+                '\\' => match chars.next() {
+                    Some((_, 'n')) => '\n',
+                    Some((_, 'r')) => '\r',
+                    Some((_, 't')) => '\t',
+                    Some((_, c @ ('\\' | '"' | '\''))) => c,
+                    _ => {
+                        self.pos += i;
+
+                        return Err(self.error("unknown escape"));
+                    }
+                },
+                c => c,
+            };
+
+            literal.push(c);
+        }
+
+        Err(self.error("terminal is not closed"))
+    }
+
+    #[cfg(feature = "regex")]
+    fn parse_regex(&mut self) -> Result<String, SurpriseError> {
+        let mut pattern = String::new();
+        let mut chars = self.rest().char_indices().skip(1);
+
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '/' => {
+                    self.pos += i + 1;
+
+                    return Ok(pattern);
+                }
+                '\\' => match chars.next() {
+                    Some((_, '/')) => pattern.push('/'),
+                    Some((_, c)) => {
+                        pattern.push('\\');
+                        pattern.push(c);
+                    }
+                    None => break,
+                },
+                c => pattern.push(c),
+            }
+        }
+
+        Err(self.error("regex is not closed"))
+    }
+}
@@ -0,0 +1,230 @@
+use std::{collections::HashSet, fmt::Write};
+
+use rand::{prelude::Distribution, Rng};
+
+use crate::{
+    budget::spend_len, error::check_probability, seed::gen_len, validate::nested_problems,
+    Surprise, SurpriseError, TryDistribution, Validate,
+};
+
+use super::{FloatSurprise, NumberSurprise, StringSurprise};
+
+#[derive(Clone, Debug, PartialEq)]
+/// A surprise factor for JSON documents, i.e. strings whose top level is an array or object.
+///
+/// Since strings already have a surprise factor, fields use this one through
+/// `#[factor = "JsonSurprise"]`.
+///
+/// ```rust
+/// # use surprise_me::{factors::JsonSurprise, Surprise};
+/// #
+/// #[derive(Surprise)]
+/// struct Request {
+///     #[factor = "JsonSurprise"]
+///     #[factor(max_depth = 2, strings(chars = surprise_me::factors::CharSurprise::Unicode))]
+///     body: String,
+/// }
+///
+/// let body = Request::generate(&mut surprise_me::rand::thread_rng()).body;
+/// assert!(body.starts_with(['[', '{']));
+/// ```
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct JsonSurprise {
+    /// How deep arrays and objects nest, including the top level, defaults to `3`.
+    ///
+    /// Must be at least `1`.
+    pub max_depth: usize,
+    /// The maximum amount of items in arrays and entries in objects, defaults to `5`
+    pub max_len: usize,
+    /// The surprise factor for keys and string values, defaults to at most 10 characters
+    pub strings: StringSurprise,
+    /// The surprise factor for integers
+    pub integers: NumberSurprise<i64>,
+    /// The surprise factor for floats, non-finite floats are written as `null`
+    pub floats: FloatSurprise<f64>,
+    /// The probability that a syntax error is injected into the document, defaults to `0.0`
+    pub corruption_chance: f64,
+}
+
+impl Distribution<String> for JsonSurprise {
+    #[inline]
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> String {
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl TryDistribution<String> for JsonSurprise {
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<String, SurpriseError> {
+        if let Some(problem) = self.problems().into_iter().next() {
+            return Err(problem);
+        }
+
+        let mut json = String::new();
+
+        if rng.gen() {
+            self.push_array(rng, 1, &mut json);
+        } else {
+            self.push_object(rng, 1, &mut json);
+        }
+
+        if rng.gen_bool(self.corruption_chance) {
+            corrupt(rng, &mut json);
+        }
+
+        Ok(json)
+    }
+}
+
+impl JsonSurprise {
+    /// Appends a value within a container at the given depth
+    fn push_value<R: Rng + ?Sized>(&self, rng: &mut R, depth: usize, json: &mut String) {
+        let kinds = if depth < self.max_depth { 7 } else { 5 };
+
+        match rng.gen_range(0..kinds) {
+            0 => json.push_str("null"),
+            1 => json.push_str(if rng.gen() { "true" } else { "false" }),
+            2 => {
+                let _ = write!(json, "{}", i64::generate_with_factor(rng, &self.integers));
+            }
+            3 => match f64::generate_with_factor(rng, &self.floats) {
+                float if float.is_finite() => {
+                    let _ = write!(json, "{float:?}");
+                }
+                _ => json.push_str("null"),
+            },
+            4 => push_quoted(&String::generate_with_factor(rng, &self.strings), json),
+            5 => self.push_array(rng, depth + 1, json),
+            _ => self.push_object(rng, depth + 1, json),
+        }
+    }
+
+    fn push_array<R: Rng + ?Sized>(&self, rng: &mut R, depth: usize, json: &mut String) {
+        json.push('[');
+
+        for i in 0..gen_container_len(rng, self.max_len) {
+            if i > 0 {
+                json.push(',');
+            }
+
+            self.push_value(rng, depth, json);
+        }
+
+        json.push(']');
+    }
+
+    fn push_object<R: Rng + ?Sized>(&self, rng: &mut R, depth: usize, json: &mut String) {
+        let mut keys = HashSet::new();
+        json.push('{');
+
+        for _ in 0..gen_container_len(rng, self.max_len) {
+            let key = String::generate_with_factor(rng, &self.strings);
+
+            // Duplicate keys are skipped
+            if keys.contains(&key) {
+                continue;
+            }
+
+            if !keys.is_empty() {
+                json.push(',');
+            }
+
+            push_quoted(&key, json);
+            json.push(':');
+            self.push_value(rng, depth, json);
+            keys.insert(key);
+        }
+
+        json.push('}');
+    }
+}
+
+/// Returns the length of an array, object or table and spends the active budget on it.
+///
+/// Each value is spent once as an element of its container, strings additionally
+/// spend their own characters like any other string.
+pub(super) fn gen_container_len<R: Rng + ?Sized>(rng: &mut R, max_len: usize) -> usize {
+    spend_len(gen_len(rng, 0..=max_len), 0, 1)
+}
+
+/// Appends the string in double quotes with quotes, backslashes and control characters
+/// escaped, which is valid in both JSON and TOML
+pub(super) fn push_quoted(string: &str, out: &mut String) {
+    out.push('"');
+
+    for c in string.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\0'..='\u{1f}' | '\u{7f}' => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+}
+
+/// Injects a syntax error into a JSON array or object
+fn corrupt<R: Rng + ?Sized>(rng: &mut R, json: &mut String) {
+    match rng.gen_range(0..3) {
+        // Any prefix of an array or object lacks at least the closing bracket
+        0 => {
+            let mut len = gen_len(rng, 0..=json.len() - 1);
+
+            while !json.is_char_boundary(len) {
+                len -= 1;
+            }
+
+            json.truncate(len);
+        }
+        // Single quotes instead of the first double quote, which always starts a string
+        1 if json.contains('"') => {
+            let idx = json.find('"').unwrap();
+            json.replace_range(idx..idx + 1, "'");
+        }
+        // A trailing comma before the closing bracket
+        _ => json.insert(json.len() - 1, ','),
+    }
+}
+
+impl Validate for JsonSurprise {
+    fn problems(&self) -> Vec<SurpriseError> {
+        let mut problems = Vec::new();
+
+        if self.max_depth == 0 {
+            problems.push(SurpriseError::new("max_depth", "must be at least 1"));
+        }
+
+        problems.extend(check_probability("corruption_chance", self.corruption_chance).err());
+        problems.extend(nested_problems::<String>("strings", &self.strings));
+        problems.extend(nested_problems::<i64>("integers", &self.integers));
+        problems.extend(nested_problems::<f64>("floats", &self.floats));
+
+        problems
+    }
+}
+
+impl Default for JsonSurprise {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_depth: 3,
+            max_len: 5,
+            strings: StringSurprise {
+                max_len: 10,
+                ..Default::default()
+            },
+            integers: NumberSurprise::default(),
+            floats: FloatSurprise::default(),
+            corruption_chance: 0.0,
+        }
+    }
+}
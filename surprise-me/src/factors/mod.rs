@@ -1,18 +1,33 @@
 pub use self::{
-    array::*, collections::*, cow::*, non_zero::*, option::*, phantom::*, primitive::*, range::*,
-    result::*, smart_pointers::*, string::*, sync::*, tuple::*,
+    array::*, btree::*, char::*, collections::*, cow::*, csv::*, distribution::*, float::*,
+    grammar::*, json::*, naughty::*, option::*, order::*, permutation::*, phantom::*, primitive::*,
+    range::*, recursive::*, regex::*, result::*, smart_pointers::*, string::*, sync::*, toml::*,
+    tuple::*,
 };
 
 mod array;
+mod btree;
+mod char;
 mod collections;
 mod cow;
+mod csv;
+mod distribution;
+mod float;
+mod grammar;
+mod json;
+mod naughty;
 mod non_zero;
 mod option;
+mod order;
+mod permutation;
 mod phantom;
 mod primitive;
 mod range;
+mod recursive;
+mod regex;
 mod result;
 mod smart_pointers;
 mod string;
 mod sync;
+mod toml;
 mod tuple;
@@ -0,0 +1,114 @@
+/// Strings that are known to break parsers, renderers and the like.
+///
+/// The corpus covers control characters and line break mixes, zero-width and
+/// joining characters, bidirectional overrides, combining marks up to very long
+/// grapheme clusters, code points next to the surrogate range or at the end of
+/// Unicode, characters whose case mapping changes their length, and strings
+/// that look like SQL, format specifiers, templates, markup or paths.
+///
+/// [`StringSurprise::naughty_chance`](super::StringSurprise::naughty_chance) mixes
+/// them into generated strings.
+pub const NAUGHTY_STRINGS: &[&str] = &[
+    // Control characters and line breaks
+    "\0",
+    "a\0b",
+    "\r\n",
+    "\n\r",
+    "\r",
+    "a\r\nb\nc\rd",
+    "\t\u{b}\u{c}",
+    "\u{7f}",
+    "\u{85}",
+    "\u{2028}",
+    "\u{2029}",
+    "\u{1b}[31mred\u{1b}[0m",
+    "\u{feff}",
+    // Zero-width and joining characters
+    "\u{200b}",
+    "\u{200c}",
+    "\u{200d}",
+    "\u{2060}",
+    "a\u{200d}b",
+    "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}\u{200d}\u{1f466}",
+    "\u{1f3f3}\u{fe0f}\u{200d}\u{1f308}",
+    "\u{1f469}\u{1f3fd}\u{200d}\u{1f680}",
+    "\u{1f1fa}\u{1f1f8}",
+    // Bidirectional text
+    "\u{202e}",
+    "abc\u{202e}fed",
+    "\u{202d}\u{202c}",
+    "\u{2066}\u{2067}\u{2068}\u{2069}",
+    "\u{200f}",
+    "\u{645}\u{631}\u{62d}\u{628}\u{627}",
+    "\u{5e9}\u{5dc}\u{5d5}\u{5dd} hello",
+    // Combining marks and long grapheme clusters
+    "\u{301}",
+    "e\u{301}",
+    concat!(
+        "Z\u{351}\u{34b}\u{35b}\u{316}a\u{36e}\u{352}\u{324}\u{33a}",
+        "l\u{343}\u{30f}\u{31f}\u{33c}g\u{36b}\u{306}\u{329}\u{35a}o\u{302}\u{36d}\u{347}",
+    ),
+    concat!(
+        "a",
+        "\u{300}\u{301}\u{302}\u{303}\u{304}\u{305}\u{306}\u{307}",
+        "\u{308}\u{309}\u{30a}\u{30b}\u{30c}\u{30d}\u{30e}\u{30f}",
+        "\u{310}\u{311}\u{312}\u{313}\u{314}\u{315}\u{316}\u{317}",
+        "\u{318}\u{319}\u{31a}\u{31b}\u{31c}\u{31d}\u{31e}\u{31f}",
+        "\u{320}\u{321}\u{322}\u{323}\u{324}\u{325}\u{326}\u{327}",
+        "\u{328}\u{329}\u{32a}\u{32b}\u{32c}\u{32d}\u{32e}\u{32f}",
+        "\u{330}\u{331}\u{332}\u{333}\u{334}\u{335}\u{336}\u{337}",
+        "\u{338}\u{339}\u{33a}\u{33b}\u{33c}\u{33d}\u{33e}\u{33f}",
+    ),
+    "\u{1100}\u{1161}\u{11a8}",
+    // Code points around surrogates and at the end of Unicode
+    "\u{d7ff}",
+    "\u{e000}",
+    "\u{f8ff}",
+    "\u{fffd}",
+    "\u{fffe}",
+    "\u{ffff}",
+    "\u{10000}",
+    "\u{10ffff}",
+    // Case mappings and compatibility forms
+    "\u{df}",
+    "\u{130}",
+    "\u{fb03}",
+    "\u{fdfa}",
+    "\u{ff21}\u{ff22}\u{ff23}",
+    "\u{663}\u{b9}\u{217b}",
+    // Values that look like other types
+    "NULL",
+    "null",
+    "undefined",
+    "NaN",
+    "-0",
+    "1e309",
+    "0x0",
+    "true",
+    "CON",
+    // SQL
+    "' OR '1'='1",
+    "'; DROP TABLE users; --",
+    "\" OR \"\"=\"",
+    "1; SELECT * FROM information_schema.tables",
+    // Format specifiers and templates
+    "%s%s%s%n",
+    "%d%x%p",
+    "{0}",
+    "{}",
+    "{{}}",
+    "${7*7}",
+    "{{7*7}}",
+    "${jndi:ldap://127.0.0.1/a}",
+    "$(id)",
+    "`id`",
+    // Markup and paths
+    "<script>alert(1)</script>",
+    "<!--",
+    "]]>",
+    "&amp;&#0;",
+    "../../../../etc/passwd",
+    "C:\\Windows\\System32",
+    "\\\\?\\C:\\",
+    "%00%2e%2e%2f",
+];
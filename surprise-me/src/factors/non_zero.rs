@@ -1,23 +1,39 @@
-use rand::{prelude::Distribution, Rng};
+use rand::{prelude::Distribution, seq::SliceRandom, Rng};
 
-use crate::Surprise;
+use crate::{
+    Enumerate, Enumerator, Shrink, Shrinker, Surprise, SurpriseError, SurpriseKey, TryDistribution,
+    Validate,
+};
 
-use super::NumberSurprise;
+use super::{NumberDistribution, NumberSurprise};
 
 macro_rules! non_zero_surprise {
     (UINT: $( $non_zero:ident ($int:ty) $(,)? )+) => {
         $(
-            impl Surprise for std::num:: $non_zero {
-                type Factor = NumberSurprise<$int>;
+            non_zero_surprise!(@SURPRISE: $non_zero($int), |factor| factor.max == 0);
+
+            impl TryDistribution<std::num::$non_zero> for NumberSurprise<$int> {
+                fn try_sample<R: Rng + ?Sized>(
+                    &self,
+                    rng: &mut R,
+                ) -> Result<std::num::$non_zero, SurpriseError> {
+                    match std::num::$non_zero::factor_problems(self).into_iter().next() {
+                        Some(problem) => Err(problem),
+                        None => Ok(self.sample_valid_non_zero(rng)),
+                    }
+                }
             }
 
-            impl Distribution<std::num::$non_zero> for NumberSurprise<$int> {
-                #[inline]
-                fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> std::num::$non_zero {
-                    assert!(
-                        self.max != 0,
-                        concat!("cannot generate ", stringify!($non_zero), " from zero"),
-                    );
+            impl NumberSurprise<$int> {
+                /// Generates a non-zero number, assuming that the surprise factor has no problems
+                fn sample_valid_non_zero<R: Rng + ?Sized>(&self, rng: &mut R) -> std::num::$non_zero {
+                    if let Some(edge) = self.try_gen_non_zero_edge(rng) {
+                        return edge;
+                    }
+
+                    if let Some(n) = self.try_gen_non_zero_distributed(rng) {
+                        return n;
+                    }
 
                     let n = rng.gen_range(self.min.max(1)..=self.max);
 
@@ -29,17 +45,30 @@ macro_rules! non_zero_surprise {
     };
     (INT: $( $non_zero:ident ($int:ty) $(,)? )+) => {
         $(
-            impl Surprise for std::num:: $non_zero {
-                type Factor = NumberSurprise<$int>;
+            non_zero_surprise!(@SURPRISE: $non_zero($int), |factor| factor.min == 0 && factor.max == 0);
+
+            impl TryDistribution<std::num::$non_zero> for NumberSurprise<$int> {
+                fn try_sample<R: Rng + ?Sized>(
+                    &self,
+                    rng: &mut R,
+                ) -> Result<std::num::$non_zero, SurpriseError> {
+                    match std::num::$non_zero::factor_problems(self).into_iter().next() {
+                        Some(problem) => Err(problem),
+                        None => Ok(self.sample_valid_non_zero(rng)),
+                    }
+                }
             }
 
-            impl Distribution<std::num::$non_zero> for NumberSurprise<$int> {
-                #[inline]
-                fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> std::num::$non_zero {
-                    assert!(
-                        self.min != 0 || self.max != 0,
-                        concat!("cannot generate ", stringify!($non_zero), " from zero"),
-                    );
+            impl NumberSurprise<$int> {
+                /// Generates a non-zero number, assuming that the surprise factor has no problems
+                fn sample_valid_non_zero<R: Rng + ?Sized>(&self, rng: &mut R) -> std::num::$non_zero {
+                    if let Some(edge) = self.try_gen_non_zero_edge(rng) {
+                        return edge;
+                    }
+
+                    if let Some(n) = self.try_gen_non_zero_distributed(rng) {
+                        return n;
+                    }
 
                     loop {
                         let n: $int = rng.gen_range(self.min..=self.max);
@@ -53,6 +82,102 @@ macro_rules! non_zero_surprise {
             }
         )*
     };
+    (@SURPRISE: $non_zero:ident ($int:ty), |$factor:ident| $only_zero:expr) => {
+        impl Surprise for std::num:: $non_zero {
+            type Factor = NumberSurprise<$int>;
+
+            #[inline]
+            fn try_generate_with_factor<R: Rng + ?Sized>(
+                rng: &mut R,
+                factor: &NumberSurprise<$int>,
+            ) -> Result<Self, SurpriseError> {
+                factor.try_sample(rng)
+            }
+
+            #[inline]
+            fn try_generate_with_valid_factor<R: Rng + ?Sized>(
+                rng: &mut R,
+                factor: &NumberSurprise<$int>,
+            ) -> Result<Self, SurpriseError> {
+                Ok(factor.sample_valid_non_zero(rng))
+            }
+
+            fn factor_problems($factor: &NumberSurprise<$int>) -> Vec<SurpriseError> {
+                let mut problems = $factor.problems();
+
+                // Zero is the only number within the bounds
+                if problems.is_empty() && $only_zero {
+                    let reason = concat!("cannot generate ", stringify!($non_zero), " from zero");
+
+                    problems.push(SurpriseError::new("max", reason));
+                }
+
+                problems
+            }
+        }
+
+        impl SurpriseKey for std::num::$non_zero {}
+
+        impl NumberSurprise<$int> {
+            /// Picks a non-zero edge case with the probability `edge_chance`
+            fn try_gen_non_zero_edge<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<std::num::$non_zero> {
+                if self.edge_chance == 0.0 || !rng.gen_bool(self.edge_chance) {
+                    return None;
+                }
+
+                let edges: Vec<_> = self
+                    .edge_cases()
+                    .into_iter()
+                    .filter_map(std::num::$non_zero::new)
+                    .collect();
+
+                edges.choose(rng).copied()
+            }
+
+            /// Samples from a non-uniform distribution, zero samples are discarded
+            fn try_gen_non_zero_distributed<R: Rng + ?Sized>(
+                &self,
+                rng: &mut R,
+            ) -> Option<std::num::$non_zero> {
+                if let NumberDistribution::Uniform = self.distribution {
+                    return None;
+                }
+
+                let n = self.distribution.sample_valid(rng, self.min as f64, self.max as f64);
+
+                std::num::$non_zero::new(self.round_sample(rng, n))
+            }
+        }
+
+        impl Distribution<std::num::$non_zero> for NumberSurprise<$int> {
+            #[inline]
+            fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> std::num::$non_zero {
+                self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+            }
+        }
+
+        impl Shrink for std::num::$non_zero {
+            /// Shrinks toward `1` or `-1`, or the bound closest to them
+            fn shrink<'a>(&'a self, factor: &'a NumberSurprise<$int>) -> Shrinker<'a, Self> {
+                let shrunk = Shrink::shrink(&self.get(), factor)
+                    .filter_map(std::num::$non_zero::new)
+                    .collect::<Vec<_>>();
+
+                Box::new(shrunk.into_iter())
+            }
+        }
+
+        impl Enumerate for std::num::$non_zero {
+            /// Enumerates all numbers from `min` to `max` except zero
+            #[inline]
+            fn enumerate<'a>(factor: &'a NumberSurprise<$int>) -> Enumerator<'a, Self>
+            where
+                Self: 'a,
+            {
+                Box::new((factor.min..=factor.max).filter_map(std::num::$non_zero::new))
+            }
+        }
+    };
 }
 
 non_zero_surprise!(
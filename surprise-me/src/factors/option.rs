@@ -2,9 +2,26 @@ use std::fmt::{Debug, Formatter, Result as FmtResult};
 
 use rand::{prelude::Distribution, Rng};
 
-use crate::{Surprise, SurpriseFactor};
+use crate::{
+    enumerate::outcomes,
+    error::{check_chance, try_gen_bool},
+    validate::nested_problems,
+    Enumerate, Enumerator, Shrink, Shrinker, Surprise, SurpriseError, SurpriseFactor, SurpriseKey,
+    TryDistribution, Validate,
+};
 
 /// The surprise factor of [`Option`]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        default,
+        bound(
+            serialize = "SurpriseFactor<T>: serde::Serialize",
+            deserialize = "SurpriseFactor<T>: serde::Deserialize<'de> + Default"
+        )
+    )
+)]
 pub struct OptionSurprise<T: Surprise> {
     /// Probability for `Some`.
     ///
@@ -16,19 +33,77 @@ pub struct OptionSurprise<T: Surprise> {
 
 impl<T: Surprise> Surprise for Option<T> {
     type Factor = OptionSurprise<T>;
+
+    delegate_to_factor!();
 }
 
+impl<T: Surprise + Ord> SurpriseKey for Option<T> {}
+
 impl<T: Surprise> Distribution<Option<T>> for OptionSurprise<T> {
     #[inline]
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<T> {
-        if rng.gen_bool(self.chance) {
-            Some(T::generate_with_factor(rng, &self.inner))
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<T: Surprise> TryDistribution<Option<T>> for OptionSurprise<T> {
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<Option<T>, SurpriseError> {
+        if try_gen_bool(rng, self.chance)? {
+            T::try_generate_with_factor(rng, &self.inner)
+                .map(Some)
+                .map_err(|err| err.in_field("inner"))
         } else {
-            None
+            Ok(None)
         }
     }
 }
 
+impl<T: Surprise> Validate for OptionSurprise<T> {
+    fn problems(&self) -> Vec<SurpriseError> {
+        check_chance(self.chance)
+            .err()
+            .into_iter()
+            .chain(nested_problems::<T>("inner", &self.inner))
+            .collect()
+    }
+}
+
+impl<T: Shrink> Shrink for Option<T> {
+    /// Shrinks toward `None` unless the factor always generates `Some`, then shrinks the inner value
+    #[inline]
+    fn shrink<'a>(&'a self, factor: &'a OptionSurprise<T>) -> Shrinker<'a, Self> {
+        match self {
+            Some(inner) => {
+                let none = (factor.chance < 1.0).then_some(None);
+                let shrunk = inner.shrink(&factor.inner).map(Some);
+
+                Box::new(none.into_iter().chain(shrunk))
+            }
+            None => Box::new(std::iter::empty()),
+        }
+    }
+}
+
+impl<T: Enumerate> Enumerate for Option<T> {
+    /// Enumerates `None` and then all `Some` values unless the factor never generates them
+    #[inline]
+    fn enumerate<'a>(factor: &'a OptionSurprise<T>) -> Enumerator<'a, Self>
+    where
+        Self: 'a,
+    {
+        Box::new(
+            outcomes(factor.chance).flat_map(move |some| -> Enumerator<'a, Self> {
+                if some {
+                    Box::new(T::enumerate(&factor.inner).map(Some))
+                } else {
+                    Box::new(std::iter::once(None))
+                }
+            }),
+        )
+    }
+}
+
 impl<T> Default for OptionSurprise<T>
 where
     T: Surprise,
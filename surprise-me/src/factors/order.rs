@@ -0,0 +1,147 @@
+use std::{
+    cmp::Ordering,
+    fmt::{Debug, Formatter, Result as FmtResult},
+};
+
+/// The comparison that orders items of an [`ItemOrder`]
+pub type Compare<T> = fn(&T, &T) -> Ordering;
+
+/// How the items of generated vecs are ordered and whether they are distinct.
+///
+/// Orders carry the comparison of the items since not every item type implements [`Ord`],
+/// e.g. floats can still be sorted through [`f64::total_cmp`]. For other types the
+/// constructors such as [`ItemOrder::sorted`] use [`Ord::cmp`].
+///
+/// The length of generated vecs is not affected, i.e. unique items are generated
+/// until there are enough of them, see [`HashMapSurprise`](super::HashMapSurprise).
+///
+/// Orders are not (de)serialized and default to [`ItemOrder::Any`].
+///
+/// ```rust
+/// # use surprise_me::{factors::ItemOrder, Surprise};
+/// #
+/// #[derive(Surprise)]
+/// struct Search {
+///     #[factor(order = ItemOrder::strictly_sorted())]
+///     haystack: Vec<u32>,
+///     #[factor(order = ItemOrder::Sorted(f64::total_cmp))]
+///     weights: Vec<f64>,
+///     #[factor(order = ItemOrder::Sorted(|a: &i8, b: &i8| b.cmp(a)))]
+///     descending: Vec<i8>,
+/// }
+/// ```
+pub enum ItemOrder<T> {
+    /// Items are generated independently of each other
+    Any,
+    /// Items are sorted by the comparison
+    Sorted(Compare<T>),
+    /// Items are sorted by the comparison and no two of them are equal, i.e. strictly increasing
+    StrictlySorted(Compare<T>),
+    /// No two items are equal by the comparison and they are in random order
+    Unique(Compare<T>),
+}
+
+impl<T: Ord> ItemOrder<T> {
+    /// Items are sorted in ascending order
+    #[inline]
+    pub fn sorted() -> Self {
+        Self::Sorted(T::cmp)
+    }
+
+    /// Items are strictly increasing
+    #[inline]
+    pub fn strictly_sorted() -> Self {
+        Self::StrictlySorted(T::cmp)
+    }
+
+    /// Items are distinct and in random order
+    #[inline]
+    pub fn unique() -> Self {
+        Self::Unique(T::cmp)
+    }
+}
+
+impl<T> ItemOrder<T> {
+    /// Whether the items are in this order
+    pub fn is_satisfied_by<'a, I>(&self, items: I) -> bool
+    where
+        T: 'a,
+        I: IntoIterator<Item = &'a T>,
+        I::IntoIter: Clone,
+    {
+        let items = items.into_iter();
+        let mut pairs = items.clone().zip(items.clone().skip(1));
+
+        match *self {
+            Self::Any => true,
+            Self::Sorted(compare) => pairs.all(|(a, b)| compare(a, b).is_le()),
+            Self::StrictlySorted(compare) => pairs.all(|(a, b)| compare(a, b).is_lt()),
+            Self::Unique(compare) => {
+                let mut sorted: Vec<_> = items.collect();
+                sorted.sort_by(|a, b| compare(a, b));
+
+                sorted
+                    .windows(2)
+                    .all(|pair| compare(pair[0], pair[1]).is_ne())
+            }
+        }
+    }
+
+    /// Whether the items could be arranged in this order, for collections like
+    /// [`BinaryHeap`](std::collections::BinaryHeap) that don't keep the generated order
+    pub(crate) fn is_satisfiable_by<'a, I>(&self, items: I) -> bool
+    where
+        T: 'a,
+        I: IntoIterator<Item = &'a T>,
+        I::IntoIter: Clone,
+    {
+        match *self {
+            Self::Any | Self::Sorted(_) => true,
+            Self::StrictlySorted(compare) | Self::Unique(compare) => {
+                Self::Unique(compare).is_satisfied_by(items)
+            }
+        }
+    }
+}
+
+impl<T> Clone for ItemOrder<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ItemOrder<T> {}
+
+impl<T> Debug for ItemOrder<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Any => f.write_str("Any"),
+            Self::Sorted(compare) => f.debug_tuple("Sorted").field(compare).finish(),
+            Self::StrictlySorted(compare) => {
+                f.debug_tuple("StrictlySorted").field(compare).finish()
+            }
+            Self::Unique(compare) => f.debug_tuple("Unique").field(compare).finish(),
+        }
+    }
+}
+
+impl<T> Default for ItemOrder<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::Any
+    }
+}
+
+impl<T> PartialEq for ItemOrder<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Any, Self::Any) => true,
+            (Self::Sorted(a), Self::Sorted(b))
+            | (Self::StrictlySorted(a), Self::StrictlySorted(b))
+            | (Self::Unique(a), Self::Unique(b)) => *a as usize == *b as usize,
+            _ => false,
+        }
+    }
+}
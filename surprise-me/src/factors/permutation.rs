@@ -0,0 +1,111 @@
+use std::{any::type_name, mem::size_of};
+
+use rand::{prelude::Distribution, seq::SliceRandom, Rng};
+
+use crate::{
+    budget::spend_len, error::check_len, size::current_size, SurpriseError, TryDistribution,
+    Validate,
+};
+
+use super::LenDistribution;
+
+#[derive(Clone, Debug, PartialEq)]
+/// A surprise factor for permutations of `0..len`, i.e. vecs that contain every
+/// number from `0` up to their length exactly once in random order.
+///
+/// Since vecs already have a surprise factor, fields use this one through
+/// `#[factor = "PermutationSurprise"]`.
+///
+/// ```rust
+/// # use surprise_me::{factors::PermutationSurprise, Surprise};
+/// #
+/// #[derive(Surprise)]
+/// struct Shuffled {
+///     #[factor = "PermutationSurprise"]
+///     indices: Vec<usize>,
+/// }
+///
+/// let mut indices = Shuffled::generate(&mut surprise_me::rand::thread_rng()).indices;
+/// let len = indices.len();
+/// indices.sort();
+///
+/// assert!(indices.into_iter().eq(0..len));
+/// ```
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct PermutationSurprise {
+    /// The minimum length of generated permutations
+    pub min_len: usize,
+    /// The maximum length of generated permutations.
+    ///
+    /// Defaults to the [current size](crate::size::current_size).
+    pub max_len: usize,
+    /// How lengths are distributed between `min_len` and `max_len`, defaults to uniformly
+    pub len: LenDistribution,
+}
+
+impl PermutationSurprise {
+    #[allow(clippy::len_without_is_empty)]
+    /// Returns a random length within the `min_len` and `max_len` values, distributed by `len`
+    pub fn len<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
+        self.try_len(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns a random length within the `min_len` and `max_len` values, distributed by `len`,
+    /// or an error if `min_len` is greater than `max_len` or `len` is invalid
+    pub fn try_len<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<usize, SurpriseError> {
+        self.len.try_sample(rng, self.min_len, self.max_len)
+    }
+}
+
+impl<N: TryFrom<usize>> Distribution<Vec<N>> for PermutationSurprise {
+    #[inline]
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec<N> {
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<N: TryFrom<usize>> TryDistribution<Vec<N>> for PermutationSurprise {
+    /// Fails if the numbers up to the length don't fit into `N`
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<Vec<N>, SurpriseError> {
+        let len = spend_len(self.try_len(rng)?, self.min_len, size_of::<N>());
+
+        let mut permutation = (0..len)
+            .map(N::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| {
+                let reason = format!("{len} numbers don't fit into `{}`", type_name::<N>());
+
+                SurpriseError::new("max_len", reason)
+            })?;
+
+        permutation.shuffle(rng);
+
+        Ok(permutation)
+    }
+}
+
+impl Validate for PermutationSurprise {
+    #[inline]
+    fn problems(&self) -> Vec<SurpriseError> {
+        check_len(self.min_len, self.max_len)
+            .err()
+            .into_iter()
+            .chain(self.len.problem())
+            .collect()
+    }
+}
+
+impl Default for PermutationSurprise {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            min_len: 0,
+            max_len: current_size(),
+            len: LenDistribution::Uniform,
+        }
+    }
+}
@@ -2,7 +2,9 @@ use std::marker::PhantomData;
 
 use rand::{prelude::Distribution, Rng};
 
-use crate::Surprise;
+use crate::{
+    Enumerate, Enumerator, Shrink, Shrinker, Surprise, SurpriseError, SurpriseKey, TryDistribution,
+};
 
 use super::UnitSurprise;
 
@@ -11,11 +13,39 @@ pub type PhantomDataSurprise = UnitSurprise;
 
 impl<T> Surprise for PhantomData<T> {
     type Factor = PhantomDataSurprise;
+
+    delegate_to_factor!();
 }
 
+impl<T> SurpriseKey for PhantomData<T> {}
+
 impl<T> Distribution<PhantomData<T>> for PhantomDataSurprise {
     #[inline]
     fn sample<R: Rng + ?Sized>(&self, _: &mut R) -> PhantomData<T> {
         PhantomData
     }
 }
+
+impl<T> TryDistribution<PhantomData<T>> for PhantomDataSurprise {
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(&self, _: &mut R) -> Result<PhantomData<T>, SurpriseError> {
+        Ok(PhantomData)
+    }
+}
+
+impl<T> Shrink for PhantomData<T> {
+    #[inline]
+    fn shrink<'a>(&'a self, _: &'a PhantomDataSurprise) -> Shrinker<'a, Self> {
+        Box::new(std::iter::empty())
+    }
+}
+
+impl<T> Enumerate for PhantomData<T> {
+    #[inline]
+    fn enumerate<'a>(_: &'a PhantomDataSurprise) -> Enumerator<'a, Self>
+    where
+        Self: 'a,
+    {
+        Box::new(std::iter::once(PhantomData))
+    }
+}
@@ -1,17 +1,49 @@
-use rand::{
-    distributions::{Alphanumeric, Standard},
-    prelude::Distribution,
-    Rng,
+use rand::{prelude::Distribution, seq::SliceRandom, Rng};
+
+use crate::{
+    enumerate::outcomes,
+    error::{check_chance, check_probability, try_gen_bool},
+    Enumerate, Enumerator, Shrink, Shrinker, Surprise, SurpriseError, SurpriseKey, TryDistribution,
+    Validate,
 };
 
-use crate::Surprise;
+use super::NumberDistribution;
 
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "EmptyStruct", into = "EmptyStruct")
+)]
 /// The surprise factor of the unit type `()`
 pub struct UnitSurprise;
 
+/// Serde representation of [`UnitSurprise`] so that formats without
+/// a unit type, e.g. TOML, can handle it
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct EmptyStruct {}
+
+#[cfg(feature = "serde")]
+impl From<EmptyStruct> for UnitSurprise {
+    #[inline]
+    fn from(_: EmptyStruct) -> Self {
+        Self
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<UnitSurprise> for EmptyStruct {
+    #[inline]
+    fn from(_: UnitSurprise) -> Self {
+        Self {}
+    }
+}
+
 impl Surprise for () {
     type Factor = UnitSurprise;
+
+    delegate_to_factor!();
 }
 
 impl Distribution<()> for UnitSurprise {
@@ -19,24 +51,196 @@ impl Distribution<()> for UnitSurprise {
     fn sample<R: Rng + ?Sized>(&self, _: &mut R) {}
 }
 
+impl TryDistribution<()> for UnitSurprise {
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(&self, _: &mut R) -> Result<(), SurpriseError> {
+        Ok(())
+    }
+}
+
+impl Validate for UnitSurprise {
+    #[inline]
+    fn problems(&self) -> Vec<SurpriseError> {
+        Vec::new()
+    }
+}
+
+impl SurpriseKey for () {}
+
+impl Shrink for () {
+    #[inline]
+    fn shrink<'a>(&'a self, _: &'a UnitSurprise) -> Shrinker<'a, Self> {
+        Box::new(std::iter::empty())
+    }
+}
+
+impl Enumerate for () {
+    #[inline]
+    fn enumerate<'a>(_: &'a UnitSurprise) -> Enumerator<'a, Self>
+    where
+        Self: 'a,
+    {
+        Box::new(std::iter::once(()))
+    }
+}
+
 macro_rules! surprise_number {
     (INT: $( $ty:ident $(,)? )* ) => {
-        surprise_number!(@SURPRISE: $($ty,)*);
+        surprise_number!(@SURPRISE $($ty,)*);
 
         $(
+            impl Surprise for $ty {
+                type Factor = NumberSurprise<Self>;
+
+                delegate_to_factor!();
+
+                #[inline]
+                fn try_generate_with_valid_factor<R: Rng + ?Sized>(
+                    rng: &mut R,
+                    factor: &NumberSurprise<Self>,
+                ) -> Result<Self, SurpriseError> {
+                    Ok(factor.sample_valid(rng))
+                }
+            }
+
+            impl SurpriseKey for $ty {
+                #[inline]
+                fn bounded_factor(
+                    factor: &NumberSurprise<Self>,
+                    min: Option<&Self>,
+                    max: Option<&Self>,
+                ) -> Option<NumberSurprise<Self>> {
+                    let mut bounded = factor.clone();
+                    bounded.min = min.map_or(factor.min, |&min| factor.min.max(min));
+                    bounded.max = max.map_or(factor.max, |&max| factor.max.min(max));
+
+                    Some(bounded)
+                }
+
+                #[inline]
+                fn successor(&self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+
+                #[inline]
+                fn predecessor(&self) -> Option<Self> {
+                    self.checked_sub(1)
+                }
+            }
+
             impl Default for NumberSurprise<$ty> {
                 #[inline]
                 fn default() -> Self {
                     Self {
                         min: $ty::MIN,
                         max: $ty::MAX,
+                        edge_chance: 0.0,
+                        distribution: NumberDistribution::Uniform,
+                    }
+                }
+            }
+
+            impl NumberSurprise<$ty> {
+                /// Returns the edge cases within the bounds in ascending order.
+                ///
+                /// These are `min`, `max`, the numbers right next to them, as well as `0`, `1` and `-1`.
+                pub fn edge_cases(&self) -> Vec<$ty> {
+                    let candidates = [
+                        Some(self.min),
+                        self.min.checked_add(1),
+                        self.max.checked_sub(1),
+                        Some(self.max),
+                        Some(0),
+                        Some(1),
+                        (0 as $ty).checked_sub(1),
+                    ];
+
+                    let mut edges: Vec<_> = candidates
+                        .into_iter()
+                        .flatten()
+                        .filter(|n| (self.min..=self.max).contains(n))
+                        .collect();
+
+                    edges.sort_unstable();
+                    edges.dedup();
+
+                    edges
+                }
+
+                /// Rounds a sample of the distribution to the nearest number within the bounds.
+                ///
+                /// Beyond 2^53 the gap between floats is larger than 1, so a uniform offset
+                /// within that gap picks one of the numbers that round to the sample.
+                pub(super) fn round_sample<R: Rng + ?Sized>(&self, rng: &mut R, n: f64) -> $ty {
+                    // Same as `next_up` which requires Rust 1.86
+                    let gap = f64::from_bits(n.abs().to_bits() + 1) - n.abs();
+
+                    if gap <= 1.0 {
+                        return (n.round() as $ty).clamp(self.min, self.max);
+                    }
+
+                    let low = ((n - gap / 2.0) as $ty).clamp(self.min, self.max);
+                    let high = ((n + gap / 2.0) as $ty).clamp(self.min, self.max);
+
+                    rng.gen_range(low..=high)
+                }
+            }
+
+            impl Validate for NumberSurprise<$ty> {
+                #[inline]
+                fn problems(&self) -> Vec<SurpriseError> {
+                    let mut problems = Vec::new();
+
+                    if self.min > self.max {
+                        let reason = format!("{} is greater than `max` ({})", self.min, self.max);
+
+                        problems.push(SurpriseError::new("min", reason));
+                    }
+
+                    problems.extend(check_probability("edge_chance", self.edge_chance).err());
+                    problems.extend(self.distribution.problem());
+
+                    problems
+                }
+            }
+
+            impl Shrink for $ty {
+                /// Shrinks toward `0` or, if `0` is out of bounds, toward whichever bound is closer to it.
+                fn shrink<'a>(&'a self, factor: &'a NumberSurprise<$ty>) -> Shrinker<'a, Self> {
+                    let n = *self;
+                    let target = (0 as $ty).max(factor.min).min(factor.max);
+
+                    if n == target {
+                        return Box::new(std::iter::empty());
                     }
+
+                    // Values out of bounds can be too far from the target to subtract it
+                    let Some(diff) = n.checked_sub(target) else {
+                        return Box::new(std::iter::once(target));
+                    };
+
+                    // Approach the target by halving the distance to it
+                    let halve = |diff: &$ty| Some(*diff / 2).filter(|diff| *diff != 0);
+                    let halves = std::iter::successors(halve(&diff), halve);
+
+                    Box::new(std::iter::once(target).chain(halves.map(move |diff| n - diff)))
+                }
+            }
+
+            impl Enumerate for $ty {
+                /// Enumerates all numbers from `min` to `max`
+                #[inline]
+                fn enumerate<'a>(factor: &'a NumberSurprise<$ty>) -> Enumerator<'a, Self>
+                where
+                    Self: 'a,
+                {
+                    Box::new(factor.min..=factor.max)
                 }
             }
         )*
     };
     (FLOAT: $( $ty:ident $(,)? )* ) => {
-        surprise_number!(@SURPRISE: $($ty,)*);
+        surprise_number!(@SURPRISE $($ty,)*);
 
         $(
             impl Default for NumberSurprise<$ty> {
@@ -45,21 +249,133 @@ macro_rules! surprise_number {
                     Self {
                         min: 0.0,
                         max: 1.0,
+                        edge_chance: 0.0,
+                        distribution: NumberDistribution::Uniform,
                     }
                 }
             }
+
+            impl NumberSurprise<$ty> {
+                /// Returns the edge cases within the bounds in ascending order.
+                ///
+                /// These are `min`, `max`, the numbers right next to them, as well as
+                /// `0.0`, `-0.0`, `1.0`, `-1.0` and the smallest positive normal number.
+                pub fn edge_cases(&self) -> Vec<$ty> {
+                    // Same as `next_up` which requires Rust 1.86
+                    let next_up = |n: $ty| {
+                        if n.is_nan() || n == $ty::INFINITY {
+                            n
+                        } else if n == 0.0 {
+                            $ty::from_bits(1)
+                        } else if n > 0.0 {
+                            $ty::from_bits(n.to_bits() + 1)
+                        } else {
+                            $ty::from_bits(n.to_bits() - 1)
+                        }
+                    };
+
+                    let candidates = [
+                        self.min,
+                        next_up(self.min),
+                        -next_up(-self.max),
+                        self.max,
+                        0.0,
+                        -0.0,
+                        1.0,
+                        -1.0,
+                        $ty::MIN_POSITIVE,
+                    ];
+
+                    let mut edges: Vec<_> = candidates
+                        .into_iter()
+                        .filter(|n| (self.min..=self.max).contains(n))
+                        .collect();
+
+                    edges.sort_unstable_by($ty::total_cmp);
+                    edges.dedup_by(|a, b| a.to_bits() == b.to_bits());
+
+                    edges
+                }
+
+                /// Clamps a sample of the distribution to the bounds
+                #[inline]
+                pub(super) fn round_sample<R: Rng + ?Sized>(&self, _: &mut R, n: f64) -> $ty {
+                    (n as $ty).clamp(self.min, self.max)
+                }
+            }
+
+            impl Validate for NumberSurprise<$ty> {
+                fn problems(&self) -> Vec<SurpriseError> {
+                    let (min, max) = (self.min, self.max);
+                    let mut problems: Vec<_> = check_probability("edge_chance", self.edge_chance)
+                        .err()
+                        .into_iter()
+                        .collect();
+
+                    if !min.is_finite() {
+                        problems.push(SurpriseError::new("min", format!("{min} is not finite")));
+                    }
+
+                    if !max.is_finite() {
+                        problems.push(SurpriseError::new("max", format!("{max} is not finite")));
+                    }
+
+                    problems.extend(self.distribution.problem());
+
+                    if !(min.is_finite() && max.is_finite()) {
+                        return problems;
+                    }
+
+                    if min > max {
+                        let reason = format!("{min} is greater than `max` ({max})");
+
+                        problems.push(SurpriseError::new("min", reason));
+                    } else if !(max - min).is_finite() {
+                        let reason = format!("the range from `min` ({min}) to {max} is too large");
+
+                        problems.push(SurpriseError::new("max", reason));
+                    }
+
+                    problems
+                }
+            }
         )*
     };
-    (@SURPRISE: $( $ty:ident $(,)? )* ) => {
+    (@SURPRISE $( $ty:ident $(,)? )* ) => {
         $(
-            impl Surprise for $ty {
-                type Factor = NumberSurprise<Self>;
-            }
-
             impl Distribution<$ty> for NumberSurprise<$ty> {
                 #[inline]
                 fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> $ty {
-                    rng.gen_range(self.min..=self.max)
+                    self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+                }
+            }
+
+            impl TryDistribution<$ty> for NumberSurprise<$ty> {
+                #[inline]
+                fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<$ty, SurpriseError> {
+                    match self.problems().into_iter().next() {
+                        Some(problem) => Err(problem),
+                        None => Ok(self.sample_valid(rng)),
+                    }
+                }
+            }
+
+            impl NumberSurprise<$ty> {
+                /// Generates a number, assuming that the surprise factor has no problems
+                pub(crate) fn sample_valid<R: Rng + ?Sized>(&self, rng: &mut R) -> $ty {
+                    if self.edge_chance > 0.0 && rng.gen_bool(self.edge_chance) {
+                        if let Some(&edge) = self.edge_cases().choose(rng) {
+                            return edge;
+                        }
+                    }
+
+                    if let NumberDistribution::Uniform = self.distribution {
+                        return rng.gen_range(self.min..=self.max);
+                    }
+
+                    let n = self.distribution.sample_valid(rng, self.min as f64, self.max as f64);
+
+                    self.round_sample(rng, n)
                 }
             }
         )*
@@ -71,7 +387,15 @@ surprise_number!(INT: i8, i16, i32, i64, i128, isize);
 surprise_number!(FLOAT: f32, f64);
 
 #[derive(Clone, Debug, PartialEq)]
-/// The surprise factor of numbers
+/// The surprise factor of integers and the bounds of ranges.
+///
+/// Floats are generated through [`FloatSurprise`](super::FloatSurprise) which additionally
+/// supports special values like NaN.
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct NumberSurprise<N> {
     /// The minimum value of generated numbers.
     ///
@@ -81,14 +405,28 @@ pub struct NumberSurprise<N> {
     ///
     /// For integers the default is `MAX`, for `f32` and `f64` it's `1.0`
     pub max: N,
+    /// Probability to generate one of the [edge cases](NumberSurprise::edge_cases)
+    /// instead of a uniformly distributed number.
+    ///
+    /// Should be between `0.0` and `1.0`, the default is `0.0` so that edge cases are opt-in.
+    pub edge_chance: f64,
+    /// How numbers are distributed between `min` and `max`, defaults to uniformly
+    pub distribution: NumberDistribution,
 }
 
 impl Surprise for bool {
     type Factor = BoolSurprise;
+
+    delegate_to_factor!();
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 /// The surprise factor of [`bool`]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct BoolSurprise {
     /// Probability for `true`.
     ///
@@ -96,46 +434,51 @@ pub struct BoolSurprise {
     pub chance: f64,
 }
 
+impl SurpriseKey for bool {}
+
 impl Distribution<bool> for BoolSurprise {
     #[inline]
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> bool {
-        rng.gen_bool(self.chance)
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
     }
 }
 
-impl Default for BoolSurprise {
+impl TryDistribution<bool> for BoolSurprise {
     #[inline]
-    fn default() -> Self {
-        Self { chance: 0.5 }
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<bool, SurpriseError> {
+        try_gen_bool(rng, self.chance)
     }
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-/// The surprise factor of [`char`]
-pub enum CharSurprise {
-    /// Only generates ASCII characters
-    Ascii,
-    /// Generates UTF-8 characters
-    Unicode,
+impl Validate for BoolSurprise {
+    #[inline]
+    fn problems(&self) -> Vec<SurpriseError> {
+        check_chance(self.chance).err().into_iter().collect()
+    }
 }
 
-impl Default for CharSurprise {
+impl Default for BoolSurprise {
     #[inline]
     fn default() -> Self {
-        Self::Ascii
+        Self { chance: 0.5 }
     }
 }
 
-impl Surprise for char {
-    type Factor = CharSurprise;
+impl Shrink for bool {
+    /// Shrinks `true` to `false` unless the factor always generates `true`
+    #[inline]
+    fn shrink<'a>(&'a self, factor: &'a BoolSurprise) -> Shrinker<'a, Self> {
+        Box::new((*self && factor.chance < 1.0).then_some(false).into_iter())
+    }
 }
 
-impl Distribution<char> for CharSurprise {
+impl Enumerate for bool {
+    /// Enumerates `false` and `true` unless the factor never generates them
     #[inline]
-    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> char {
-        match self {
-            Self::Ascii => rng.sample(Alphanumeric) as char,
-            Self::Unicode => rng.sample(Standard),
-        }
+    fn enumerate<'a>(factor: &'a BoolSurprise) -> Enumerator<'a, Self>
+    where
+        Self: 'a,
+    {
+        outcomes(factor.chance)
     }
 }
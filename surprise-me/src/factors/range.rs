@@ -2,11 +2,34 @@ use std::ops::{Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToIncl
 
 use rand::{prelude::Distribution, Rng};
 
-use crate::Surprise;
+use crate::{Shrink, Shrinker, Surprise, SurpriseError, SurpriseFactor, TryDistribution, Validate};
 
 use super::{NumberSurprise, UnitSurprise};
 
+/// Shrinks a number through the surprise factor of its type, e.g. [`FloatSurprise`](super::FloatSurprise) for floats
+fn shrink_number<N>(n: &N, factor: &NumberSurprise<N>) -> Vec<N>
+where
+    N: Shrink,
+    NumberSurprise<N>: Clone,
+    SurpriseFactor<N>: From<NumberSurprise<N>>,
+{
+    let factor = SurpriseFactor::<N>::from(factor.clone());
+
+    n.shrink(&factor).collect()
+}
+
 /// The surprise factor of [`Range`]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        default,
+        bound(
+            serialize = "NumberSurprise<N>: serde::Serialize",
+            deserialize = "NumberSurprise<N>: serde::Deserialize<'de> + Default"
+        )
+    )
+)]
 pub struct RangeSurprise<N> {
     /// The surprise factor of the range's start value
     pub start: NumberSurprise<N>,
@@ -16,18 +39,88 @@ pub struct RangeSurprise<N> {
 
 impl<N> Surprise for Range<N>
 where
-    NumberSurprise<N>: Distribution<N>,
+    NumberSurprise<N>: Distribution<N> + TryDistribution<N> + Validate,
 {
     type Factor = RangeSurprise<N>;
+
+    delegate_to_factor!();
 }
 
 impl<N> Distribution<Range<N>> for RangeSurprise<N>
 where
-    NumberSurprise<N>: Distribution<N>,
+    NumberSurprise<N>: Distribution<N> + TryDistribution<N> + Validate,
 {
     #[inline]
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Range<N> {
-        self.start.sample(rng)..self.end.sample(rng)
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<N> TryDistribution<Range<N>> for RangeSurprise<N>
+where
+    NumberSurprise<N>: Distribution<N> + TryDistribution<N> + Validate,
+{
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<Range<N>, SurpriseError> {
+        let start = self
+            .start
+            .try_sample(rng)
+            .map_err(|err| err.in_field("start"))?;
+        let end = self
+            .end
+            .try_sample(rng)
+            .map_err(|err| err.in_field("end"))?;
+
+        Ok(start..end)
+    }
+}
+
+impl<N> Default for RangeSurprise<N>
+where
+    NumberSurprise<N>: Default,
+{
+    #[inline]
+    fn default() -> Self {
+        Self {
+            start: Default::default(),
+            end: Default::default(),
+        }
+    }
+}
+
+impl<N> Validate for RangeSurprise<N>
+where
+    NumberSurprise<N>: Validate,
+{
+    fn problems(&self) -> Vec<SurpriseError> {
+        let start = self.start.problems().into_iter();
+        let end = self.end.problems().into_iter();
+
+        start
+            .map(|problem| problem.in_field("start"))
+            .chain(end.map(|problem| problem.in_field("end")))
+            .collect()
+    }
+}
+
+impl<N> Shrink for Range<N>
+where
+    N: Shrink + Clone,
+    NumberSurprise<N>: Distribution<N> + TryDistribution<N> + Validate + Clone,
+    SurpriseFactor<N>: From<NumberSurprise<N>>,
+{
+    /// Shrinks the start, then the end
+    #[inline]
+    fn shrink<'a>(&'a self, factor: &'a RangeSurprise<N>) -> Shrinker<'a, Self> {
+        let start = shrink_number(&self.start, &factor.start);
+        let end = shrink_number(&self.end, &factor.end);
+
+        Box::new(
+            start
+                .into_iter()
+                .map(move |start| start..self.end.clone())
+                .chain(end.into_iter().map(move |end| self.start.clone()..end)),
+        )
     }
 }
 
@@ -36,18 +129,60 @@ pub type RangeInclusiveSurprise<N> = RangeSurprise<N>;
 
 impl<N> Surprise for RangeInclusive<N>
 where
-    NumberSurprise<N>: Distribution<N>,
+    NumberSurprise<N>: Distribution<N> + TryDistribution<N> + Validate,
 {
     type Factor = RangeInclusiveSurprise<N>;
+
+    delegate_to_factor!();
 }
 
 impl<N> Distribution<RangeInclusive<N>> for RangeInclusiveSurprise<N>
 where
-    NumberSurprise<N>: Distribution<N>,
+    NumberSurprise<N>: Distribution<N> + TryDistribution<N> + Validate,
 {
     #[inline]
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> RangeInclusive<N> {
-        self.start.sample(rng)..=self.end.sample(rng)
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<N> TryDistribution<RangeInclusive<N>> for RangeInclusiveSurprise<N>
+where
+    NumberSurprise<N>: Distribution<N> + TryDistribution<N> + Validate,
+{
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<RangeInclusive<N>, SurpriseError> {
+        let start = self
+            .start
+            .try_sample(rng)
+            .map_err(|err| err.in_field("start"))?;
+        let end = self
+            .end
+            .try_sample(rng)
+            .map_err(|err| err.in_field("end"))?;
+
+        Ok(start..=end)
+    }
+}
+
+impl<N> Shrink for RangeInclusive<N>
+where
+    N: Shrink + Clone,
+    NumberSurprise<N>: Distribution<N> + TryDistribution<N> + Validate + Clone,
+    SurpriseFactor<N>: From<NumberSurprise<N>>,
+{
+    /// Shrinks the start, then the end
+    #[inline]
+    fn shrink<'a>(&'a self, factor: &'a RangeInclusiveSurprise<N>) -> Shrinker<'a, Self> {
+        let start = shrink_number(self.start(), &factor.start);
+        let end = shrink_number(self.end(), &factor.end);
+
+        Box::new(
+            start
+                .into_iter()
+                .map(move |start| start..=self.end().clone())
+                .chain(end.into_iter().map(move |end| self.start().clone()..=end)),
+        )
     }
 }
 
@@ -56,6 +191,8 @@ pub type RangeFullSurprise = UnitSurprise;
 
 impl Surprise for RangeFull {
     type Factor = RangeFullSurprise;
+
+    delegate_to_factor!();
 }
 
 impl Distribution<RangeFull> for RangeFullSurprise {
@@ -65,23 +202,66 @@ impl Distribution<RangeFull> for RangeFullSurprise {
     }
 }
 
+impl TryDistribution<RangeFull> for RangeFullSurprise {
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(&self, _: &mut R) -> Result<RangeFull, SurpriseError> {
+        Ok(..)
+    }
+}
+
+impl Shrink for RangeFull {
+    #[inline]
+    fn shrink<'a>(&'a self, _: &'a RangeFullSurprise) -> Shrinker<'a, Self> {
+        Box::new(std::iter::empty())
+    }
+}
+
 /// The surprise factor of [`RangeFrom`]
 pub type RangeFromSurprise<N> = NumberSurprise<N>;
 
 impl<N> Surprise for RangeFrom<N>
 where
-    NumberSurprise<N>: Distribution<N>,
+    NumberSurprise<N>: Distribution<N> + TryDistribution<N> + Validate,
 {
     type Factor = RangeFromSurprise<N>;
+
+    delegate_to_factor!();
 }
 
 impl<N> Distribution<RangeFrom<N>> for RangeFromSurprise<N>
 where
-    NumberSurprise<N>: Distribution<N>,
+    NumberSurprise<N>: Distribution<N> + TryDistribution<N> + Validate,
 {
     #[inline]
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> RangeFrom<N> {
-        rng.sample::<N, _>(self)..
+        TryDistribution::<RangeFrom<N>>::try_sample(self, rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<N> TryDistribution<RangeFrom<N>> for RangeFromSurprise<N>
+where
+    NumberSurprise<N>: Distribution<N> + TryDistribution<N> + Validate,
+{
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<RangeFrom<N>, SurpriseError> {
+        Ok(TryDistribution::<N>::try_sample(self, rng)?..)
+    }
+}
+
+impl<N> Shrink for RangeFrom<N>
+where
+    N: Shrink,
+    NumberSurprise<N>: Distribution<N> + TryDistribution<N> + Validate + Clone,
+    SurpriseFactor<N>: From<NumberSurprise<N>>,
+{
+    /// Shrinks the start
+    #[inline]
+    fn shrink<'a>(&'a self, factor: &'a RangeFromSurprise<N>) -> Shrinker<'a, Self> {
+        Box::new(
+            shrink_number(&self.start, factor)
+                .into_iter()
+                .map(|start| start..),
+        )
     }
 }
 
@@ -90,18 +270,47 @@ pub type RangeToSurprise<N> = NumberSurprise<N>;
 
 impl<N> Surprise for RangeTo<N>
 where
-    NumberSurprise<N>: Distribution<N>,
+    NumberSurprise<N>: Distribution<N> + TryDistribution<N> + Validate,
 {
     type Factor = RangeToSurprise<N>;
+
+    delegate_to_factor!();
 }
 
 impl<N> Distribution<RangeTo<N>> for RangeToSurprise<N>
 where
-    NumberSurprise<N>: Distribution<N>,
+    NumberSurprise<N>: Distribution<N> + TryDistribution<N> + Validate,
 {
     #[inline]
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> RangeTo<N> {
-        ..rng.sample::<N, _>(self)
+        TryDistribution::<RangeTo<N>>::try_sample(self, rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<N> TryDistribution<RangeTo<N>> for RangeToSurprise<N>
+where
+    NumberSurprise<N>: Distribution<N> + TryDistribution<N> + Validate,
+{
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<RangeTo<N>, SurpriseError> {
+        Ok(..TryDistribution::<N>::try_sample(self, rng)?)
+    }
+}
+
+impl<N> Shrink for RangeTo<N>
+where
+    N: Shrink,
+    NumberSurprise<N>: Distribution<N> + TryDistribution<N> + Validate + Clone,
+    SurpriseFactor<N>: From<NumberSurprise<N>>,
+{
+    /// Shrinks the end
+    #[inline]
+    fn shrink<'a>(&'a self, factor: &'a RangeToSurprise<N>) -> Shrinker<'a, Self> {
+        Box::new(
+            shrink_number(&self.end, factor)
+                .into_iter()
+                .map(|end| ..end),
+        )
     }
 }
 
@@ -110,17 +319,50 @@ pub type RangeToInclusiveSurprise<N> = NumberSurprise<N>;
 
 impl<N> Surprise for RangeToInclusive<N>
 where
-    NumberSurprise<N>: Distribution<N>,
+    NumberSurprise<N>: Distribution<N> + TryDistribution<N> + Validate,
 {
     type Factor = RangeToInclusiveSurprise<N>;
+
+    delegate_to_factor!();
 }
 
 impl<N> Distribution<RangeToInclusive<N>> for RangeToInclusiveSurprise<N>
 where
-    NumberSurprise<N>: Distribution<N>,
+    NumberSurprise<N>: Distribution<N> + TryDistribution<N> + Validate,
 {
     #[inline]
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> RangeToInclusive<N> {
-        ..=rng.sample::<N, _>(self)
+        TryDistribution::<RangeToInclusive<N>>::try_sample(self, rng)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<N> TryDistribution<RangeToInclusive<N>> for RangeToInclusiveSurprise<N>
+where
+    NumberSurprise<N>: Distribution<N> + TryDistribution<N> + Validate,
+{
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+    ) -> Result<RangeToInclusive<N>, SurpriseError> {
+        Ok(..=TryDistribution::<N>::try_sample(self, rng)?)
+    }
+}
+
+impl<N> Shrink for RangeToInclusive<N>
+where
+    N: Shrink,
+    NumberSurprise<N>: Distribution<N> + TryDistribution<N> + Validate + Clone,
+    SurpriseFactor<N>: From<NumberSurprise<N>>,
+{
+    /// Shrinks the end
+    #[inline]
+    fn shrink<'a>(&'a self, factor: &'a RangeToInclusiveSurprise<N>) -> Shrinker<'a, Self> {
+        Box::new(
+            shrink_number(&self.end, factor)
+                .into_iter()
+                .map(|end| ..=end),
+        )
     }
 }
@@ -0,0 +1,100 @@
+use rand::Rng;
+
+use crate::{
+    error::{check_chance, check_len},
+    SurpriseError, Validate,
+};
+
+use super::LenDistribution;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+/// The surprise factor of an `Option<Box<Self>>` field within a type that
+/// derives [`Surprise`] with `#[surprise(recursive(...))]`.
+///
+/// Once the maximum depth is reached, the field is always `None`.
+///
+/// [`Surprise`]: crate::Surprise
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct RecursiveOptionSurprise {
+    /// Probability for `Some` as long as the maximum depth isn't reached.
+    ///
+    /// Should be between `0.0` and `1.0`.
+    pub chance: f64,
+}
+
+impl Validate for RecursiveOptionSurprise {
+    #[inline]
+    fn problems(&self) -> Vec<SurpriseError> {
+        check_chance(self.chance).err().into_iter().collect()
+    }
+}
+
+impl Default for RecursiveOptionSurprise {
+    #[inline]
+    fn default() -> Self {
+        Self { chance: 0.5 }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// The surprise factor of a `Vec<Self>` field within a type that
+/// derives [`Surprise`] with `#[surprise(recursive(...))]`.
+///
+/// Once the maximum depth is reached, the field is always empty.
+///
+/// [`Surprise`]: crate::Surprise
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct RecursiveVecSurprise {
+    /// The minimum length of generated vecs as long as the maximum depth isn't reached
+    pub min_len: usize,
+    /// The maximum length of generated vecs
+    ///
+    /// Defaults to `4` since the amount of items grows exponentially with the depth.
+    pub max_len: usize,
+    /// How lengths are distributed between `min_len` and `max_len`, defaults to uniformly
+    pub len: LenDistribution,
+}
+
+impl RecursiveVecSurprise {
+    #[allow(clippy::len_without_is_empty)]
+    /// Returns a random length within the `min_len` and `max_len` values, distributed by `len`
+    pub fn len<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
+        self.try_len(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns a random length within the `min_len` and `max_len` values, distributed by `len`,
+    /// or an error if `min_len` is greater than `max_len` or `len` is invalid
+    pub fn try_len<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<usize, SurpriseError> {
+        self.len.try_sample(rng, self.min_len, self.max_len)
+    }
+}
+
+impl Validate for RecursiveVecSurprise {
+    #[inline]
+    fn problems(&self) -> Vec<SurpriseError> {
+        check_len(self.min_len, self.max_len)
+            .err()
+            .into_iter()
+            .chain(self.len.problem())
+            .collect()
+    }
+}
+
+impl Default for RecursiveVecSurprise {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            min_len: 0,
+            max_len: 4,
+            len: LenDistribution::Uniform,
+        }
+    }
+}
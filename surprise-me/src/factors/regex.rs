@@ -0,0 +1,362 @@
+#[cfg(feature = "regex")]
+use std::fmt::Display;
+use std::{
+    cmp::Ordering,
+    fmt::{Debug, Formatter, Result as FmtResult},
+};
+
+use rand::{prelude::Distribution, Rng};
+#[cfg(feature = "regex")]
+use regex_syntax::{
+    ast::{
+        self, Ast, ClassAscii, ClassAsciiKind, ClassBracketed, ClassPerl, ClassPerlKind, ClassSet,
+        ClassSetItem,
+    },
+    hir::{translate::Translator, Class, HirKind},
+};
+
+#[cfg(feature = "regex")]
+use crate::size::current_size;
+use crate::SurpriseError;
+
+/// A regex that generated strings match, see [`StringSurprise::regex`](super::StringSurprise::regex).
+///
+/// Compiling regexes requires the `regex` feature.
+///
+/// In derived surprise factors, it is set through `#[factor(regex = "...")]` which
+/// panics when the default surprise factor is created if the regex is not supported.
+///
+/// ```rust
+/// # use surprise_me::Surprise;
+/// #
+/// # #[cfg(feature = "regex")]
+/// #[derive(Surprise)]
+/// struct User {
+///     #[factor(regex = r"[a-z]{3,10}@[a-z]{3,10}\.(com|org)")]
+///     email: String,
+///     #[factor(regex = "[0-9a-f]{40}")]
+///     commit: String,
+/// }
+/// ```
+#[derive(Clone)]
+pub struct StringRegex {
+    pattern: String,
+    #[cfg(feature = "regex")]
+    regex: rand_regex::Regex,
+}
+
+impl StringRegex {
+    /// Compiles the regex `pattern`.
+    ///
+    /// Unlike in the `regex` crate, the classes `\d`, `\w` and `\s` only match ASCII
+    /// characters, so that generated strings pass ASCII-only validators. Unicode classes
+    /// are written out as e.g. `\p{Nd}` instead, while `.` and negated classes such as
+    /// `\D` still match any character.
+    ///
+    /// Repetitions without upper bound such as `*` or `+` repeat up to the
+    /// [current size](crate::size::current_size) times more than their minimum.
+    ///
+    /// Fails if the pattern is invalid or contains anchors or word boundaries,
+    /// and always fails without the `regex` feature.
+    #[cfg(feature = "regex")]
+    pub fn new(pattern: &str) -> Result<Self, SurpriseError> {
+        let max_repeat = u32::try_from(current_size()).unwrap_or(u32::MAX);
+        let invalid =
+            |err: &dyn Display| SurpriseError::new("regex", format!("`{pattern}`: {err}"));
+
+        let mut ast = ast::parse::Parser::new()
+            .parse(pattern)
+            .map_err(|err| invalid(&err))?;
+        ascii_perl_classes(&mut ast);
+
+        let hir = Translator::new()
+            .translate(pattern, &ast)
+            .map_err(|err| invalid(&err))?;
+        let regex = rand_regex::Regex::with_hir(hir, max_repeat).map_err(|err| invalid(&err))?;
+
+        Ok(Self {
+            pattern: pattern.to_owned(),
+            regex,
+        })
+    }
+
+    /// Compiling regexes requires the `regex` feature
+    #[cfg(not(feature = "regex"))]
+    pub fn new(pattern: &str) -> Result<Self, SurpriseError> {
+        let reason = format!("`{pattern}`: regexes require the `regex` feature");
+
+        Err(SurpriseError::new("regex", reason))
+    }
+
+    /// The pattern that the regex was compiled from
+    #[inline]
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+}
+
+/// Replaces the Perl classes `\d`, `\w` and `\s` with their ASCII counterparts
+#[cfg(feature = "regex")]
+fn ascii_perl_classes(ast: &mut Ast) {
+    match ast {
+        Ast::ClassPerl(class) => {
+            let span = class.span;
+            let ascii = ClassSetItem::Ascii(ascii_class(class));
+
+            *ast = Ast::class_bracketed(ClassBracketed {
+                span,
+                negated: false,
+                kind: ClassSet::Item(ascii),
+            });
+        }
+        Ast::ClassBracketed(class) => ascii_class_set(&mut class.kind),
+        Ast::Repetition(repetition) => ascii_perl_classes(&mut repetition.ast),
+        Ast::Group(group) => ascii_perl_classes(&mut group.ast),
+        Ast::Alternation(alternation) => alternation.asts.iter_mut().for_each(ascii_perl_classes),
+        Ast::Concat(concat) => concat.asts.iter_mut().for_each(ascii_perl_classes),
+        _ => {}
+    }
+}
+
+#[cfg(feature = "regex")]
+fn ascii_class_set(set: &mut ClassSet) {
+    match set {
+        ClassSet::Item(item) => ascii_class_set_item(item),
+        ClassSet::BinaryOp(op) => {
+            ascii_class_set(&mut op.lhs);
+            ascii_class_set(&mut op.rhs);
+        }
+    }
+}
+
+#[cfg(feature = "regex")]
+fn ascii_class_set_item(item: &mut ClassSetItem) {
+    match item {
+        ClassSetItem::Perl(class) => *item = ClassSetItem::Ascii(ascii_class(class)),
+        ClassSetItem::Bracketed(class) => ascii_class_set(&mut class.kind),
+        ClassSetItem::Union(union) => union.items.iter_mut().for_each(ascii_class_set_item),
+        _ => {}
+    }
+}
+
+#[cfg(feature = "regex")]
+fn ascii_class(class: &ClassPerl) -> ClassAscii {
+    let kind = match class.kind {
+        ClassPerlKind::Digit => ClassAsciiKind::Digit,
+        ClassPerlKind::Space => ClassAsciiKind::Space,
+        ClassPerlKind::Word => ClassAsciiKind::Word,
+    };
+
+    ClassAscii {
+        span: class.span,
+        kind,
+        negated: class.negated,
+    }
+}
+
+impl Distribution<String> for StringRegex {
+    #[cfg(feature = "regex")]
+    #[inline]
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> String {
+        self.regex.sample(rng)
+    }
+
+    #[cfg(not(feature = "regex"))]
+    fn sample<R: Rng + ?Sized>(&self, _rng: &mut R) -> String {
+        unreachable!("regexes can't be compiled without the `regex` feature")
+    }
+}
+
+impl Debug for StringRegex {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_tuple("StringRegex").field(&self.pattern).finish()
+    }
+}
+
+impl PartialEq for StringRegex {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for StringRegex {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.pattern)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for StringRegex {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pattern = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+
+        Self::new(&pattern).map_err(serde::de::Error::custom)
+    }
+}
+
+/// A class of characters written like in a regex, e.g. `[a-z0-9]`, a Unicode general
+/// category such as `\p{Lu}` or `\p{Punctuation}`, or a script such as `\p{Cyrillic}`,
+/// `\p{Han}` or `\p{Arabic}`. Emoji are available through `\p{Emoji_Presentation}`.
+///
+/// All characters of the class are equally likely.
+///
+/// In derived surprise factors, it is set through `#[factor(char_set = "...")]` which
+/// panics when the default surprise factor is created if the class is invalid.
+///
+/// Parsing classes requires the `regex` feature.
+#[derive(Clone)]
+pub struct CharClass {
+    pattern: String,
+    /// Inclusive ranges of the class, split around surrogates
+    pub(super) ranges: Vec<(char, char)>,
+    /// The number of characters up to and including each range
+    counts: Vec<u32>,
+}
+
+impl CharClass {
+    /// Parses the class `pattern`.
+    ///
+    /// Fails if the pattern is not a single character class or if it contains no characters,
+    /// and always fails without the `regex` feature.
+    #[cfg(feature = "regex")]
+    pub fn new(pattern: &str) -> Result<Self, SurpriseError> {
+        let invalid = |reason: &dyn std::fmt::Display| {
+            SurpriseError::new("class", format!("`{pattern}`: {reason}"))
+        };
+
+        let hir = regex_syntax::Parser::new()
+            .parse(pattern)
+            .map_err(|err| invalid(&err))?;
+
+        let class_ranges: Vec<(char, char)> = match hir.into_kind() {
+            HirKind::Class(Class::Unicode(class)) => class
+                .ranges()
+                .iter()
+                .map(|range| (range.start(), range.end()))
+                .collect(),
+            HirKind::Class(Class::Bytes(class)) => class
+                .to_unicode_class()
+                .ok_or_else(|| invalid(&"class contains bytes that are not characters"))?
+                .ranges()
+                .iter()
+                .map(|range| (range.start(), range.end()))
+                .collect(),
+            // Classes with a single character are turned into literals
+            HirKind::Literal(literal) => match std::str::from_utf8(&literal.0) {
+                Ok(c) if c.chars().count() == 1 => {
+                    let c = c.chars().next().unwrap();
+
+                    vec![(c, c)]
+                }
+                _ => return Err(invalid(&"not a character class")),
+            },
+            _ => return Err(invalid(&"not a character class")),
+        };
+
+        let mut ranges = Vec::with_capacity(class_ranges.len());
+
+        for (start, end) in class_ranges {
+            if (start as u32) < 0xd800 && (end as u32) > 0xdfff {
+                ranges.push((start, '\u{d7ff}'));
+                ranges.push(('\u{e000}', end));
+            } else {
+                ranges.push((start, end));
+            }
+        }
+
+        if ranges.is_empty() {
+            return Err(invalid(&"class contains no characters"));
+        }
+
+        let counts = ranges
+            .iter()
+            .scan(0, |count, (start, end)| {
+                *count += *end as u32 - *start as u32 + 1;
+
+                Some(*count)
+            })
+            .collect();
+
+        Ok(Self {
+            pattern: pattern.to_owned(),
+            ranges,
+            counts,
+        })
+    }
+
+    /// Parsing classes requires the `regex` feature
+    #[cfg(not(feature = "regex"))]
+    pub fn new(pattern: &str) -> Result<Self, SurpriseError> {
+        let reason = format!("`{pattern}`: classes require the `regex` feature");
+
+        Err(SurpriseError::new("class", reason))
+    }
+
+    /// The pattern that the class was parsed from
+    #[inline]
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Whether the class contains the character
+    pub fn contains(&self, c: char) -> bool {
+        self.ranges
+            .binary_search_by(|(start, end)| {
+                if *end < c {
+                    Ordering::Less
+                } else if *start > c {
+                    Ordering::Greater
+                } else {
+                    Ordering::Equal
+                }
+            })
+            .is_ok()
+    }
+}
+
+impl Distribution<char> for CharClass {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> char {
+        let total = *self.counts.last().expect("class has no characters");
+        let n = rng.gen_range(0..total);
+        let idx = self.counts.partition_point(|count| *count <= n);
+        let before = idx.checked_sub(1).map_or(0, |prev| self.counts[prev]);
+        let start = self.ranges[idx].0 as u32;
+
+        char::from_u32(start + n - before).expect("ranges don't contain surrogates")
+    }
+}
+
+impl Debug for CharClass {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_tuple("CharClass").field(&self.pattern).finish()
+    }
+}
+
+impl PartialEq for CharClass {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for CharClass {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.pattern)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for CharClass {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pattern = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+
+        Self::new(&pattern).map_err(serde::de::Error::custom)
+    }
+}
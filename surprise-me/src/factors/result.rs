@@ -1,8 +1,25 @@
-use rand::prelude::Distribution;
+use rand::{prelude::Distribution, Rng};
 
-use crate::{Surprise, SurpriseFactor};
+use crate::{
+    enumerate::outcomes,
+    error::{check_chance, try_gen_bool},
+    validate::nested_problems,
+    Enumerate, Enumerator, Shrink, Shrinker, Surprise, SurpriseError, SurpriseFactor, SurpriseKey,
+    TryDistribution, Validate,
+};
 
 /// The surprise factor of [`Result`]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        default,
+        bound(
+            serialize = "SurpriseFactor<T>: serde::Serialize, SurpriseFactor<E>: serde::Serialize",
+            deserialize = "SurpriseFactor<T>: serde::Deserialize<'de> + Default, SurpriseFactor<E>: serde::Deserialize<'de> + Default"
+        )
+    )
+)]
 pub struct ResultSurprise<T: Surprise, E: Surprise> {
     /// Probability for `Ok`
     pub chance: f64,
@@ -14,15 +31,87 @@ pub struct ResultSurprise<T: Surprise, E: Surprise> {
 
 impl<T: Surprise, E: Surprise> Surprise for Result<T, E> {
     type Factor = ResultSurprise<T, E>;
+
+    delegate_to_factor!();
 }
 
+impl<T: Surprise + Ord, E: Surprise + Ord> SurpriseKey for Result<T, E> {}
+
 impl<T: Surprise, E: Surprise> Distribution<Result<T, E>> for ResultSurprise<T, E> {
     #[inline]
-    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Result<T, E> {
-        if rng.gen_bool(self.chance) {
-            Ok(rng.sample(&self.ok))
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<T, E> {
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<T: Surprise, E: Surprise> TryDistribution<Result<T, E>> for ResultSurprise<T, E> {
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<Result<T, E>, SurpriseError> {
+        if try_gen_bool(rng, self.chance)? {
+            T::try_generate_with_factor(rng, &self.ok)
+                .map(Ok)
+                .map_err(|err| err.in_field("ok"))
         } else {
-            Err(rng.sample(&self.err))
+            E::try_generate_with_factor(rng, &self.err)
+                .map(Err)
+                .map_err(|err| err.in_field("err"))
+        }
+    }
+}
+
+impl<T: Surprise, E: Surprise> Validate for ResultSurprise<T, E> {
+    fn problems(&self) -> Vec<SurpriseError> {
+        check_chance(self.chance)
+            .err()
+            .into_iter()
+            .chain(nested_problems::<T>("ok", &self.ok))
+            .chain(nested_problems::<E>("err", &self.err))
+            .collect()
+    }
+}
+
+impl<T, E> Default for ResultSurprise<T, E>
+where
+    T: Surprise,
+    E: Surprise,
+    SurpriseFactor<T>: Default,
+    SurpriseFactor<E>: Default,
+{
+    #[inline]
+    fn default() -> Self {
+        Self {
+            chance: 0.5,
+            ok: Default::default(),
+            err: Default::default(),
+        }
+    }
+}
+
+impl<T: Shrink, E: Shrink> Shrink for Result<T, E> {
+    /// Shrinks the inner value
+    #[inline]
+    fn shrink<'a>(&'a self, factor: &'a ResultSurprise<T, E>) -> Shrinker<'a, Self> {
+        match self {
+            Ok(ok) => Box::new(ok.shrink(&factor.ok).map(Ok)),
+            Err(err) => Box::new(err.shrink(&factor.err).map(Err)),
         }
     }
 }
+
+impl<T: Enumerate, E: Enumerate> Enumerate for Result<T, E> {
+    /// Enumerates all `Ok` values and then all `Err` values unless the factor never generates them
+    #[inline]
+    fn enumerate<'a>(factor: &'a ResultSurprise<T, E>) -> Enumerator<'a, Self>
+    where
+        Self: 'a,
+    {
+        let ok = outcomes(factor.chance)
+            .filter(|&ok| ok)
+            .flat_map(move |_| T::enumerate(&factor.ok).map(Ok));
+        let err = outcomes(factor.chance)
+            .filter(|&ok| !ok)
+            .flat_map(move |_| E::enumerate(&factor.err).map(Err));
+
+        Box::new(ok.chain(err))
+    }
+}
@@ -1,14 +1,29 @@
 use std::{
     fmt::{Debug, Formatter, Result as FmtResult},
+    mem::size_of,
     rc::Rc,
     sync::Arc,
 };
 
 use rand::{prelude::Distribution, Rng};
 
-use crate::{Surprise, SurpriseFactor};
+use crate::{
+    budget::spend_box, validate::nested_problems, Enumerate, Enumerator, Shrink, Shrinker,
+    Surprise, SurpriseError, SurpriseFactor, SurpriseKey, TryDistribution, Validate,
+};
 
 /// The surprise factor of [`Box`]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        default,
+        bound(
+            serialize = "SurpriseFactor<T>: serde::Serialize",
+            deserialize = "SurpriseFactor<T>: serde::Deserialize<'de> + Default"
+        )
+    )
+)]
 pub struct BoxSurprise<T: Surprise> {
     /// The surprise factor for the inner type
     pub inner: SurpriseFactor<T>,
@@ -16,12 +31,52 @@ pub struct BoxSurprise<T: Surprise> {
 
 impl<T: Surprise> Surprise for Box<T> {
     type Factor = BoxSurprise<T>;
+
+    delegate_to_factor!();
 }
 
+impl<T: Surprise + Ord> SurpriseKey for Box<T> {}
+
 impl<T: Surprise> Distribution<Box<T>> for BoxSurprise<T> {
     #[inline]
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Box<T> {
-        Box::new(self.inner.sample(rng))
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<T: Surprise> TryDistribution<Box<T>> for BoxSurprise<T> {
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<Box<T>, SurpriseError> {
+        spend_box(size_of::<T>());
+
+        T::try_generate_with_factor(rng, &self.inner)
+            .map(Box::new)
+            .map_err(|err| err.in_field("inner"))
+    }
+}
+
+impl<T: Surprise> Validate for BoxSurprise<T> {
+    #[inline]
+    fn problems(&self) -> Vec<SurpriseError> {
+        nested_problems::<T>("inner", &self.inner).collect()
+    }
+}
+
+impl<T: Shrink> Shrink for Box<T> {
+    /// Shrinks the inner value
+    #[inline]
+    fn shrink<'a>(&'a self, factor: &'a BoxSurprise<T>) -> Shrinker<'a, Self> {
+        Box::new((**self).shrink(&factor.inner).map(Box::new))
+    }
+}
+
+impl<T: Enumerate> Enumerate for Box<T> {
+    #[inline]
+    fn enumerate<'a>(factor: &'a BoxSurprise<T>) -> Enumerator<'a, Self>
+    where
+        Self: 'a,
+    {
+        Box::new(T::enumerate(&factor.inner).map(Box::new))
     }
 }
 
@@ -78,27 +133,93 @@ where
 /// The surprise factor of [`Rc`]
 pub type RcSurprise<T> = BoxSurprise<T>;
 
-impl<T: Surprise + ?Sized> Surprise for Rc<T> {
+impl<T: Surprise> Surprise for Rc<T> {
     type Factor = RcSurprise<T>;
+
+    delegate_to_factor!();
 }
 
+impl<T: Surprise + Ord> SurpriseKey for Rc<T> {}
+
 impl<T: Surprise> Distribution<Rc<T>> for RcSurprise<T> {
     #[inline]
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Rc<T> {
-        Rc::new(self.inner.sample(rng))
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<T: Surprise> TryDistribution<Rc<T>> for RcSurprise<T> {
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<Rc<T>, SurpriseError> {
+        spend_box(size_of::<T>());
+
+        T::try_generate_with_factor(rng, &self.inner)
+            .map(Rc::new)
+            .map_err(|err| err.in_field("inner"))
+    }
+}
+
+impl<T: Shrink> Shrink for Rc<T> {
+    /// Shrinks the inner value
+    #[inline]
+    fn shrink<'a>(&'a self, factor: &'a RcSurprise<T>) -> Shrinker<'a, Self> {
+        Box::new((**self).shrink(&factor.inner).map(Rc::new))
+    }
+}
+
+impl<T: Enumerate> Enumerate for Rc<T> {
+    #[inline]
+    fn enumerate<'a>(factor: &'a RcSurprise<T>) -> Enumerator<'a, Self>
+    where
+        Self: 'a,
+    {
+        Box::new(T::enumerate(&factor.inner).map(Rc::new))
     }
 }
 
 /// The surprise factor of [`Arc`]
 pub type ArcSurprise<T> = BoxSurprise<T>;
 
-impl<T: Surprise + ?Sized> Surprise for Arc<T> {
+impl<T: Surprise> Surprise for Arc<T> {
     type Factor = ArcSurprise<T>;
+
+    delegate_to_factor!();
 }
 
+impl<T: Surprise + Ord> SurpriseKey for Arc<T> {}
+
 impl<T: Surprise> Distribution<Arc<T>> for ArcSurprise<T> {
     #[inline]
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Arc<T> {
-        Arc::new(self.inner.sample(rng))
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<T: Surprise> TryDistribution<Arc<T>> for ArcSurprise<T> {
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<Arc<T>, SurpriseError> {
+        spend_box(size_of::<T>());
+
+        T::try_generate_with_factor(rng, &self.inner)
+            .map(Arc::new)
+            .map_err(|err| err.in_field("inner"))
+    }
+}
+
+impl<T: Shrink> Shrink for Arc<T> {
+    /// Shrinks the inner value
+    #[inline]
+    fn shrink<'a>(&'a self, factor: &'a ArcSurprise<T>) -> Shrinker<'a, Self> {
+        Box::new((**self).shrink(&factor.inner).map(Arc::new))
+    }
+}
+
+impl<T: Enumerate> Enumerate for Arc<T> {
+    #[inline]
+    fn enumerate<'a>(factor: &'a ArcSurprise<T>) -> Enumerator<'a, Self>
+    where
+        Self: 'a,
+    {
+        Box::new(T::enumerate(&factor.inner).map(Arc::new))
     }
 }
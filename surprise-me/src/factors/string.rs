@@ -1,49 +1,200 @@
-use rand::{
-    distributions::{Alphanumeric, Standard},
-    prelude::Distribution,
-    Rng,
-};
+use std::borrow::Cow;
+
+use rand::{prelude::Distribution, seq::SliceRandom, Rng};
 
-use crate::Surprise;
+use crate::{
+    budget::spend_len,
+    error::{check_len, check_probability},
+    shrink::shrink_len,
+    size::current_size,
+    Shrink, Shrinker, Surprise, SurpriseError, SurpriseKey, TryDistribution, Validate,
+};
 
-use super::CharSurprise;
+use super::{CharSetSurprise, CharSurprise, LenDistribution, StringRegex, NAUGHTY_STRINGS};
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 /// The surprise factor of [`String`]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct StringSurprise {
     /// The minimum length of generated strings
     pub min_len: usize,
-    /// The maximum length of generated strings
+    /// The maximum length of generated strings.
+    ///
+    /// Defaults to the [current size](crate::size::current_size).
     pub max_len: usize,
-    /// The surprise factor for generated characters
+    /// How lengths are distributed between `min_len` and `max_len`, defaults to uniformly
+    pub len: LenDistribution,
+    /// The surprise factor for generated characters, see [`CharSurprise`]
     pub chars: CharSurprise,
+    /// A set of characters that generated characters are picked from instead of `chars`,
+    /// such as an alphabet or a Unicode script, defaults to `None`
+    pub char_set: Option<CharSetSurprise>,
+    /// The probability that a character is replaced with one of the [`NAUGHTY_STRINGS`],
+    /// defaults to `0.0`.
+    ///
+    /// Payloads are not restricted to `chars` and strings with payloads may have more
+    /// than `max_len` characters.
+    pub naughty_chance: f64,
+    /// A regex that generated strings match instead of using the other fields, defaults to `None`.
+    ///
+    /// Regexes can only be compiled with the `regex` feature.
+    pub regex: Option<StringRegex>,
 }
 
 impl Surprise for String {
     type Factor = StringSurprise;
+
+    delegate_to_factor!();
 }
 
+impl SurpriseKey for String {}
+
 impl StringSurprise {
+    /// Creates a surprise factor for strings that match the regex `pattern`.
+    ///
+    /// Fails if the pattern is invalid or contains anchors or word boundaries
+    /// which are not supported, see [`StringRegex::new`]. Always fails without the `regex` feature.
+    ///
+    /// ```rust
+    /// # use surprise_me::{factors::StringSurprise, rand, Surprise};
+    /// #
+    /// # #[cfg(feature = "regex")] {
+    /// let factor = StringSurprise::regex(r"[A-Z]{3}-\d{4}").unwrap();
+    /// let id = String::generate_with_factor(&mut rand::thread_rng(), &factor);
+    ///
+    /// assert_eq!(id.len(), 8);
+    /// assert!(StringSurprise::regex("^start").is_err());
+    /// # }
+    /// ```
+    pub fn regex(pattern: &str) -> Result<Self, SurpriseError> {
+        Ok(Self {
+            regex: Some(StringRegex::new(pattern)?),
+            ..Default::default()
+        })
+    }
+
     #[allow(clippy::len_without_is_empty)]
+    /// Returns a random length within the `min_len` and `max_len` values, distributed by `len`
     pub fn len<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
-        rng.gen_range(self.min_len..=self.max_len)
+        self.try_len(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns a random length within the `min_len` and `max_len` values, distributed by `len`,
+    /// or an error if `min_len` is greater than `max_len` or `len` is invalid
+    pub fn try_len<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<usize, SurpriseError> {
+        self.len.try_sample(rng, self.min_len, self.max_len)
+    }
+
+    /// The characters that are generated, i.e. `char_set` if set or else `chars`
+    fn char_set(&self) -> Cow<'_, CharSetSurprise> {
+        match self.char_set {
+            Some(ref char_set) => Cow::Borrowed(char_set),
+            None => Cow::Owned(CharSetSurprise::Chars(self.chars)),
+        }
+    }
+
+    /// The field name and problems of the generated characters
+    fn char_problems(&self) -> impl Iterator<Item = SurpriseError> {
+        let (field, problems) = match self.char_set {
+            Some(ref char_set) => ("char_set", char_set.problems()),
+            None => ("chars", self.chars.problems()),
+        };
+
+        problems
+            .into_iter()
+            .map(move |problem| problem.in_field(field))
     }
 }
 
 impl Distribution<String> for StringSurprise {
     #[inline]
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> String {
-        let len = self.len(rng);
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
 
-        match self.chars {
-            CharSurprise::Ascii => {
-                let bytes = rng.sample_iter(Alphanumeric).take(len).collect();
+impl TryDistribution<String> for StringSurprise {
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<String, SurpriseError> {
+        if let Some(ref regex) = self.regex {
+            let string = regex.sample(rng);
+            let len = string.chars().count();
+            spend_len(len, len, if string.is_ascii() { 1 } else { 4 });
 
-                // SAFETY: bytes are guaranteed to be valid ASCII characters
-                unsafe { String::from_utf8_unchecked(bytes) }
+            return Ok(string);
+        }
+
+        if let Some(problem) = self.char_problems().next() {
+            return Err(problem);
+        }
+
+        check_probability("naughty_chance", self.naughty_chance)?;
+
+        // Characters are charged with the most bytes they can take up
+        let chars = self.char_set();
+        let char_size = chars.max_len_utf8();
+        let len = spend_len(self.try_len(rng)?, self.min_len, char_size);
+
+        if self.naughty_chance == 0.0 {
+            return Ok((0..len).map(|_| chars.sample_valid(rng)).collect());
+        }
+
+        let mut string = String::with_capacity(len * char_size);
+
+        for _ in 0..len {
+            if rng.gen_bool(self.naughty_chance) {
+                string.push_str(NAUGHTY_STRINGS.choose(rng).unwrap());
+            } else {
+                string.push(chars.sample_valid(rng));
             }
-            CharSurprise::Unicode => rng.sample_iter::<char, _>(Standard).take(len).collect(),
         }
+
+        Ok(string)
+    }
+}
+
+impl Validate for StringSurprise {
+    #[inline]
+    fn problems(&self) -> Vec<SurpriseError> {
+        check_len(self.min_len, self.max_len)
+            .err()
+            .into_iter()
+            .chain(self.len.problem())
+            .chain(check_probability("naughty_chance", self.naughty_chance).err())
+            .chain(self.char_problems())
+            .collect()
+    }
+}
+
+impl Shrink for String {
+    /// Shrinks toward `min_len` characters, then replaces characters with `'a'`, or the
+    /// first generated character if `'a'` isn't generated.
+    ///
+    /// Strings of a regex are not shrunk because that could break the match.
+    #[inline]
+    fn shrink<'a>(&'a self, factor: &'a StringSurprise) -> Shrinker<'a, Self> {
+        if factor.regex.is_some() {
+            return Box::new(std::iter::empty());
+        }
+
+        let removed = shrink_len(self.chars(), self.chars().count(), factor.min_len);
+        let simplest = factor.char_set().simplest();
+
+        let shrunk = self
+            .char_indices()
+            .filter(move |(_, c)| *c != simplest)
+            .map(move |(i, c)| {
+                let mut string = self.clone();
+                string.replace_range(i..i + c.len_utf8(), simplest.encode_utf8(&mut [0; 4]));
+
+                string
+            });
+
+        Box::new(removed.chain(shrunk))
     }
 }
 
@@ -52,8 +203,12 @@ impl Default for StringSurprise {
     fn default() -> Self {
         Self {
             min_len: 0,
-            max_len: 100,
+            max_len: current_size(),
+            len: LenDistribution::Uniform,
             chars: CharSurprise::default(),
+            char_set: None,
+            naughty_chance: 0.0,
+            regex: None,
         }
     }
 }
@@ -1,8 +1,8 @@
-use std::sync::{Mutex, RwLock};
+use std::sync::{Mutex, PoisonError, RwLock};
 
 use rand::{prelude::Distribution, Rng};
 
-use crate::Surprise;
+use crate::{Shrink, Shrinker, Surprise, SurpriseError, TryDistribution};
 
 use super::BoxSurprise;
 
@@ -11,12 +11,35 @@ pub type MutexSurprise<T> = BoxSurprise<T>;
 
 impl<T: Surprise> Surprise for Mutex<T> {
     type Factor = MutexSurprise<T>;
+
+    delegate_to_factor!();
 }
 
 impl<T: Surprise> Distribution<Mutex<T>> for MutexSurprise<T> {
     #[inline]
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Mutex<T> {
-        Mutex::new(rng.sample(&self.inner))
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<T: Surprise> TryDistribution<Mutex<T>> for MutexSurprise<T> {
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<Mutex<T>, SurpriseError> {
+        T::try_generate_with_factor(rng, &self.inner)
+            .map(Mutex::new)
+            .map_err(|err| err.in_field("inner"))
+    }
+}
+
+impl<T: Shrink> Shrink for Mutex<T> {
+    /// Shrinks the inner value.
+    ///
+    /// Since the lock can't be held onto, all candidates are collected eagerly.
+    fn shrink<'a>(&'a self, factor: &'a MutexSurprise<T>) -> Shrinker<'a, Self> {
+        let inner = self.lock().unwrap_or_else(PoisonError::into_inner);
+        let candidates: Vec<_> = inner.shrink(&factor.inner).map(Mutex::new).collect();
+
+        Box::new(candidates.into_iter())
     }
 }
 
@@ -25,11 +48,34 @@ pub type RwLockSurprise<T> = BoxSurprise<T>;
 
 impl<T: Surprise> Surprise for RwLock<T> {
     type Factor = RwLockSurprise<T>;
+
+    delegate_to_factor!();
 }
 
 impl<T: Surprise> Distribution<RwLock<T>> for RwLockSurprise<T> {
     #[inline]
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> RwLock<T> {
-        RwLock::new(rng.sample(&self.inner))
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<T: Surprise> TryDistribution<RwLock<T>> for RwLockSurprise<T> {
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<RwLock<T>, SurpriseError> {
+        T::try_generate_with_factor(rng, &self.inner)
+            .map(RwLock::new)
+            .map_err(|err| err.in_field("inner"))
+    }
+}
+
+impl<T: Shrink> Shrink for RwLock<T> {
+    /// Shrinks the inner value.
+    ///
+    /// Since the lock can't be held onto, all candidates are collected eagerly.
+    fn shrink<'a>(&'a self, factor: &'a RwLockSurprise<T>) -> Shrinker<'a, Self> {
+        let inner = self.read().unwrap_or_else(PoisonError::into_inner);
+        let candidates: Vec<_> = inner.shrink(&factor.inner).map(RwLock::new).collect();
+
+        Box::new(candidates.into_iter())
     }
 }
@@ -0,0 +1,244 @@
+use std::{collections::HashSet, fmt::Write};
+
+use rand::{prelude::Distribution, seq::SliceRandom, Rng};
+
+use crate::{
+    error::check_probability, validate::nested_problems, Surprise, SurpriseError, TryDistribution,
+    Validate,
+};
+
+use super::{
+    json::{gen_container_len, push_quoted},
+    FloatSurprise, NumberSurprise, StringSurprise,
+};
+
+/// Lines that are invalid wherever they are inserted
+const INVALID_LINES: [&str; 4] = ["= 1", "[unclosed", "key = \"unterminated", "key ="];
+
+#[derive(Clone, Debug, PartialEq)]
+/// A surprise factor for TOML documents.
+///
+/// Tables contain key-value pairs and subtables, which are written as `[a.b]` headers.
+/// Values are strings, integers, floats, booleans, arrays and inline tables.
+///
+/// Since strings already have a surprise factor, fields use this one through
+/// `#[factor = "TomlSurprise"]`.
+///
+/// ```rust
+/// # use surprise_me::{factors::TomlSurprise, Surprise};
+/// #
+/// #[derive(Surprise)]
+/// struct Config {
+///     #[factor = "TomlSurprise"]
+///     #[factor(max_len = 8, corruption_chance = 0.1)]
+///     manifest: String,
+/// }
+/// ```
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct TomlSurprise {
+    /// How deep tables, arrays and inline tables nest, including the root table,
+    /// defaults to `3`.
+    ///
+    /// Must be at least `1`.
+    pub max_depth: usize,
+    /// The maximum amount of entries in tables and items in arrays, defaults to `5`
+    pub max_len: usize,
+    /// The surprise factor for keys and string values, defaults to at most 10 characters
+    pub strings: StringSurprise,
+    /// The surprise factor for integers
+    pub integers: NumberSurprise<i64>,
+    /// The surprise factor for floats
+    pub floats: FloatSurprise<f64>,
+    /// The probability that a syntax error is injected into the document, defaults to `0.0`
+    pub corruption_chance: f64,
+}
+
+impl Distribution<String> for TomlSurprise {
+    #[inline]
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> String {
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl TryDistribution<String> for TomlSurprise {
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<String, SurpriseError> {
+        if let Some(problem) = self.problems().into_iter().next() {
+            return Err(problem);
+        }
+
+        let mut toml = String::new();
+        self.push_table(rng, 1, &mut Vec::new(), &mut toml);
+
+        if rng.gen_bool(self.corruption_chance) {
+            corrupt(rng, &mut toml);
+        }
+
+        Ok(toml)
+    }
+}
+
+impl TomlSurprise {
+    /// Appends the key-value pairs of a table at the given depth, followed by its subtables
+    fn push_table<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        depth: usize,
+        path: &mut Vec<String>,
+        toml: &mut String,
+    ) {
+        let mut keys = HashSet::new();
+        let mut subtables = Vec::new();
+
+        for _ in 0..gen_container_len(rng, self.max_len) {
+            let key = String::generate_with_factor(rng, &self.strings);
+
+            // Duplicate keys are skipped
+            if !keys.insert(key.clone()) {
+                continue;
+            }
+
+            if depth < self.max_depth && rng.gen_ratio(1, 4) {
+                subtables.push(key);
+            } else {
+                push_key(&key, toml);
+                toml.push_str(" = ");
+                self.push_value(rng, depth, toml);
+                toml.push('\n');
+            }
+        }
+
+        for key in subtables {
+            path.push(key);
+            toml.push('[');
+
+            for (i, key) in path.iter().enumerate() {
+                if i > 0 {
+                    toml.push('.');
+                }
+
+                push_key(key, toml);
+            }
+
+            toml.push_str("]\n");
+            self.push_table(rng, depth + 1, path, toml);
+            path.pop();
+        }
+    }
+
+    /// Appends a value within a table or array at the given depth
+    fn push_value<R: Rng + ?Sized>(&self, rng: &mut R, depth: usize, toml: &mut String) {
+        let kinds = if depth < self.max_depth { 6 } else { 4 };
+
+        match rng.gen_range(0..kinds) {
+            0 => toml.push_str(if rng.gen() { "true" } else { "false" }),
+            1 => {
+                let _ = write!(toml, "{}", i64::generate_with_factor(rng, &self.integers));
+            }
+            2 => match f64::generate_with_factor(rng, &self.floats) {
+                float if float.is_nan() => toml.push_str("nan"),
+                float if float.is_infinite() && float > 0.0 => toml.push_str("inf"),
+                float if float.is_infinite() => toml.push_str("-inf"),
+                float => {
+                    let _ = write!(toml, "{float:?}");
+                }
+            },
+            3 => push_quoted(&String::generate_with_factor(rng, &self.strings), toml),
+            4 => {
+                toml.push('[');
+
+                for i in 0..gen_container_len(rng, self.max_len) {
+                    if i > 0 {
+                        toml.push_str(", ");
+                    }
+
+                    self.push_value(rng, depth + 1, toml);
+                }
+
+                toml.push(']');
+            }
+            _ => {
+                let mut keys = HashSet::new();
+                toml.push('{');
+
+                for _ in 0..gen_container_len(rng, self.max_len) {
+                    let key = String::generate_with_factor(rng, &self.strings);
+
+                    if keys.contains(&key) {
+                        continue;
+                    }
+
+                    toml.push_str(if keys.is_empty() { " " } else { ", " });
+                    push_key(&key, toml);
+                    toml.push_str(" = ");
+                    self.push_value(rng, depth + 1, toml);
+                    keys.insert(key);
+                }
+
+                toml.push_str(if keys.is_empty() { "}" } else { " }" });
+            }
+        }
+    }
+}
+
+/// Appends the key as bare key if possible, quoted otherwise
+fn push_key(key: &str, toml: &mut String) {
+    let is_bare = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-';
+
+    if !key.is_empty() && key.chars().all(is_bare) {
+        toml.push_str(key);
+    } else {
+        push_quoted(key, toml);
+    }
+}
+
+/// Inserts an invalid line at the start of a random line.
+///
+/// Since strings, arrays and inline tables are generated on a single line,
+/// every line starts a new key-value pair or table header.
+fn corrupt<R: Rng + ?Sized>(rng: &mut R, toml: &mut String) {
+    let line_starts: Vec<_> = std::iter::once(0)
+        .chain(toml.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+
+    let idx = *line_starts.choose(rng).unwrap();
+    let line = INVALID_LINES.choose(rng).unwrap();
+    toml.insert_str(idx, &format!("{line}\n"));
+}
+
+impl Validate for TomlSurprise {
+    fn problems(&self) -> Vec<SurpriseError> {
+        let mut problems = Vec::new();
+
+        if self.max_depth == 0 {
+            problems.push(SurpriseError::new("max_depth", "must be at least 1"));
+        }
+
+        problems.extend(check_probability("corruption_chance", self.corruption_chance).err());
+        problems.extend(nested_problems::<String>("strings", &self.strings));
+        problems.extend(nested_problems::<i64>("integers", &self.integers));
+        problems.extend(nested_problems::<f64>("floats", &self.floats));
+
+        problems
+    }
+}
+
+impl Default for TomlSurprise {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_depth: 3,
+            max_len: 5,
+            strings: StringSurprise {
+                max_len: 10,
+                ..Default::default()
+            },
+            integers: NumberSurprise::default(),
+            floats: FloatSurprise::default(),
+            corruption_chance: 0.0,
+        }
+    }
+}
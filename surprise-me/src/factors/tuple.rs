@@ -1,6 +1,9 @@
 use rand::{distributions::Distribution, Rng};
 
-use crate::{Surprise, SurpriseFactor};
+use crate::{
+    enumerate::product, validate::nested_problems, Enumerate, Enumerator, Shrink, Shrinker,
+    Surprise, SurpriseError, SurpriseFactor, SurpriseKey, TryDistribution, Validate,
+};
 
 /// The surprise factor of tuples
 pub struct TupleSurprise<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16>(
@@ -43,17 +46,85 @@ macro_rules! tuple_surprise {
     ( $( $tuple_ty:ident : $idx:tt ),+ $(..)? $( $remaining_ty:ty ),* ) => {
         impl<$( $tuple_ty: Surprise ,)*> Surprise for ( $( $tuple_ty ,)* ) {
             type Factor = TupleSurprise<$( $tuple_ty ,)* $( $remaining_ty ,)*>;
+
+            delegate_to_factor!();
         }
 
         impl<$( $tuple_ty: Surprise ,)*> Distribution<( $( $tuple_ty ,)* )> for TupleSurprise<$( $tuple_ty ,)* $( $remaining_ty ),*> {
             #[inline]
             fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> ( $( $tuple_ty ,)* ) {
-                ( $( self.$idx.sample(rng), )* )
+                self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+            }
+        }
+
+        impl<$( $tuple_ty: Surprise ,)*> TryDistribution<( $( $tuple_ty ,)* )> for TupleSurprise<$( $tuple_ty ,)* $( $remaining_ty ),*> {
+            #[inline]
+            fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<( $( $tuple_ty ,)* ), SurpriseError> {
+                Ok((
+                    $(
+                        $tuple_ty::try_generate_with_factor(rng, &self.$idx)
+                            .map_err(|err| err.in_field(stringify!($idx)))?,
+                    )*
+                ))
+            }
+        }
+
+        impl<$( $tuple_ty: Shrink + Clone ,)*> Shrink for ( $( $tuple_ty ,)* ) {
+            /// Shrinks one element at a time
+            #[inline]
+            fn shrink<'a>(&'a self, factor: &'a SurpriseFactor<Self>) -> Shrinker<'a, Self> {
+                let shrunk = std::iter::empty()
+                    $(
+                        .chain(self.$idx.shrink(&factor.$idx).map(move |elem| {
+                            let mut tuple = self.clone();
+                            tuple.$idx = elem;
+
+                            tuple
+                        }))
+                    )*;
+
+                Box::new(shrunk)
+            }
+        }
+
+        impl<$( $tuple_ty: Enumerate + Clone ,)*> Enumerate for ( $( $tuple_ty ,)* ) {
+            /// Enumerates all combinations of elements with the first element changing slowest
+            #[inline]
+            #[allow(non_snake_case)]
+            fn enumerate<'a>(factor: &'a SurpriseFactor<Self>) -> Enumerator<'a, Self>
+            where
+                Self: 'a,
+            {
+                let nested = tuple_enumerator!(factor; $( $tuple_ty : $idx ),+);
+
+                Box::new(nested.map(|nested_pattern!($( $tuple_ty ),+)| ( $( $tuple_ty ,)* )))
             }
         }
     };
 }
 
+/// Enumerates the elements of a tuple as nested pairs, e.g. `(T1, (T2, T3))`
+macro_rules! tuple_enumerator {
+    ( $factor:ident; $ty:ident : $idx:tt ) => {
+        $ty::enumerate(&$factor.$idx)
+    };
+    ( $factor:ident; $ty:ident : $idx:tt, $( $rest_ty:ident : $rest_idx:tt ),+ ) => {
+        product($ty::enumerate(&$factor.$idx), move || {
+            tuple_enumerator!($factor; $( $rest_ty : $rest_idx ),+)
+        })
+    };
+}
+
+/// Pattern to destructure the nested pairs of [`tuple_enumerator`]
+macro_rules! nested_pattern {
+    ( $last:ident ) => {
+        $last
+    };
+    ( $first:ident, $( $rest:ident ),+ ) => {
+        ($first, nested_pattern!($( $rest ),+))
+    };
+}
+
 impl<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16> Default
     for TupleSurprise<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16>
 where
@@ -172,6 +243,337 @@ where
     }
 }
 
+impl<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16> Validate
+    for TupleSurprise<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16>
+where
+    T1: Surprise,
+    T2: Surprise,
+    T3: Surprise,
+    T4: Surprise,
+    T5: Surprise,
+    T6: Surprise,
+    T7: Surprise,
+    T8: Surprise,
+    T9: Surprise,
+    T10: Surprise,
+    T11: Surprise,
+    T12: Surprise,
+    T13: Surprise,
+    T14: Surprise,
+    T15: Surprise,
+    T16: Surprise,
+{
+    fn problems(&self) -> Vec<SurpriseError> {
+        std::iter::empty()
+            .chain(nested_problems::<T1>("0", &self.0))
+            .chain(nested_problems::<T2>("1", &self.1))
+            .chain(nested_problems::<T3>("2", &self.2))
+            .chain(nested_problems::<T4>("3", &self.3))
+            .chain(nested_problems::<T5>("4", &self.4))
+            .chain(nested_problems::<T6>("5", &self.5))
+            .chain(nested_problems::<T7>("6", &self.6))
+            .chain(nested_problems::<T8>("7", &self.7))
+            .chain(nested_problems::<T9>("8", &self.8))
+            .chain(nested_problems::<T10>("9", &self.9))
+            .chain(nested_problems::<T11>("10", &self.10))
+            .chain(nested_problems::<T12>("11", &self.11))
+            .chain(nested_problems::<T13>("12", &self.12))
+            .chain(nested_problems::<T14>("13", &self.13))
+            .chain(nested_problems::<T15>("14", &self.14))
+            .chain(nested_problems::<T16>("15", &self.15))
+            .collect()
+    }
+}
+
+/// Serialized as a sequence of the factors.
+///
+/// Trailing zero-sized factors, e.g. those of unused tuple slots, carry
+/// no information and are omitted.
+#[cfg(feature = "serde")]
+impl<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16> serde::Serialize
+    for TupleSurprise<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16>
+where
+    T1: Surprise,
+    SurpriseFactor<T1>: serde::Serialize,
+    T2: Surprise,
+    SurpriseFactor<T2>: serde::Serialize,
+    T3: Surprise,
+    SurpriseFactor<T3>: serde::Serialize,
+    T4: Surprise,
+    SurpriseFactor<T4>: serde::Serialize,
+    T5: Surprise,
+    SurpriseFactor<T5>: serde::Serialize,
+    T6: Surprise,
+    SurpriseFactor<T6>: serde::Serialize,
+    T7: Surprise,
+    SurpriseFactor<T7>: serde::Serialize,
+    T8: Surprise,
+    SurpriseFactor<T8>: serde::Serialize,
+    T9: Surprise,
+    SurpriseFactor<T9>: serde::Serialize,
+    T10: Surprise,
+    SurpriseFactor<T10>: serde::Serialize,
+    T11: Surprise,
+    SurpriseFactor<T11>: serde::Serialize,
+    T12: Surprise,
+    SurpriseFactor<T12>: serde::Serialize,
+    T13: Surprise,
+    SurpriseFactor<T13>: serde::Serialize,
+    T14: Surprise,
+    SurpriseFactor<T14>: serde::Serialize,
+    T15: Surprise,
+    SurpriseFactor<T15>: serde::Serialize,
+    T16: Surprise,
+    SurpriseFactor<T16>: serde::Serialize,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeSeq;
+        use std::mem::size_of;
+
+        let sizes = [
+            size_of::<SurpriseFactor<T1>>(),
+            size_of::<SurpriseFactor<T2>>(),
+            size_of::<SurpriseFactor<T3>>(),
+            size_of::<SurpriseFactor<T4>>(),
+            size_of::<SurpriseFactor<T5>>(),
+            size_of::<SurpriseFactor<T6>>(),
+            size_of::<SurpriseFactor<T7>>(),
+            size_of::<SurpriseFactor<T8>>(),
+            size_of::<SurpriseFactor<T9>>(),
+            size_of::<SurpriseFactor<T10>>(),
+            size_of::<SurpriseFactor<T11>>(),
+            size_of::<SurpriseFactor<T12>>(),
+            size_of::<SurpriseFactor<T13>>(),
+            size_of::<SurpriseFactor<T14>>(),
+            size_of::<SurpriseFactor<T15>>(),
+            size_of::<SurpriseFactor<T16>>(),
+        ];
+
+        let len = sizes
+            .iter()
+            .rposition(|&size| size > 0)
+            .map_or(0, |i| i + 1);
+        let mut seq = serializer.serialize_seq(Some(len))?;
+
+        if len > 0 {
+            seq.serialize_element(&self.0)?;
+        }
+
+        if len > 1 {
+            seq.serialize_element(&self.1)?;
+        }
+
+        if len > 2 {
+            seq.serialize_element(&self.2)?;
+        }
+
+        if len > 3 {
+            seq.serialize_element(&self.3)?;
+        }
+
+        if len > 4 {
+            seq.serialize_element(&self.4)?;
+        }
+
+        if len > 5 {
+            seq.serialize_element(&self.5)?;
+        }
+
+        if len > 6 {
+            seq.serialize_element(&self.6)?;
+        }
+
+        if len > 7 {
+            seq.serialize_element(&self.7)?;
+        }
+
+        if len > 8 {
+            seq.serialize_element(&self.8)?;
+        }
+
+        if len > 9 {
+            seq.serialize_element(&self.9)?;
+        }
+
+        if len > 10 {
+            seq.serialize_element(&self.10)?;
+        }
+
+        if len > 11 {
+            seq.serialize_element(&self.11)?;
+        }
+
+        if len > 12 {
+            seq.serialize_element(&self.12)?;
+        }
+
+        if len > 13 {
+            seq.serialize_element(&self.13)?;
+        }
+
+        if len > 14 {
+            seq.serialize_element(&self.14)?;
+        }
+
+        if len > 15 {
+            seq.serialize_element(&self.15)?;
+        }
+
+        seq.end()
+    }
+}
+
+/// Missing factors at the end of the sequence fall back to their default.
+#[cfg(feature = "serde")]
+impl<'de, T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16>
+    serde::Deserialize<'de>
+    for TupleSurprise<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16>
+where
+    T1: Surprise,
+    SurpriseFactor<T1>: serde::Deserialize<'de> + Default,
+    T2: Surprise,
+    SurpriseFactor<T2>: serde::Deserialize<'de> + Default,
+    T3: Surprise,
+    SurpriseFactor<T3>: serde::Deserialize<'de> + Default,
+    T4: Surprise,
+    SurpriseFactor<T4>: serde::Deserialize<'de> + Default,
+    T5: Surprise,
+    SurpriseFactor<T5>: serde::Deserialize<'de> + Default,
+    T6: Surprise,
+    SurpriseFactor<T6>: serde::Deserialize<'de> + Default,
+    T7: Surprise,
+    SurpriseFactor<T7>: serde::Deserialize<'de> + Default,
+    T8: Surprise,
+    SurpriseFactor<T8>: serde::Deserialize<'de> + Default,
+    T9: Surprise,
+    SurpriseFactor<T9>: serde::Deserialize<'de> + Default,
+    T10: Surprise,
+    SurpriseFactor<T10>: serde::Deserialize<'de> + Default,
+    T11: Surprise,
+    SurpriseFactor<T11>: serde::Deserialize<'de> + Default,
+    T12: Surprise,
+    SurpriseFactor<T12>: serde::Deserialize<'de> + Default,
+    T13: Surprise,
+    SurpriseFactor<T13>: serde::Deserialize<'de> + Default,
+    T14: Surprise,
+    SurpriseFactor<T14>: serde::Deserialize<'de> + Default,
+    T15: Surprise,
+    SurpriseFactor<T15>: serde::Deserialize<'de> + Default,
+    T16: Surprise,
+    SurpriseFactor<T16>: serde::Deserialize<'de> + Default,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use std::{fmt::Formatter, marker::PhantomData};
+
+        use serde::de::{SeqAccess, Visitor};
+
+        struct TupleVisitor<T>(PhantomData<fn() -> T>);
+
+        impl<'de, T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16>
+            Visitor<'de>
+            for TupleVisitor<
+                TupleSurprise<
+                    T1,
+                    T2,
+                    T3,
+                    T4,
+                    T5,
+                    T6,
+                    T7,
+                    T8,
+                    T9,
+                    T10,
+                    T11,
+                    T12,
+                    T13,
+                    T14,
+                    T15,
+                    T16,
+                >,
+            >
+        where
+            T1: Surprise,
+            SurpriseFactor<T1>: serde::Deserialize<'de> + Default,
+            T2: Surprise,
+            SurpriseFactor<T2>: serde::Deserialize<'de> + Default,
+            T3: Surprise,
+            SurpriseFactor<T3>: serde::Deserialize<'de> + Default,
+            T4: Surprise,
+            SurpriseFactor<T4>: serde::Deserialize<'de> + Default,
+            T5: Surprise,
+            SurpriseFactor<T5>: serde::Deserialize<'de> + Default,
+            T6: Surprise,
+            SurpriseFactor<T6>: serde::Deserialize<'de> + Default,
+            T7: Surprise,
+            SurpriseFactor<T7>: serde::Deserialize<'de> + Default,
+            T8: Surprise,
+            SurpriseFactor<T8>: serde::Deserialize<'de> + Default,
+            T9: Surprise,
+            SurpriseFactor<T9>: serde::Deserialize<'de> + Default,
+            T10: Surprise,
+            SurpriseFactor<T10>: serde::Deserialize<'de> + Default,
+            T11: Surprise,
+            SurpriseFactor<T11>: serde::Deserialize<'de> + Default,
+            T12: Surprise,
+            SurpriseFactor<T12>: serde::Deserialize<'de> + Default,
+            T13: Surprise,
+            SurpriseFactor<T13>: serde::Deserialize<'de> + Default,
+            T14: Surprise,
+            SurpriseFactor<T14>: serde::Deserialize<'de> + Default,
+            T15: Surprise,
+            SurpriseFactor<T15>: serde::Deserialize<'de> + Default,
+            T16: Surprise,
+            SurpriseFactor<T16>: serde::Deserialize<'de> + Default,
+        {
+            type Value = TupleSurprise<
+                T1,
+                T2,
+                T3,
+                T4,
+                T5,
+                T6,
+                T7,
+                T8,
+                T9,
+                T10,
+                T11,
+                T12,
+                T13,
+                T14,
+                T15,
+                T16,
+            >;
+
+            fn expecting(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                f.write_str("a sequence of surprise factors")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                Ok(TupleSurprise(
+                    seq.next_element()?.unwrap_or_default(),
+                    seq.next_element()?.unwrap_or_default(),
+                    seq.next_element()?.unwrap_or_default(),
+                    seq.next_element()?.unwrap_or_default(),
+                    seq.next_element()?.unwrap_or_default(),
+                    seq.next_element()?.unwrap_or_default(),
+                    seq.next_element()?.unwrap_or_default(),
+                    seq.next_element()?.unwrap_or_default(),
+                    seq.next_element()?.unwrap_or_default(),
+                    seq.next_element()?.unwrap_or_default(),
+                    seq.next_element()?.unwrap_or_default(),
+                    seq.next_element()?.unwrap_or_default(),
+                    seq.next_element()?.unwrap_or_default(),
+                    seq.next_element()?.unwrap_or_default(),
+                    seq.next_element()?.unwrap_or_default(),
+                    seq.next_element()?.unwrap_or_default(),
+                ))
+            }
+        }
+
+        deserializer.deserialize_seq(TupleVisitor(PhantomData))
+    }
+}
+
 tuple_surprise!(T1: 0 .. (), (), (), (), (), (), (), (), (), (), (), (), (), (), ());
 tuple_surprise!(T1: 0, T2: 1 .. (), (), (), (), (), (), (), (), (), (), (), (), (), ());
 tuple_surprise!(T1: 0, T2: 1, T3: 2 .. (), (), (), (), (), (), (), (), (), (), (), (), ());
@@ -188,3 +590,22 @@ tuple_surprise!(T1: 0, T2: 1, T3: 2, T4: 3, T5: 4, T6: 5, T7: 6, T8: 7, T9: 8, T
 tuple_surprise!(T1: 0, T2: 1, T3: 2, T4: 3, T5: 4, T6: 5, T7: 6, T8: 7, T9: 8, T10: 9, T11: 10, T12: 11, T13: 12, T14: 13 .. (), ());
 tuple_surprise!(T1: 0, T2: 1, T3: 2, T4: 3, T5: 4, T6: 5, T7: 6, T8: 7, T9: 8, T10: 9, T11: 10, T12: 11, T13: 12, T14: 13, T15: 14 .. ());
 tuple_surprise!(T1: 0, T2: 1, T3: 2, T4: 3, T5: 4, T6: 5, T7: 6, T8: 7, T9: 8, T10: 9, T11: 10, T12: 11, T13: 12, T14: 13, T15: 14, T16: 15);
+
+/// Implements [`SurpriseKey`] for tuples, which the standard library only orders up to 12 elements
+macro_rules! tuple_key {
+    ( $( ( $( $tuple_ty:ident ),+ ) )+ ) => {
+        $(
+            impl<$( $tuple_ty: Surprise + Ord ,)+> SurpriseKey for ( $( $tuple_ty ,)+ ) {}
+        )+
+    };
+}
+
+tuple_key!(
+    (T1)(T1, T2)(T1, T2, T3)(T1, T2, T3, T4)(T1, T2, T3, T4, T5)(T1, T2, T3, T4, T5, T6)(
+        T1, T2, T3, T4, T5, T6, T7
+    )(T1, T2, T3, T4, T5, T6, T7, T8)(T1, T2, T3, T4, T5, T6, T7, T8, T9)(
+        T1, T2, T3, T4, T5, T6, T7, T8, T9, T10
+    )(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11)(
+        T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12
+    )
+);
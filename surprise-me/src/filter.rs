@@ -0,0 +1,240 @@
+//! Rejection sampling that only generates values satisfying an invariant.
+//!
+//! Values are generated over and over until a predicate accepts one, with
+//! up to `max_retries` attempts after the first before generation fails.
+//! Every rejected value is wasted work, so [`FilterStats`] count them to
+//! spot filters that reject too much.
+//!
+//! ```rust
+//! use surprise_me::{factors::NumberSurprise, filter::Filter, rand, Surprise};
+//!
+//! let factor = NumberSurprise::<u32>::default().filter(|n: &u32| n % 2 == 0);
+//! let even = u32::generate_with_factor(&mut rand::thread_rng(), &factor);
+//!
+//! assert_eq!(even % 2, 0);
+//! assert!(factor.stats().rejection_rate() < 1.0);
+//! ```
+//!
+//! Derived types are filtered with `#[surprise(filter = "path::to::predicate")]`,
+//! see [`Surprise`](crate::Surprise). Like a [`Filtered`] factor, their surprise factors
+//! keep their own `max_retries` and `filter_stats` and generate through [`try_filter`],
+//! so [`Filter::filter`] is only needed for other surprise factors.
+
+use std::{
+    fmt,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use rand::{prelude::Distribution, Rng};
+
+use crate::{SurpriseError, TryDistribution, Validate};
+
+/// The amount of retries unless specified otherwise
+pub const DEFAULT_MAX_RETRIES: usize = 1000;
+
+/// Counts of the values that a filter accepted and rejected
+#[derive(Debug, Default)]
+pub struct FilterStats {
+    accepted: AtomicUsize,
+    rejected: AtomicUsize,
+}
+
+impl FilterStats {
+    /// Stats without any values
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            accepted: AtomicUsize::new(0),
+            rejected: AtomicUsize::new(0),
+        }
+    }
+
+    /// The amount of values that were accepted
+    #[inline]
+    pub fn accepted(&self) -> usize {
+        self.accepted.load(Ordering::Relaxed)
+    }
+
+    /// The amount of values that were rejected
+    #[inline]
+    pub fn rejected(&self) -> usize {
+        self.rejected.load(Ordering::Relaxed)
+    }
+
+    /// The share of generated values that were rejected, `0.0` if there are none yet
+    pub fn rejection_rate(&self) -> f64 {
+        let rejected = self.rejected();
+        let total = self.accepted() + rejected;
+
+        if total == 0 {
+            0.0
+        } else {
+            rejected as f64 / total as f64
+        }
+    }
+
+    /// Sets both counts back to zero
+    #[inline]
+    pub fn reset(&self) {
+        self.accepted.store(0, Ordering::Relaxed);
+        self.rejected.store(0, Ordering::Relaxed);
+    }
+}
+
+impl Clone for FilterStats {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            accepted: AtomicUsize::new(self.accepted()),
+            rejected: AtomicUsize::new(self.rejected()),
+        }
+    }
+}
+
+/// Generates values through `sample` until `predicate` accepts one and records them in `stats`.
+///
+/// Fails with an error for the field `max_retries` once `max_retries + 1` values were rejected.
+pub fn try_filter<T, R, S, P>(
+    rng: &mut R,
+    max_retries: usize,
+    stats: &FilterStats,
+    mut sample: S,
+    predicate: P,
+) -> Result<T, SurpriseError>
+where
+    R: Rng + ?Sized,
+    S: FnMut(&mut R) -> Result<T, SurpriseError>,
+    P: Fn(&T) -> bool,
+{
+    for _ in 0..=max_retries {
+        let value = sample(rng)?;
+
+        if predicate(&value) {
+            stats.accepted.fetch_add(1, Ordering::Relaxed);
+
+            return Ok(value);
+        }
+
+        stats.rejected.fetch_add(1, Ordering::Relaxed);
+    }
+
+    let reason = format!(
+        "the filter rejected {} values in a row",
+        max_retries.saturating_add(1)
+    );
+
+    Err(SurpriseError::new("max_retries", reason))
+}
+
+/// Adds a filter to any surprise factor
+pub trait Filter<T>: Sized {
+    /// Only generates values that `predicate` accepts, see the [module](self) documentation
+    fn filter<P>(self, predicate: P) -> Filtered<Self, P>
+    where
+        P: Fn(&T) -> bool;
+}
+
+impl<T, D> Filter<T> for D
+where
+    D: TryDistribution<T>,
+{
+    #[inline]
+    fn filter<P>(self, predicate: P) -> Filtered<Self, P>
+    where
+        P: Fn(&T) -> bool,
+    {
+        Filtered {
+            factor: self,
+            predicate,
+            max_retries: DEFAULT_MAX_RETRIES,
+            stats: FilterStats::new(),
+        }
+    }
+}
+
+/// A surprise factor that only generates values accepted by its predicate, see [`Filter::filter`]
+pub struct Filtered<D, P> {
+    /// The surprise factor that generates the values
+    pub factor: D,
+    /// The predicate that accepts values
+    pub predicate: P,
+    /// How many more values are generated after the first one is rejected,
+    /// defaults to [`DEFAULT_MAX_RETRIES`]
+    pub max_retries: usize,
+    stats: FilterStats,
+}
+
+impl<D, P> Filtered<D, P> {
+    /// The values that were generated through this surprise factor so far
+    #[inline]
+    pub fn stats(&self) -> &FilterStats {
+        &self.stats
+    }
+}
+
+impl<T, D, P> Distribution<T> for Filtered<D, P>
+where
+    D: TryDistribution<T>,
+    P: Fn(&T) -> bool,
+{
+    #[inline]
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> T {
+        self.try_sample(rng).unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+impl<T, D, P> TryDistribution<T> for Filtered<D, P>
+where
+    D: TryDistribution<T>,
+    P: Fn(&T) -> bool,
+{
+    #[inline]
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<T, SurpriseError> {
+        try_filter(
+            rng,
+            self.max_retries,
+            &self.stats,
+            |rng| self.factor.try_sample(rng),
+            &self.predicate,
+        )
+    }
+}
+
+impl<D, P> Validate for Filtered<D, P>
+where
+    D: Validate,
+{
+    #[inline]
+    fn problems(&self) -> Vec<SurpriseError> {
+        self.factor.problems()
+    }
+}
+
+impl<D, P> Clone for Filtered<D, P>
+where
+    D: Clone,
+    P: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            factor: self.factor.clone(),
+            predicate: self.predicate.clone(),
+            max_retries: self.max_retries,
+            stats: self.stats.clone(),
+        }
+    }
+}
+
+impl<D, P> fmt::Debug for Filtered<D, P>
+where
+    D: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Filtered")
+            .field("factor", &self.factor)
+            .field("max_retries", &self.max_retries)
+            .field("stats", &self.stats)
+            .finish_non_exhaustive()
+    }
+}
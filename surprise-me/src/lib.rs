@@ -1,26 +1,144 @@
 #![doc = include_str!("../../README.md")]
 
 pub use self::{
+    enumerate::{Enumerate, Enumerator},
+    error::SurpriseError,
     rand::{distributions::Distribution, Rng},
-    surprise::{Surprise, SurpriseFactor},
+    shrink::{Shrink, Shrinker},
+    surprise::{Surprise, SurpriseFactor, SurpriseKey, TryDistribution},
+    validate::Validate,
 };
 
 pub use rand;
-pub use surprise_me_derive::Surprise;
+pub use surprise_me_derive::{Enumerate, Shrink, Surprise};
+
+/// Overrides [`Surprise::try_generate_with_factor`] and [`Surprise::factor_problems`]
+/// to delegate to the [`TryDistribution`] and [`Validate`] implementations of the surprise factor
+macro_rules! delegate_to_factor {
+    () => {
+        #[inline]
+        fn try_generate_with_factor<R: ::rand::Rng + ?Sized>(
+            rng: &mut R,
+            factor: &$crate::SurpriseFactor<Self>,
+        ) -> Result<Self, $crate::SurpriseError> {
+            <$crate::SurpriseFactor<Self> as $crate::TryDistribution<Self>>::try_sample(factor, rng)
+        }
+
+        #[inline]
+        fn factor_problems(factor: &$crate::SurpriseFactor<Self>) -> Vec<$crate::SurpriseError> {
+            $crate::Validate::problems(factor)
+        }
+    };
+}
 
 /// Surprise factors of types in the standard library
 pub mod factors;
 
+pub mod budget;
+pub mod filter;
+pub mod seed;
+pub mod size;
+
+mod enumerate;
+mod error;
+mod shrink;
 mod surprise;
+mod validate;
+#[cfg(feature = "serde")]
+mod weights;
+
+#[doc(hidden)]
+/// Items used by the derive macros
+pub mod __private {
+    pub use crate::{
+        enumerate::product,
+        error::{check_weight_decay, check_weights, try_gen_bool, weighted_index},
+        shrink::{shrink_recursive_option, shrink_recursive_vec, simpler_variants},
+    };
+
+    use crate::factors::{CharClass, CharSetSurprise, Grammar, StringRegex};
+
+    /// Conversion of string literals in `#[factor(field = "...")]` attributes
+    pub trait FromStrLiteral {
+        fn from_str_literal(literal: &'static str) -> Self;
+    }
+
+    impl FromStrLiteral for &'static str {
+        #[inline]
+        fn from_str_literal(literal: &'static str) -> Self {
+            literal
+        }
+    }
+
+    impl FromStrLiteral for String {
+        #[inline]
+        fn from_str_literal(literal: &'static str) -> Self {
+            literal.to_owned()
+        }
+    }
+
+    impl FromStrLiteral for StringRegex {
+        /// Panics if the regex is invalid or unsupported
+        #[inline]
+        fn from_str_literal(literal: &'static str) -> Self {
+            StringRegex::new(literal).unwrap_or_else(|err| panic!("{err}"))
+        }
+    }
+
+    impl FromStrLiteral for CharClass {
+        /// Panics if the class is invalid
+        #[inline]
+        fn from_str_literal(literal: &'static str) -> Self {
+            CharClass::new(literal).unwrap_or_else(|err| panic!("{err}"))
+        }
+    }
+
+    impl FromStrLiteral for CharSetSurprise {
+        /// Panics if the class is invalid
+        #[inline]
+        fn from_str_literal(literal: &'static str) -> Self {
+            CharSetSurprise::Class(CharClass::from_str_literal(literal))
+        }
+    }
+
+    impl FromStrLiteral for Grammar {
+        /// Panics if the grammar is invalid
+        #[inline]
+        fn from_str_literal(literal: &'static str) -> Self {
+            Grammar::new(literal).unwrap_or_else(|err| panic!("{err}"))
+        }
+    }
+
+    impl<T: FromStrLiteral> FromStrLiteral for Option<T> {
+        #[inline]
+        fn from_str_literal(literal: &'static str) -> Self {
+            Some(T::from_str_literal(literal))
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    pub use serde;
+
+    #[cfg(feature = "serde")]
+    pub mod variants_weight {
+        pub use crate::weights::{deserialize, serialize};
+    }
+}
 
 #[cfg(test)]
+// The handwritten factors of the first tests trip lints that postdate them
+#[allow(dead_code, clippy::needless_borrows_for_generic_args)]
 mod tests {
+    use std::num::NonZeroI32;
+
     use rand::{distributions::WeightedIndex, prelude::Distribution};
 
     use crate::{
-        factors::{BoolSurprise, OptionSurprise, VecSurprise},
+        factors::{
+            BoolSurprise, FloatSurprise, NumberSurprise, OptionSurprise, RangeSurprise, VecSurprise,
+        },
         rand::Rng,
-        Surprise,
+        Shrink, Surprise, SurpriseError, SurpriseFactor, Validate,
     };
 
     #[test]
@@ -197,4 +315,1433 @@ mod tests {
 
         let _ = Direct::generate(&mut rand::thread_rng());
     }
+
+    #[test]
+    fn shrink_numbers() {
+        let factor = NumberSurprise {
+            min: 3,
+            max: 100,
+            ..Default::default()
+        };
+        let candidates: Vec<_> = 20_u8.shrink(&factor).collect();
+        assert_eq!(candidates, [3, 12, 16, 18, 19]);
+        assert_eq!(1_u8.shrink(&factor).collect::<Vec<_>>(), [3]);
+
+        let factor = NumberSurprise {
+            min: 100,
+            max: 127,
+            ..Default::default()
+        };
+        assert_eq!(i8::MIN.shrink(&factor).collect::<Vec<_>>(), [100]);
+
+        let factor = FloatSurprise {
+            min: -1.5,
+            max: 2.0,
+            ..Default::default()
+        };
+        assert_eq!(1.75_f64.minimize(&factor, |n| *n >= 1.0), 1.0);
+        assert_eq!((-1.25_f64).minimize(&factor, |n| *n <= -1.0), -1.0);
+    }
+
+    #[test]
+    fn shrink_collections() {
+        let factor = VecSurprise::<u8> {
+            min_len: 2,
+            max_len: 10,
+            len: Default::default(),
+            order: Default::default(),
+            items: NumberSurprise {
+                min: 0,
+                max: 255,
+                ..Default::default()
+            },
+        };
+
+        let vec = vec![5, 1, 8, 4, 9, 2];
+        let minimal = vec.minimize(&factor, |vec| vec.contains(&8));
+        assert_eq!(minimal, [8, 0]);
+
+        let factor = OptionSurprise::<bool> {
+            chance: 1.0,
+            inner: BoolSurprise { chance: 0.5 },
+        };
+
+        assert_eq!(Some(true).minimize(&factor, |_| true), Some(false));
+
+        // Heaps don't iterate in order, so only the distinctness of their items is kept
+        let factor = VecSurprise::<u8> {
+            order: crate::factors::ItemOrder::strictly_sorted(),
+            ..Default::default()
+        };
+
+        let heap: std::collections::BinaryHeap<_> = [5, 9, 3].into_iter().collect();
+        let minimal = heap.minimize(&factor, |heap| heap.len() == 3);
+        assert_eq!(minimal.into_sorted_vec(), [0, 1, 2]);
+    }
+
+    #[test]
+    fn fallible_generation() {
+        let mut rng = rand::thread_rng();
+
+        let factor = NumberSurprise {
+            min: 5,
+            max: 3,
+            ..Default::default()
+        };
+        let err = u8::try_generate_with_factor(&mut rng, &factor).unwrap_err();
+        assert_eq!(err.field(), "min");
+
+        let factor = FloatSurprise {
+            min: 0.0,
+            max: f64::INFINITY,
+            ..Default::default()
+        };
+        let err = f64::try_generate_with_factor(&mut rng, &factor).unwrap_err();
+        assert_eq!(err.field(), "max");
+
+        let factor = VecSurprise::<bool> {
+            min_len: 3,
+            max_len: 2,
+            len: Default::default(),
+            order: Default::default(),
+            items: BoolSurprise { chance: 0.5 },
+        };
+        let err = Vec::try_generate_with_factor(&mut rng, &factor).unwrap_err();
+        assert_eq!(err.field(), "min_len");
+
+        let factor = VecSurprise::<bool> {
+            min_len: 1,
+            max_len: 2,
+            len: Default::default(),
+            order: Default::default(),
+            items: BoolSurprise { chance: -0.5 },
+        };
+        let err = Vec::try_generate_with_factor(&mut rng, &factor).unwrap_err();
+        assert_eq!(err.field(), "items.chance");
+
+        let factor = OptionSurprise::<u8> {
+            chance: 1.0,
+            inner: NumberSurprise {
+                min: 2,
+                max: 1,
+                ..Default::default()
+            },
+        };
+        let err = Option::try_generate_with_factor(&mut rng, &factor).unwrap_err();
+        assert_eq!(err.field(), "inner.min");
+
+        let factor = NumberSurprise {
+            min: 0,
+            max: 0,
+            ..Default::default()
+        };
+        let err = NonZeroI32::try_generate_with_factor(&mut rng, &factor).unwrap_err();
+        assert_eq!(err.field(), "max");
+
+        assert!(<(u8, bool)>::try_generate(&mut rng).is_ok());
+
+        // Manual implementations only need to report the problems of their factor
+        struct Percent(u8);
+
+        struct PercentSurprise {
+            max: u8,
+        }
+
+        impl Distribution<Percent> for PercentSurprise {
+            fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Percent {
+                Percent(rng.gen_range(0..=self.max))
+            }
+        }
+
+        impl Surprise for Percent {
+            type Factor = PercentSurprise;
+
+            fn factor_problems(factor: &PercentSurprise) -> Vec<SurpriseError> {
+                if factor.max > 100 {
+                    vec![SurpriseError::new("max", "greater than 100")]
+                } else {
+                    Vec::new()
+                }
+            }
+        }
+
+        let percent = Percent::try_generate_with_factor(&mut rng, &PercentSurprise { max: 100 });
+        assert!(percent.unwrap().0 <= 100);
+
+        let factor = PercentSurprise { max: 200 };
+        let err = Percent::try_generate_with_factor(&mut rng, &factor)
+            .err()
+            .unwrap();
+        assert_eq!(err.field(), "max");
+    }
+
+    #[test]
+    fn validate_factors() {
+        let factor = VecSurprise::<Option<u8>> {
+            min_len: 5,
+            max_len: 2,
+            len: Default::default(),
+            order: Default::default(),
+            items: OptionSurprise {
+                chance: 1.3,
+                inner: NumberSurprise {
+                    min: 9,
+                    max: 1,
+                    ..Default::default()
+                },
+            },
+        };
+
+        let problems = factor.validate().unwrap_err();
+        let fields: Vec<_> = problems.iter().map(|problem| problem.field()).collect();
+        assert_eq!(fields, ["min_len", "items.chance", "items.inner.min"]);
+
+        let factor = RangeSurprise {
+            start: NumberSurprise {
+                min: f32::NAN,
+                max: 1.0,
+                ..Default::default()
+            },
+            end: NumberSurprise {
+                min: 0.0,
+                max: 1.0,
+                ..Default::default()
+            },
+        };
+
+        let problems = factor.validate().unwrap_err();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].field(), "start.min");
+
+        let factor = SurpriseFactor::<(bool, Vec<u8>)>::default();
+        assert!(factor.validate().is_ok());
+    }
+
+    #[test]
+    fn seeded_generation() {
+        use rand::RngCore;
+
+        use crate::seed::{gen_len, portable_rng, SurpriseRng};
+
+        // The output must not change between platforms or versions
+        assert_eq!(portable_rng(0).next_u64(), 13080132717333068652);
+
+        // Lengths are sampled as `u64` on every platform
+        let len = gen_len(&mut portable_rng(1), 0..=100);
+        assert_eq!(len as u64, portable_rng(1).gen_range(0..=100u64));
+
+        let value = <Vec<(u32, Option<bool>)>>::generate_seeded(42);
+        assert_eq!(value, <Vec<(u32, Option<bool>)>>::generate_seeded(42));
+
+        let mut rng = SurpriseRng::with_seed(7);
+        assert_eq!(rng.seed(), 7);
+
+        let value = String::generate(&mut rng);
+        assert_eq!(value, String::generate(&mut portable_rng(7)));
+    }
+
+    #[test]
+    fn sized_generation() {
+        use std::collections::HashMap;
+
+        use crate::size::{current_size, with_size, DEFAULT_SIZE};
+
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..20 {
+            let map = HashMap::<u8, Vec<String>>::generate_sized(&mut rng, 3);
+
+            assert!(map.len() <= 3);
+            assert!(map.values().flatten().all(|string| string.len() <= 3));
+        }
+
+        let factor = with_size(0, || {
+            assert_eq!(current_size(), 0);
+
+            let factor = with_size(5, VecSurprise::<Vec<u8>>::default);
+            assert_eq!(current_size(), 0);
+
+            factor
+        });
+
+        assert_eq!(factor.max_len, 5);
+        assert_eq!(factor.items.max_len, 5);
+        assert_eq!(current_size(), DEFAULT_SIZE);
+
+        let result = std::panic::catch_unwind(|| with_size(1, || panic!()));
+        assert!(result.is_err());
+        assert_eq!(current_size(), DEFAULT_SIZE);
+    }
+
+    #[test]
+    fn budget_generation() {
+        use crate::budget::{remaining_budget, with_budget, Budget};
+
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..20 {
+            let value = Vec::<Vec<String>>::generate_with_budget(&mut rng, Budget::Elements(300));
+
+            let elements = value.len()
+                + value.iter().map(Vec::len).sum::<usize>()
+                + value.iter().flatten().map(String::len).sum::<usize>();
+
+            assert!(elements <= 300);
+
+            let value = Vec::<u64>::generate_with_budget(&mut rng, Budget::Bytes(80));
+            assert!(value.len() <= 10);
+        }
+
+        assert_eq!(remaining_budget(), None);
+
+        // Minimum lengths are kept even if the budget ran out
+        let factor = VecSurprise::<u8> {
+            min_len: 5,
+            max_len: 10,
+            len: Default::default(),
+            order: Default::default(),
+            items: Default::default(),
+        };
+
+        let (value, remaining): (Vec<u8>, _) = with_budget(Budget::Elements(2), || {
+            (factor.sample(&mut rng), remaining_budget())
+        });
+
+        assert_eq!(value.len(), 5);
+        assert_eq!(remaining, Some(Budget::Elements(0)));
+    }
+
+    #[test]
+    fn edge_cases() {
+        let mut rng = rand::thread_rng();
+
+        assert_eq!(
+            NumberSurprise::<u8>::default().edge_cases(),
+            [0, 1, 254, 255]
+        );
+
+        let factor = NumberSurprise::<i32> {
+            min: -5,
+            max: 5,
+            edge_chance: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(factor.edge_cases(), [-5, -4, -1, 0, 1, 4, 5]);
+
+        for _ in 0..100 {
+            assert!(factor.edge_cases().contains(&factor.sample(&mut rng)));
+        }
+
+        let factor = NumberSurprise::<f64> {
+            min: 0.5,
+            max: 2.0,
+            edge_chance: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(
+            factor.edge_cases(),
+            [
+                0.5,
+                f64::from_bits(0.5f64.to_bits() + 1),
+                1.0,
+                f64::from_bits(2.0f64.to_bits() - 1),
+                2.0
+            ]
+        );
+
+        let factor = NumberSurprise {
+            min: -1,
+            max: 1,
+            edge_chance: 1.0,
+            ..Default::default()
+        };
+        for _ in 0..100 {
+            assert_ne!(NonZeroI32::generate_with_factor(&mut rng, &factor).get(), 0);
+        }
+
+        let factor = NumberSurprise {
+            edge_chance: -0.1,
+            ..NumberSurprise::<i64>::default()
+        };
+        let err = i64::try_generate_with_factor(&mut rng, &factor).unwrap_err();
+        assert_eq!(err.field(), "edge_chance");
+
+        // Without edge cases, numbers don't draw whether to pick one
+        let factor = NumberSurprise::<u32> {
+            min: 0,
+            max: 100,
+            ..Default::default()
+        };
+        let n: u32 = factor.sample(&mut crate::seed::portable_rng(3));
+        assert_eq!(n, crate::seed::portable_rng(3).gen_range(0..=100));
+
+        // The items are validated once, even if there are none
+        let factor = VecSurprise {
+            max_len: 0,
+            items: NumberSurprise {
+                edge_chance: 1.5,
+                ..NumberSurprise::<u8>::default()
+            },
+            ..Default::default()
+        };
+        let err = Vec::<u8>::try_generate_with_factor(&mut rng, &factor).unwrap_err();
+        assert_eq!(err.field(), "items.edge_chance");
+    }
+
+    #[test]
+    fn special_floats() {
+        let mut rng = rand::thread_rng();
+
+        let default = FloatSurprise::<f32>::default();
+        for _ in 0..100 {
+            assert!((0.0..=1.0).contains(&default.sample(&mut rng)));
+        }
+
+        // Without special values, floats are generated like plain numbers
+        let n: f32 = default.sample(&mut crate::seed::portable_rng(5));
+        let number: f32 = NumberSurprise::default().sample(&mut crate::seed::portable_rng(5));
+        assert_eq!(n, number);
+
+        let cases = [
+            (
+                FloatSurprise {
+                    nan_chance: 1.0,
+                    ..Default::default()
+                },
+                f64::is_nan as fn(f64) -> bool,
+            ),
+            (
+                FloatSurprise {
+                    infinity_chance: 1.0,
+                    ..Default::default()
+                },
+                f64::is_infinite,
+            ),
+            (
+                FloatSurprise {
+                    neg_zero_chance: 1.0,
+                    ..Default::default()
+                },
+                |n: f64| n == 0.0 && n.is_sign_negative(),
+            ),
+            (
+                FloatSurprise {
+                    subnormal_chance: 1.0,
+                    ..Default::default()
+                },
+                f64::is_subnormal,
+            ),
+        ];
+
+        for (factor, is_special) in cases {
+            for _ in 0..20 {
+                assert!(is_special(factor.sample(&mut rng)));
+            }
+        }
+
+        let factor = FloatSurprise::<f64> {
+            any_bits: true,
+            ..Default::default()
+        };
+        assert!((0..100).any(|_| !(0.0..=1.0).contains(&factor.sample(&mut rng))));
+
+        assert_eq!(
+            f64::INFINITY
+                .shrink(&Default::default())
+                .collect::<Vec<_>>(),
+            [0.0]
+        );
+
+        let factor = FloatSurprise::<f32> {
+            infinity_chance: 1.5,
+            ..Default::default()
+        };
+        assert_eq!(factor.problems()[0].field(), "infinity_chance");
+
+        let factor = RangeSurprise::<f64>::default();
+        assert_eq!(
+            (0.5..1.0).minimize(&factor, |range| range.end >= 1.0),
+            0.0..1.0
+        );
+    }
+
+    #[test]
+    fn number_distributions() {
+        use crate::factors::NumberDistribution;
+
+        let mut rng = rand::thread_rng();
+
+        fn with<N>(min: N, max: N, distribution: NumberDistribution) -> NumberSurprise<N> {
+            NumberSurprise {
+                min,
+                max,
+                edge_chance: 0.0,
+                distribution,
+            }
+        }
+
+        let factor = with(1u32, 1_000_000, NumberDistribution::LogUniform);
+        let small = (0..1000)
+            .filter(|_| u32::generate_with_factor(&mut rng, &factor) < 1000)
+            .count();
+        assert!((300..=700).contains(&small));
+
+        let factor = with(-1_000.0, -1.0, NumberDistribution::LogUniform);
+        let small = (0..1000)
+            .map(|_| f64::generate_with_factor(&mut rng, &factor))
+            .inspect(|n| assert!((-1_000.0..=-1.0).contains(n)))
+            .filter(|n| *n > -10.0)
+            .count();
+        assert!((250..=420).contains(&small));
+
+        let factor = with(0u32, 1_000_000, NumberDistribution::LogUniform);
+        let small = (0..1000)
+            .filter(|_| u32::generate_with_factor(&mut rng, &factor) < 1000)
+            .count();
+        assert!((300..=700).contains(&small));
+
+        let factor = with(i32::MIN, i32::MAX, NumberDistribution::LogUniform);
+        let negative = (0..1000)
+            .filter(|_| i32::generate_with_factor(&mut rng, &factor) < 0)
+            .count();
+        assert!((350..=650).contains(&negative));
+
+        // Digits below the precision of `f64` are filled in
+        let factor = with(1u64 << 60, u64::MAX, NumberDistribution::LogUniform);
+        assert!((0..100).any(|_| u64::generate_with_factor(&mut rng, &factor) % 2 == 1));
+
+        #[cfg(not(feature = "distributions"))]
+        {
+            let factor = with(0u8, 10, NumberDistribution::Poisson { lambda: 3.0 });
+            assert_eq!(factor.problems()[0].field(), "distribution");
+
+            let factor = VecSurprise::<u8> {
+                len: crate::factors::LenDistribution::Geometric { p: 0.5 },
+                ..Default::default()
+            };
+            assert_eq!(factor.problems()[0].field(), "len");
+        }
+
+        #[cfg(feature = "distributions")]
+        {
+            let factor = with(
+                0,
+                100,
+                NumberDistribution::Normal {
+                    mean: 50.0,
+                    std_dev: 5.0,
+                },
+            );
+            let mean = (0..1000)
+                .map(|_| i32::generate_with_factor(&mut rng, &factor) as f64)
+                .sum::<f64>()
+                / 1000.0;
+            assert!((45.0..=55.0).contains(&mean));
+
+            let factor = with(0u8, 255, NumberDistribution::Poisson { lambda: 3.0 });
+            let mean = (0..1000)
+                .map(|_| u8::generate_with_factor(&mut rng, &factor) as f64)
+                .sum::<f64>()
+                / 1000.0;
+            assert!((2.0..=4.0).contains(&mean));
+
+            let factor = with(10u32, 20, NumberDistribution::Zipf { exponent: 2.0 });
+            let tens = (0..1000)
+                .filter(|_| u32::generate_with_factor(&mut rng, &factor) == 10)
+                .count();
+            assert!(tens > 500);
+
+            let factor = with(-1.0, 1.0, NumberDistribution::Exponential { lambda: 0.1 });
+            for _ in 0..100 {
+                assert!((-1.0..=1.0).contains(&factor.sample(&mut rng)));
+            }
+
+            let factor = with(
+                1_000u32,
+                100_000,
+                NumberDistribution::Exponential { lambda: 0.01 },
+            );
+            let mean = (0..1000)
+                .map(|_| u32::generate_with_factor(&mut rng, &factor) as f64)
+                .sum::<f64>()
+                / 1000.0;
+            assert!((1_080.0..=1_120.0).contains(&mean));
+
+            let factor = with(
+                -5,
+                5,
+                NumberDistribution::Normal {
+                    mean: 0.0,
+                    std_dev: 0.0,
+                },
+            );
+            for _ in 0..100 {
+                assert_ne!(NonZeroI32::generate_with_factor(&mut rng, &factor).get(), 0);
+            }
+
+            let factor = with(
+                0,
+                10,
+                NumberDistribution::Normal {
+                    mean: 0.0,
+                    std_dev: -1.0,
+                },
+            );
+            let err = u8::try_generate_with_factor(&mut rng, &factor).unwrap_err();
+            assert_eq!(err.field(), "distribution");
+        }
+    }
+
+    #[test]
+    fn distinct_keys() {
+        use std::collections::{BTreeSet, HashMap, HashSet};
+
+        use crate::factors::{BTreeSetSurprise, HashMapSurprise, HashSetSurprise};
+
+        let mut rng = rand::thread_rng();
+
+        let factor = HashSetSurprise::<u8> {
+            min_len: 200,
+            max_len: 200,
+            ..Default::default()
+        };
+        assert_eq!(HashSet::generate_with_factor(&mut rng, &factor).len(), 200);
+
+        let factor = BTreeSetSurprise::<u8> {
+            min_len: 256,
+            max_len: 256,
+            ..Default::default()
+        };
+        assert_eq!(BTreeSet::generate_with_factor(&mut rng, &factor).len(), 256);
+
+        let factor = HashMapSurprise::<bool, u8> {
+            min_len: 0,
+            max_len: 50,
+            ..Default::default()
+        };
+        assert!(HashMap::generate_with_factor(&mut rng, &factor).len() <= 2);
+
+        let factor = HashSetSurprise::<bool> {
+            min_len: 50,
+            max_len: 50,
+            ..Default::default()
+        };
+        let err = HashSet::try_generate_with_factor(&mut rng, &factor).unwrap_err();
+        assert_eq!(err.field(), "min_len");
+    }
+
+    #[test]
+    fn ordered_factors() {
+        use std::collections::{BTreeMap, BTreeSet};
+
+        use crate::factors::{BTreeMapSurprise, BTreeSetSurprise, KeyLayout};
+
+        let mut rng = rand::thread_rng();
+
+        let factor = BTreeMapSurprise::<u32, bool> {
+            min_len: 10,
+            max_len: 10,
+            min_key: Some(100),
+            max_key: Some(200),
+            ..Default::default()
+        };
+        let map = BTreeMap::generate_with_factor(&mut rng, &factor);
+        assert_eq!(map.len(), 10);
+        assert!(map.keys().all(|key| (100..=200).contains(key)));
+
+        let factor = BTreeMapSurprise::<i8, bool> {
+            min_len: 20,
+            max_len: 20,
+            max_key: Some(-100),
+            layout: KeyLayout::Contiguous,
+            ..Default::default()
+        };
+        for _ in 0..20 {
+            let map = BTreeMap::generate_with_factor(&mut rng, &factor);
+            let keys: Vec<_> = map.keys().copied().collect();
+            assert_eq!(keys.len(), 20);
+            assert!(keys.windows(2).all(|pair| pair[0] + 1 == pair[1]));
+            assert!(keys[19] <= -100);
+        }
+
+        let factor = BTreeSetSurprise::<u8> {
+            min_len: 1,
+            items: NumberSurprise {
+                max: 10,
+                ..Default::default()
+            },
+            min_item: Some(20),
+            ..Default::default()
+        };
+        assert_eq!(factor.problems()[0].field(), "items");
+
+        let factor = BTreeSetSurprise::<u32> {
+            min_len: 20,
+            max_len: 20,
+            layout: KeyLayout::Clustered {
+                clusters: 1,
+                tightness: 1000,
+            },
+            ..Default::default()
+        };
+        let set = BTreeSet::generate_with_factor(&mut rng, &factor);
+        let spread = set.last().unwrap() - set.first().unwrap();
+        assert!(spread < u32::MAX / 10);
+
+        let factor = BTreeSetSurprise::<u32> {
+            min_item: Some(5),
+            max_item: Some(4),
+            ..Default::default()
+        };
+        let err = BTreeSet::try_generate_with_factor(&mut rng, &factor).unwrap_err();
+        assert_eq!(err.field(), "items");
+        assert_eq!(factor.problems().len(), 1);
+
+        let factor = BTreeMapSurprise::<u32, bool> {
+            layout: KeyLayout::Clustered {
+                clusters: 0,
+                tightness: 1,
+            },
+            ..Default::default()
+        };
+        let fields: Vec<_> = factor
+            .problems()
+            .into_iter()
+            .map(|err| err.field().to_owned())
+            .collect();
+        assert_eq!(fields, ["layout"]);
+    }
+
+    #[test]
+    fn item_orders() {
+        use std::collections::VecDeque;
+
+        use crate::{
+            factors::{ItemOrder, PermutationSurprise},
+            Enumerate, TryDistribution,
+        };
+
+        let mut rng = rand::thread_rng();
+
+        let factor = VecSurprise::<u8> {
+            min_len: 200,
+            max_len: 200,
+            order: ItemOrder::strictly_sorted(),
+            ..Default::default()
+        };
+        let vec = Vec::generate_with_factor(&mut rng, &factor);
+        assert_eq!(vec.len(), 200);
+        assert!(vec.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let factor = VecSurprise::<f64> {
+            order: ItemOrder::Sorted(f64::total_cmp),
+            ..Default::default()
+        };
+        let deque = VecDeque::generate_with_factor(&mut rng, &factor);
+        assert!(factor.order.is_satisfied_by(&deque));
+
+        let factor = VecSurprise::<bool> {
+            min_len: 2,
+            max_len: 2,
+            order: ItemOrder::unique(),
+            ..Default::default()
+        };
+        let vec = Vec::generate_with_factor(&mut rng, &factor);
+        assert_ne!(vec[0], vec[1]);
+        assert_eq!(Vec::enumerate(&factor).count(), 2);
+
+        let factor = VecSurprise::<bool> {
+            min_len: 3,
+            ..factor
+        };
+        let err = Vec::try_generate_with_factor(&mut rng, &factor).unwrap_err();
+        assert_eq!(err.field(), "min_len");
+
+        let factor = VecSurprise::<u8> {
+            order: ItemOrder::sorted(),
+            ..Default::default()
+        };
+        let vec = vec![3, 5, 5, 9];
+        assert!(vec
+            .shrink(&factor)
+            .all(|shrunk| shrunk.windows(2).all(|pair| pair[0] <= pair[1])));
+
+        let factor = PermutationSurprise {
+            min_len: 50,
+            max_len: 50,
+            ..Default::default()
+        };
+        let mut permutation: Vec<u8> = factor.sample(&mut rng);
+        permutation.sort_unstable();
+        assert!(permutation.into_iter().eq(0..50));
+
+        let factor = PermutationSurprise {
+            min_len: 300,
+            max_len: 300,
+            ..Default::default()
+        };
+        let err = TryDistribution::<Vec<u8>>::try_sample(&factor, &mut rng).unwrap_err();
+        assert_eq!(err.field(), "max_len");
+    }
+
+    #[test]
+    #[cfg(not(feature = "regex"))]
+    fn regex_strings_without_feature() {
+        use crate::factors::StringSurprise;
+
+        assert_eq!(StringSurprise::regex("[a-z]").unwrap_err().field(), "regex");
+        assert_eq!(StringSurprise::default().regex, None);
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn regex_strings() {
+        use crate::factors::StringSurprise;
+
+        let mut rng = rand::thread_rng();
+
+        let factor = StringSurprise::regex(r"[A-Z]{3}-\d{4}").unwrap();
+        for _ in 0..100 {
+            let id = String::generate_with_factor(&mut rng, &factor);
+            let (letters, digits) = id.split_once('-').unwrap();
+            assert!(letters.len() == 3 && letters.bytes().all(|b| b.is_ascii_uppercase()));
+            assert!(digits.len() == 4 && digits.bytes().all(|b| b.is_ascii_digit()));
+            assert_eq!(id.shrink(&factor).count(), 0);
+        }
+
+        let factor = StringSurprise::regex(r"(\w|[\s\d]){20}\D").unwrap();
+        for _ in 0..100 {
+            let string = String::generate_with_factor(&mut rng, &factor);
+            assert!(string.chars().take(20).all(|c| c.is_ascii()));
+            assert!(!string.ends_with(|c: char| c.is_ascii_digit()));
+        }
+
+        assert_eq!(StringSurprise::regex("(").unwrap_err().field(), "regex");
+        assert_eq!(
+            StringSurprise::regex(r"\bword").unwrap_err().field(),
+            "regex"
+        );
+
+        let factor = crate::size::with_size(3, || StringSurprise::regex("a+").unwrap());
+        for _ in 0..100 {
+            let len = String::generate_with_factor(&mut rng, &factor).len();
+            assert!((1..=4).contains(&len));
+        }
+    }
+
+    #[test]
+    fn grammar_strings() {
+        use crate::{
+            factors::{Grammar, GrammarSurprise},
+            TryDistribution,
+        };
+
+        let mut rng = rand::thread_rng();
+
+        let factor = GrammarSurprise {
+            grammar: Grammar::new(r#"expr = "(" , expr , ")" @ 5 | "x" ;"#).unwrap(),
+            max_depth: 4,
+            ..Default::default()
+        };
+        for _ in 0..100 {
+            let expr = String::generate_with_factor(&mut rng, &factor);
+            let depth = expr.matches('(').count();
+            assert!(depth < 4);
+            assert_eq!(expr, format!("{}x{}", "(".repeat(depth), ")".repeat(depth)));
+        }
+
+        let grammar = Grammar::new(
+            r#"
+            (* BNF with repeated definitions *)
+            <digits> ::= <digit>+   # at least one digit
+            <digit> ::= "1"
+            <digit> ::= '2' | "3" @ 0
+            <letters> ::= { "a" , [ "\n" ] }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            grammar.rules().collect::<Vec<_>>(),
+            ["digits", "digit", "letters"]
+        );
+
+        let factor = GrammarSurprise {
+            grammar,
+            max_repeat: 3,
+            ..Default::default()
+        };
+        for _ in 0..100 {
+            let digits = String::generate_with_factor(&mut rng, &factor);
+            assert!((1..=3).contains(&digits.len()), "{digits}");
+            assert!(digits.bytes().all(|b| b == b'1' || b == b'2'));
+        }
+
+        let factor = GrammarSurprise {
+            start: Some("letters".to_owned()),
+            ..factor
+        };
+        let letters = String::generate_with_factor(&mut rng, &factor);
+        assert!(letters.chars().all(|c| c == 'a' || c == '\n'));
+
+        #[cfg(feature = "regex")]
+        {
+            let path = std::env::temp_dir().join("surprise_me_grammar.ebnf");
+            std::fs::write(&path, "id = /[a-z]{3}/ , /[0-9]/ ;").unwrap();
+            let factor = GrammarSurprise {
+                grammar: Grammar::from_file(&path).unwrap(),
+                ..Default::default()
+            };
+            let id = String::generate_with_factor(&mut rng, &factor);
+            assert!(id.len() == 4 && id.bytes().last().unwrap().is_ascii_digit());
+            std::fs::remove_file(path).unwrap();
+        }
+
+        let invalid = [
+            "a = b ;",
+            "a = \"a\" , a ;",
+            "a = \"x\" @ 0 ;",
+            "a = ( \"x\" ;",
+            "a = 'x ;",
+            "a = /(/ ;",
+            "(* a = \"x\" ;",
+            "a \"x\" ;",
+        ];
+        for grammar in invalid {
+            assert_eq!(Grammar::new(grammar).unwrap_err().field(), "grammar");
+        }
+        assert!(Grammar::from_file("missing.ebnf").is_err());
+
+        let factor = GrammarSurprise::default();
+        let err = TryDistribution::<String>::try_sample(&factor, &mut rng).unwrap_err();
+        assert_eq!(err.field(), "grammar");
+
+        let factor = GrammarSurprise {
+            grammar: Grammar::new("a = 'a' ;").unwrap(),
+            start: Some("b".to_owned()),
+            ..Default::default()
+        };
+        let fields: Vec<_> = factor
+            .problems()
+            .into_iter()
+            .map(|err| err.field().to_owned())
+            .collect();
+        assert_eq!(fields, ["start"]);
+    }
+
+    #[test]
+    fn structured_text() {
+        use crate::{
+            budget::{remaining_budget, with_budget, Budget},
+            factors::{
+                CharSetSurprise, CharSurprise, CsvSurprise, JsonSurprise, StringSurprise,
+                TomlSurprise,
+            },
+            TryDistribution,
+        };
+
+        let mut rng = rand::thread_rng();
+
+        let strings = StringSurprise {
+            max_len: 4,
+            chars: CharSurprise::Printable,
+            naughty_chance: 0.1,
+            ..Default::default()
+        };
+
+        let factor = JsonSurprise {
+            strings: strings.clone(),
+            ..Default::default()
+        };
+        for _ in 0..200 {
+            let json = TryDistribution::<String>::try_sample(&factor, &mut rng).unwrap();
+            let value: serde_json::Value = serde_json::from_str(&json).expect(&json);
+            assert!(value.is_array() || value.is_object());
+        }
+
+        let factor = TomlSurprise {
+            strings,
+            ..Default::default()
+        };
+        for _ in 0..200 {
+            let toml = TryDistribution::<String>::try_sample(&factor, &mut rng).unwrap();
+            toml::from_str::<toml::Table>(&toml).expect(&toml);
+        }
+
+        // Counts the fields of each record, or returns `None` if a quote is unterminated
+        let records = |csv: &str| {
+            let mut records = vec![1];
+            let mut chars = csv.chars().peekable();
+            let mut quoted = false;
+
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' if quoted && chars.peek() == Some(&'"') => drop(chars.next()),
+                    '"' => quoted = !quoted,
+                    ';' if !quoted => *records.last_mut().unwrap() += 1,
+                    '\n' if !quoted => records.push(1),
+                    _ => {}
+                }
+            }
+
+            records.pop();
+            (!quoted).then_some(records)
+        };
+
+        let factor = CsvSurprise {
+            min_len: 2,
+            max_len: 10,
+            delimiter: ';',
+            fields: StringSurprise {
+                char_set: Some(CharSetSurprise::Alphabet(vec!['a', '"', ';', '\r', '\n'])),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        for _ in 0..100 {
+            let csv = TryDistribution::<String>::try_sample(&factor, &mut rng).unwrap();
+            let records = records(&csv).unwrap();
+            assert!((3..=11).contains(&records.len()));
+            assert!(records.iter().all(|&fields| fields == records[0]));
+        }
+
+        let corrupt = |factor: &dyn Fn() -> String, valid: &dyn Fn(&str) -> bool| {
+            for _ in 0..100 {
+                let text = factor();
+                assert!(!valid(&text), "{text}");
+            }
+        };
+        let json = JsonSurprise {
+            corruption_chance: 1.0,
+            ..Default::default()
+        };
+        corrupt(
+            &|| TryDistribution::<String>::try_sample(&json, &mut rand::thread_rng()).unwrap(),
+            &|text| serde_json::from_str::<serde_json::Value>(text).is_ok(),
+        );
+        let toml = TomlSurprise {
+            corruption_chance: 1.0,
+            ..Default::default()
+        };
+        corrupt(
+            &|| TryDistribution::<String>::try_sample(&toml, &mut rand::thread_rng()).unwrap(),
+            &|text| toml::from_str::<toml::Table>(text).is_ok(),
+        );
+        let csv = CsvSurprise {
+            corruption_chance: 1.0,
+            ..factor
+        };
+        corrupt(
+            &|| TryDistribution::<String>::try_sample(&csv, &mut rand::thread_rng()).unwrap(),
+            &|text| records(text).is_some_and(|records| records.iter().all(|&n| n == records[0])),
+        );
+
+        let factor = CsvSurprise {
+            min_columns: 0,
+            delimiter: '"',
+            ..Default::default()
+        };
+        let fields: Vec<_> = factor
+            .problems()
+            .into_iter()
+            .map(|err| err.field().to_owned())
+            .collect();
+        assert_eq!(fields, ["min_columns", "delimiter"]);
+        let factor = JsonSurprise {
+            max_depth: 0,
+            ..Default::default()
+        };
+        let err = TryDistribution::<String>::try_sample(&factor, &mut rng).unwrap_err();
+        assert_eq!(err.field(), "max_depth");
+
+        // Each value is spent once as an element of its container
+        let factor = JsonSurprise {
+            max_depth: 1,
+            strings: StringSurprise {
+                max_len: 0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        for _ in 0..20 {
+            let (json, remaining) = with_budget(Budget::Elements(3), || {
+                let json: String = factor.sample(&mut rng);
+
+                (json, remaining_budget())
+            });
+            let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+            if let Some(values) = value.as_array() {
+                assert_eq!(remaining, Some(Budget::Elements(3 - values.len())));
+            } else {
+                assert!(value.as_object().unwrap().len() <= 1);
+            }
+        }
+    }
+
+    #[test]
+    fn naughty_strings() {
+        use crate::factors::{StringSurprise, NAUGHTY_STRINGS};
+
+        let mut rng = rand::thread_rng();
+
+        let factor = StringSurprise {
+            min_len: 1,
+            max_len: 1,
+            naughty_chance: 1.0,
+            ..Default::default()
+        };
+        for _ in 0..100 {
+            let string = String::generate_with_factor(&mut rng, &factor);
+            assert!(NAUGHTY_STRINGS.contains(&string.as_str()));
+        }
+
+        let factor = StringSurprise {
+            min_len: 200,
+            max_len: 200,
+            naughty_chance: 0.1,
+            ..Default::default()
+        };
+        let string = String::generate_with_factor(&mut rng, &factor);
+        assert!(string.chars().any(|c| !c.is_ascii_alphanumeric()));
+
+        let factor = StringSurprise {
+            naughty_chance: -0.5,
+            ..Default::default()
+        };
+        let err = String::try_generate_with_factor(&mut rng, &factor).unwrap_err();
+        assert_eq!(err.field(), "naughty_chance");
+        assert_eq!(factor.problems().len(), 1);
+    }
+
+    #[test]
+    fn char_classes() {
+        use crate::{
+            factors::{CharSetSurprise, CharSurprise, StringSurprise, WeightedChars},
+            TryDistribution,
+        };
+
+        let mut rng = rand::thread_rng();
+
+        let chars = [
+            CharSurprise::Printable,
+            CharSurprise::Whitespace,
+            CharSurprise::Punctuation,
+            CharSurprise::Range {
+                min: 'α', max: 'ω'
+            },
+        ];
+
+        for factor in chars {
+            let string_factor = StringSurprise {
+                min_len: 50,
+                max_len: 50,
+                chars: factor,
+                ..Default::default()
+            };
+
+            let string = String::generate_with_factor(&mut rng, &string_factor);
+            assert!(string.chars().all(|c| factor.contains(c)), "{factor:?}");
+            assert!(string.len() <= 50 * factor.max_len_utf8());
+
+            let c = char::generate_with_factor(&mut rng, &factor);
+            assert!(factor.contains(c));
+            assert!(c.shrink(&factor).all(|c| factor.contains(c)));
+            assert!(string
+                .shrink(&string_factor)
+                .all(|string| string.chars().all(|c| factor.contains(c))));
+        }
+
+        #[cfg_attr(not(feature = "regex"), allow(unused_mut))]
+        let mut char_sets = vec![
+            CharSetSurprise::Chars(CharSurprise::Whitespace),
+            CharSetSurprise::Alphabet(vec!['x', 'ÿ', '🦀']),
+            CharSetSurprise::Mixture(vec![
+                WeightedChars {
+                    chars: CharSurprise::Ascii.into(),
+                    weight: 3.0,
+                },
+                WeightedChars {
+                    chars: CharSurprise::Whitespace.into(),
+                    weight: 1.0,
+                },
+            ]),
+        ];
+        #[cfg(feature = "regex")]
+        char_sets.extend([
+            CharSetSurprise::class(r"\p{Cyrillic}").unwrap(),
+            CharSetSurprise::class(r"[\p{Han}0-9]").unwrap(),
+            CharSetSurprise::class(r"\p{Emoji_Presentation}").unwrap(),
+            CharSetSurprise::class(r"[\u{d000}-\u{e100}]").unwrap(),
+        ]);
+
+        for factor in char_sets {
+            let string_factor = StringSurprise {
+                min_len: 50,
+                max_len: 50,
+                char_set: Some(factor.clone()),
+                ..Default::default()
+            };
+
+            let string = String::generate_with_factor(&mut rng, &string_factor);
+            assert!(string.chars().all(|c| factor.contains(c)), "{factor:?}");
+            assert!(string.len() <= 50 * factor.max_len_utf8());
+
+            let c: char = factor.sample(&mut rng);
+            assert!(factor.contains(c));
+            assert!(string
+                .shrink(&string_factor)
+                .all(|string| string.chars().all(|c| factor.contains(c))));
+        }
+
+        #[cfg(feature = "regex")]
+        {
+            assert!(CharSetSurprise::class(r"\p{Greek}").unwrap().contains('λ'));
+            assert!(!CharSetSurprise::class(r"\p{Greek}").unwrap().contains('l'));
+            assert!(CharSetSurprise::class("[a]").unwrap().contains('a'));
+
+            for pattern in [
+                "abc",
+                "[a",
+                r"\p{Unknown}",
+                r"[^\x00-\u{10ffff}]",
+                r"(?-u:\xff)",
+            ] {
+                assert_eq!(
+                    CharSetSurprise::class(pattern).unwrap_err().field(),
+                    "class"
+                );
+            }
+        }
+
+        #[cfg(not(feature = "regex"))]
+        assert_eq!(
+            CharSetSurprise::class("[a-z]").unwrap_err().field(),
+            "class"
+        );
+
+        let factor = CharSurprise::Range { min: 'z', max: 'a' };
+        let err = char::try_generate_with_factor(&mut rng, &factor).unwrap_err();
+        assert_eq!(err.field(), "min");
+
+        let factor = StringSurprise {
+            min_len: 1,
+            chars: factor,
+            ..Default::default()
+        };
+        let err = String::try_generate_with_factor(&mut rng, &factor).unwrap_err();
+        assert_eq!(err.field(), "chars.min");
+
+        let invalid = [
+            (CharSetSurprise::Alphabet(Vec::new()), "alphabet"),
+            (CharSetSurprise::Mixture(Vec::new()), "mixture"),
+            (
+                CharSetSurprise::Mixture(vec![WeightedChars {
+                    chars: CharSetSurprise::Alphabet(Vec::new()),
+                    weight: 1.0,
+                }]),
+                "mixture.alphabet",
+            ),
+        ];
+
+        for (factor, field) in invalid {
+            let err = TryDistribution::<char>::try_sample(&factor, &mut rng).unwrap_err();
+            assert_eq!(err.field(), field);
+
+            let factor = StringSurprise {
+                min_len: 1,
+                char_set: Some(factor),
+                ..Default::default()
+            };
+            let err = String::try_generate_with_factor(&mut rng, &factor).unwrap_err();
+            assert_eq!(err.field(), format!("char_set.{field}"));
+        }
+    }
+
+    #[test]
+    fn len_distributions() {
+        use crate::factors::{LenBucket, LenDistribution, StringSurprise};
+
+        let mut rng = rand::thread_rng();
+
+        #[cfg(feature = "distributions")]
+        {
+            let factor = VecSurprise::<u8> {
+                min_len: 2,
+                max_len: 100,
+                len: LenDistribution::Geometric { p: 0.5 },
+                ..Default::default()
+            };
+            let short = (0..1000).filter(|_| factor.len(&mut rng) <= 4).count();
+            assert!(short > 800);
+        }
+
+        let factor = StringSurprise {
+            len: LenDistribution::MostlyEmpty { chance: 1.0 },
+            ..Default::default()
+        };
+        for _ in 0..100 {
+            assert!(String::generate_with_factor(&mut rng, &factor).len() <= 1);
+        }
+
+        let factor = VecSurprise::<u8> {
+            min_len: 0,
+            max_len: 15,
+            len: LenDistribution::Buckets(vec![
+                LenBucket {
+                    min_len: 3,
+                    max_len: 3,
+                    weight: 1.0,
+                },
+                LenBucket {
+                    min_len: 10,
+                    max_len: 20,
+                    weight: 1.0,
+                },
+            ]),
+            ..Default::default()
+        };
+        for _ in 0..100 {
+            let len = factor.len(&mut rng);
+            assert!(len == 3 || (10..=15).contains(&len));
+        }
+
+        let factor = VecSurprise::<u8> {
+            len: LenDistribution::Buckets(Vec::new()),
+            ..Default::default()
+        };
+        let err = Vec::try_generate_with_factor(&mut rng, &factor).unwrap_err();
+        assert_eq!(err.field(), "len");
+    }
+
+    #[test]
+    fn enumerate_values() {
+        use crate::Enumerate;
+
+        assert_eq!(bool::enumerate_all(), [false, true]);
+        assert_eq!(
+            bool::enumerate(&BoolSurprise { chance: 1.0 }).collect::<Vec<_>>(),
+            [true]
+        );
+
+        assert_eq!(
+            Option::<bool>::enumerate_all(),
+            [None, Some(false), Some(true)]
+        );
+
+        let factor = NumberSurprise {
+            min: 3u8,
+            max: 6,
+            ..Default::default()
+        };
+        assert_eq!(u8::enumerate(&factor).collect::<Vec<_>>(), [3, 4, 5, 6]);
+        assert_eq!(u8::enumerate_all().len(), 256);
+
+        let factor = VecSurprise::<bool> {
+            min_len: 0,
+            max_len: 2,
+            len: Default::default(),
+            order: Default::default(),
+            items: BoolSurprise { chance: 0.5 },
+        };
+        assert_eq!(
+            Vec::<bool>::enumerate(&factor).collect::<Vec<_>>(),
+            [
+                vec![],
+                vec![false],
+                vec![true],
+                vec![false, false],
+                vec![false, true],
+                vec![true, false],
+                vec![true, true],
+            ]
+        );
+
+        assert_eq!(
+            <(bool, Result<(), bool>)>::enumerate_all(),
+            [
+                (false, Ok(())),
+                (false, Err(false)),
+                (false, Err(true)),
+                (true, Ok(())),
+                (true, Err(false)),
+                (true, Err(true)),
+            ]
+        );
+        assert_eq!(
+            <[NonZeroI32; 2]>::enumerate(&Default::default())
+                .next()
+                .unwrap()[0]
+                .get(),
+            i32::MIN
+        );
+    }
+
+    #[test]
+    fn filter() {
+        use crate::{filter::Filter, TryDistribution};
+
+        let mut rng = rand::thread_rng();
+
+        let factor = NumberSurprise::<u8>::default().filter(|n: &u8| *n < 64);
+        for _ in 0..50 {
+            assert!(u8::generate_with_factor(&mut rng, &factor) < 64);
+        }
+        assert_eq!(factor.stats().accepted(), 50);
+        assert!(factor.stats().rejection_rate() > 0.0);
+
+        let factor = factor.clone();
+        assert_eq!(factor.stats().accepted(), 50);
+        factor.stats().reset();
+        assert_eq!(factor.stats().rejection_rate(), 0.0);
+
+        let mut factor = VecSurprise::<bool>::default().filter(|vec: &Vec<bool>| vec.len() > 200);
+        factor.max_retries = 3;
+        let err = TryDistribution::<Vec<bool>>::try_sample(&factor, &mut rng).unwrap_err();
+        assert_eq!(err.field(), "max_retries");
+        assert_eq!(factor.stats().rejected(), 4);
+
+        factor.factor.min_len = 5;
+        factor.factor.max_len = 4;
+        assert_eq!(factor.problems().len(), 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_factors() {
+        use std::collections::HashSet;
+
+        use crate::factors::HashSetSurprise;
+
+        let factor: SurpriseFactor<Vec<(u8, bool)>> =
+            serde_json::from_str(r#"{ "max_len": 5, "items": [{ "max": 9 }] }"#).unwrap();
+
+        assert_eq!(factor.min_len, 0);
+        assert_eq!(factor.max_len, 5);
+        assert_eq!(
+            factor.items.0,
+            NumberSurprise {
+                min: 0,
+                max: 9,
+                ..Default::default()
+            }
+        );
+        assert_eq!(factor.items.1, BoolSurprise { chance: 0.5 });
+
+        let json = serde_json::to_string(&factor).unwrap();
+        assert_eq!(
+            json,
+            r#"{"min_len":0,"max_len":5,"len":"Uniform","items":[{"min":0,"max":9,"edge_chance":0.0,"distribution":"Uniform"},{"chance":0.5}]}"#
+        );
+
+        let factor = HashSetSurprise::<char> {
+            min_len: 3,
+            ..Default::default()
+        };
+
+        let toml = toml::to_string(&factor).unwrap();
+        let factor: SurpriseFactor<HashSet<char>> = toml::from_str(&toml).unwrap();
+
+        assert_eq!(factor.min_len, 3);
+        assert_eq!(factor.max_len, 100);
+    }
 }
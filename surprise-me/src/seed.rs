@@ -0,0 +1,141 @@
+//! Reproducible generation through seeded random number generators.
+//!
+//! A failure caused by a random value can only be replayed if the generator
+//! was seeded and its output does not depend on the platform. [`PortableRng`]
+//! provides both so a seed always generates the same values. Lengths and indices are
+//! sampled as `u64` for the same reason, only `usize` and `isize` numbers themselves
+//! differ between 32-bit and 64-bit platforms.
+//!
+//! In tests, [`SurpriseRng`] picks a random seed, or reads it from the environment
+//! variable `SURPRISE_SEED`, and prints it if the test panics:
+//!
+//! ```rust
+//! use surprise_me::{seed::SurpriseRng, Surprise};
+//!
+//! let mut rng = SurpriseRng::new();
+//! let value = Vec::<u8>::generate(&mut rng);
+//!
+//! // If this panics, the seed is printed and the failure can be replayed
+//! // by running the test again with `SURPRISE_SEED=<seed>`
+//! assert!(value.len() <= 100);
+//! ```
+
+use std::{env, ops::RangeInclusive, thread};
+
+use rand::{Error, Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// The random number generator used for seeded generation.
+///
+/// Its output for a given seed is the same on every platform.
+/// Changing the generator is considered a breaking change.
+pub type PortableRng = ChaCha8Rng;
+
+/// The environment variable from which [`SurpriseRng::new`] reads its seed
+pub const SEED_ENV_VAR: &str = "SURPRISE_SEED";
+
+/// Creates a [`PortableRng`] from the given seed
+#[inline]
+pub fn portable_rng(seed: u64) -> PortableRng {
+    PortableRng::seed_from_u64(seed)
+}
+
+/// Samples a length or index from `range` the same way on 32-bit and 64-bit platforms,
+/// which `gen_range` on `usize` doesn't
+#[inline]
+pub(crate) fn gen_len<R: Rng + ?Sized>(rng: &mut R, range: RangeInclusive<usize>) -> usize {
+    let (start, end) = range.into_inner();
+
+    rng.gen_range(start as u64..=end as u64) as usize
+}
+
+/// Reads the seed from the environment variable `SURPRISE_SEED`.
+///
+/// # Panics
+///
+/// Panics if the variable is set but is not an unsigned 64-bit integer.
+/// Silently ignoring it would generate different values than the ones being replayed.
+pub fn env_seed() -> Option<u64> {
+    let value = env::var(SEED_ENV_VAR).ok()?;
+
+    match value.trim().parse() {
+        Ok(seed) => Some(seed),
+        Err(_) => panic!("`{SEED_ENV_VAR}` must be an unsigned 64-bit integer, got `{value}`"),
+    }
+}
+
+/// A [`PortableRng`] that remembers its seed and prints it if
+/// the thread panics while the generator is alive.
+#[derive(Debug)]
+pub struct SurpriseRng {
+    seed: u64,
+    rng: PortableRng,
+}
+
+impl SurpriseRng {
+    /// Creates a generator with the seed of the environment variable
+    /// `SURPRISE_SEED` or with a random seed if the variable is not set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the variable is set but is not an unsigned 64-bit integer.
+    pub fn new() -> Self {
+        Self::with_seed(env_seed().unwrap_or_else(rand::random))
+    }
+
+    /// Creates a generator with the given seed
+    #[inline]
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: portable_rng(seed),
+        }
+    }
+
+    /// The seed that the generator was created with
+    #[inline]
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl Default for SurpriseRng {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RngCore for SurpriseRng {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    #[inline]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    #[inline]
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+impl Drop for SurpriseRng {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            eprintln!(
+                "surprise-me: panicked while generating with seed {seed}, \
+                rerun with `{SEED_ENV_VAR}={seed}` to reproduce",
+                seed = self.seed,
+            );
+        }
+    }
+}
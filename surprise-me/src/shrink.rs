@@ -0,0 +1,203 @@
+use crate::{Surprise, SurpriseFactor};
+
+/// Trait to shrink values into simpler ones.
+///
+/// Shrinking is guided by the same surprise factor that was used to generate
+/// the value so that candidates stay within its bounds, e.g. numbers shrink
+/// toward `0` or the factor's `min` and collections never drop below `min_len`.
+///
+/// Its main purpose is [`Shrink::minimize`]: When a randomly generated value
+/// makes a test fail, it can be reduced to a minimal counterexample.
+///
+/// # Derive macro
+///
+/// Structs are shrunk field by field. Enums additionally try to switch to simpler
+/// unit variants with non-zero weight before shrinking the fields of the current
+/// variant. Unit variants are simpler if they have a lower weight or are declared
+/// earlier, though unit variants themselves only switch to ones with a lower weight,
+/// or the same weight and declared earlier.
+///
+/// Variants with fields are never switched to, since there is no simplest value
+/// of them without generating one. Their fields are shrunk instead once the value
+/// is of that variant.
+///
+/// All fields must implement [`Clone`] and, unless they have a custom surprise factor
+/// through `#[factor = "..."]`, [`Shrink`]. Fields with a custom surprise factor are
+/// left as they are.
+///
+/// ```rust
+/// # use surprise_me::{Shrink, Surprise};
+/// #
+/// #[derive(Clone, Debug, Surprise, Shrink)]
+/// struct MyStruct {
+///     #[factor(min = 10)]
+///     a: u32,
+///     #[factor(min_len = 2)]
+///     b: Vec<bool>,
+/// }
+///
+/// let factor = MyStructSurprise::default();
+/// let value = MyStruct::generate_with_factor(&mut surprise_me::rand::thread_rng(), &factor);
+///
+/// // Pretend that every value fails our test
+/// let minimal = value.minimize(&factor, |_| true);
+///
+/// assert_eq!(minimal.a, 10);
+/// assert_eq!(minimal.b, [false, false]);
+/// ```
+pub trait Shrink: Surprise {
+    /// Returns candidates that are simpler than `self` but can still be generated
+    /// by the given surprise factor.
+    ///
+    /// Simpler candidates come first.
+    fn shrink<'a>(&'a self, factor: &'a SurpriseFactor<Self>) -> Shrinker<'a, Self>;
+
+    /// Repeatedly shrinks `self` for as long as a candidate still `fails` and
+    /// returns the last failing value.
+    fn minimize<F>(self, factor: &SurpriseFactor<Self>, mut fails: F) -> Self
+    where
+        F: FnMut(&Self) -> bool,
+    {
+        let mut current = self;
+
+        loop {
+            let next = current.shrink(factor).find(|candidate| fails(candidate));
+
+            match next {
+                Some(candidate) => current = candidate,
+                None => return current,
+            }
+        }
+    }
+}
+
+/// Iterator over shrink candidates, see [`Shrink::shrink`].
+pub type Shrinker<'a, T> = Box<dyn Iterator<Item = T> + 'a>;
+
+/// Shrink candidates with chunks of items removed.
+///
+/// Starts by truncating to `min_len` and then removes smaller and smaller chunks,
+/// never going below `min_len`.
+pub(crate) fn shrink_len<'a, T, I, C>(items: I, len: usize, min_len: usize) -> Shrinker<'a, C>
+where
+    I: Iterator<Item = T> + Clone + 'a,
+    C: FromIterator<T>,
+{
+    let removable = len.saturating_sub(min_len);
+
+    let sizes = std::iter::successors(Some(removable).filter(|&n| n > 0), |&n| {
+        Some(n / 2).filter(|&n| n > 0)
+    });
+
+    let candidates = sizes.flat_map(move |size| {
+        let items = items.clone();
+
+        (0..=len - size).step_by(size).map(move |start| {
+            items
+                .clone()
+                .enumerate()
+                .filter(|(i, _)| !(start..start + size).contains(i))
+                .map(|(_, item)| item)
+                .collect()
+        })
+    });
+
+    Box::new(candidates)
+}
+
+/// Shrink candidates with a single item shrunk.
+pub(crate) fn shrink_items<'a, T, I, C>(items: I, factor: &'a SurpriseFactor<T>) -> Shrinker<'a, C>
+where
+    T: Shrink + Clone + 'a,
+    I: Iterator<Item = &'a T> + Clone + 'a,
+    C: FromIterator<T>,
+{
+    let candidates = items.clone().enumerate().flat_map(move |(i, item)| {
+        let items = items.clone();
+
+        item.shrink(factor)
+            .map(move |shrunk| replace_nth(items.clone(), i, shrunk))
+    });
+
+    Box::new(candidates)
+}
+
+fn replace_nth<'a, T, I, C>(items: I, n: usize, value: T) -> C
+where
+    T: Clone + 'a,
+    I: Iterator<Item = &'a T>,
+    C: FromIterator<T>,
+{
+    let mut value = Some(value);
+
+    items
+        .enumerate()
+        .map(|(i, item)| match value.take() {
+            Some(value) if i == n => value,
+            taken => {
+                value = taken;
+
+                item.clone()
+            }
+        })
+        .collect()
+}
+
+/// Indices of the variants that an enum variant can shrink into, simplest first.
+///
+/// Variants with fields can switch to any variant that is declared earlier or has a
+/// lower weight. Unit variants only switch to variants with a lower weight, or the
+/// same weight and declared earlier, so that shrinking never goes in circles.
+/// Variants with a weight of zero are never generated and thus skipped.
+pub fn simpler_variants(weights: &[f64], current: usize, is_unit: bool) -> Vec<usize> {
+    let order = |i: usize, j: usize| weights[i].total_cmp(&weights[j]).then(i.cmp(&j));
+
+    let mut simpler: Vec<_> = (0..weights.len())
+        .filter(|&i| weights[i] > 0.0 && i != current)
+        .filter(|&i| {
+            if is_unit {
+                order(i, current).is_lt()
+            } else {
+                i < current || weights[i] < weights[current]
+            }
+        })
+        .collect();
+
+    simpler.sort_by(|&i, &j| order(i, j));
+
+    simpler
+}
+
+/// Shrink candidates of an `Option<Box<Self>>` recursion point
+pub fn shrink_recursive_option<'a, T>(
+    value: &'a Option<Box<T>>,
+    factor: &'a SurpriseFactor<T>,
+) -> Shrinker<'a, Option<Box<T>>>
+where
+    T: Shrink,
+{
+    match value {
+        Some(inner) => {
+            let shrunk = T::shrink(inner, factor).map(|inner| Some(Box::new(inner)));
+
+            Box::new(std::iter::once(None).chain(shrunk))
+        }
+        None => Box::new(std::iter::empty()),
+    }
+}
+
+/// Shrink candidates of a `Vec<Self>` recursion point.
+///
+/// Since recursion points are empty at the maximum depth, `min_len` is not respected.
+pub fn shrink_recursive_vec<'a, T>(
+    value: &'a [T],
+    factor: &'a SurpriseFactor<T>,
+) -> Shrinker<'a, Vec<T>>
+where
+    T: Shrink + Clone,
+{
+    let removed = shrink_len(value.iter().cloned(), value.len(), 0);
+    let shrunk = shrink_items(value.iter(), factor);
+
+    Box::new(removed.chain(shrunk))
+}
@@ -0,0 +1,62 @@
+//! The size that scales the default surprise factors.
+//!
+//! The default `max_len` of collection and string factors is the current size
+//! instead of a fixed number. Since derived factors create their default through
+//! the defaults of their fields, the size reaches every nested factor.
+//!
+//! Starting with a small size and growing it over a test run first covers
+//! small values that are easy to debug before moving on to larger ones:
+//!
+//! ```rust
+//! use surprise_me::{rand, Surprise};
+//! use std::collections::HashMap;
+//!
+//! let mut rng = rand::thread_rng();
+//!
+//! for size in 0..20 {
+//!     let map = HashMap::<u8, Vec<f32>>::generate_sized(&mut rng, size);
+//!
+//!     assert!(map.len() <= size);
+//!     assert!(map.values().all(|vec| vec.len() <= size));
+//! }
+//! ```
+
+use std::cell::Cell;
+
+/// The size that is used unless specified otherwise
+pub const DEFAULT_SIZE: usize = 100;
+
+thread_local! {
+    static SIZE: Cell<usize> = const { Cell::new(DEFAULT_SIZE) };
+}
+
+/// The size that default surprise factors currently use on this thread
+#[inline]
+pub fn current_size() -> usize {
+    SIZE.with(Cell::get)
+}
+
+/// Runs `f` with the given size, e.g. to create default surprise factors of that size.
+///
+/// The previous size is restored afterwards, even if `f` panics.
+///
+/// ```rust
+/// use surprise_me::{factors::StringSurprise, size};
+///
+/// let factor = size::with_size(10, StringSurprise::default);
+/// assert_eq!(factor.max_len, 10);
+/// assert_eq!(size::current_size(), size::DEFAULT_SIZE);
+/// ```
+pub fn with_size<T>(size: usize, f: impl FnOnce() -> T) -> T {
+    struct Restore(usize);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SIZE.with(|cell| cell.set(self.0));
+        }
+    }
+
+    let _restore = Restore(SIZE.with(|cell| cell.replace(size)));
+
+    f()
+}
@@ -1,5 +1,12 @@
 use rand::{prelude::Distribution, Rng};
 
+use crate::{
+    budget::{with_budget, Budget},
+    seed::portable_rng,
+    size::with_size,
+    SurpriseError,
+};
+
 /// Trait to generate random instances of types.
 ///
 /// # Derive macro
@@ -15,10 +22,20 @@ use rand::{prelude::Distribution, Rng};
 ///
 /// Fields can be annotated with:
 /// - `#[factor(field = ...)]` or `#[factor(field(...))]`: These attributes modify the
-/// default values for the surprise factor. `field` can be the name of any field
-/// of the type's [`SurpriseFactor`]. Note that this works recursively for inner fields.
+///   default values for the surprise factor. `field` can be the name of any field
+///   of the type's [`SurpriseFactor`]. Note that this works recursively for inner fields.
+///   Any other expression such as `#[factor(CharSurprise::Range { min: 'a', max: 'z' })]`
+///   replaces the whole surprise factor, or the inner one within `field(...)`.
 /// - `#[factor = "MySurpriseFactor"]`: Same as for the outer struct; specifying a factor
-/// like this will prevent the macro from creating a new type.
+///   like this will prevent the macro from creating a new type.
+/// - `#[factor(with = "path::to::function")]`: Generates the field by calling a function
+///   `fn<R: Rng + ?Sized>(&mut R) -> FieldType`. The field's surprise factor is a
+///   [`UnitSurprise`](crate::factors::UnitSurprise) and the field is never shrunk.
+/// - `#[factor(depends = |start, rng| ...)]`: Computes the field from other fields through a
+///   closure. Its parameters are named after the fields it receives references to, `_0`, `_1`
+///   and so on for tuple fields, and `rng` for the random number generator. Fields are
+///   generated in an order where they come after the fields they depend on. Like with `with`,
+///   the field is never shrunk, so shrinking may break the relation to the other fields.
 ///
 /// ```rust
 /// # use surprise_me::Surprise;
@@ -60,9 +77,12 @@ use rand::{prelude::Distribution, Rng};
 ///                     inner: NumberSurprise {
 ///                         min: 10,
 ///                         max: 20,
+///                         ..Default::default()
 ///                     },
 ///                 },
 ///                 min_len: Default::default(),
+///                 len: Default::default(),
+///                 order: Default::default(),
 ///             }
 ///         }
 ///     }
@@ -94,6 +114,8 @@ use rand::{prelude::Distribution, Rng};
 /// Fields of variants have the same attributes as fields for structs, i.e.
 /// - `#[factor(field = ...)]` or `#[factor(field(...))]`
 /// - `#[factor = "MySurpriseFactor")]`
+/// - `#[factor(with = "path::to::function")]`
+/// - `#[factor(depends = |field, rng| ...)]`
 ///
 /// ```rust
 /// # use surprise_me::Surprise;
@@ -144,13 +166,97 @@ use rand::{prelude::Distribution, Rng};
 /// }
 /// ```
 ///
+/// # Filters
+///
+/// Invariants that span several fields can be enforced with
+/// `#[surprise(filter = "path::to::predicate")]`, where the predicate is a function
+/// taking `&Self` and returning a `bool`. Values are generated until the predicate
+/// accepts one, with up to `max_retries` attempts after the first before generation
+/// fails. It defaults to [`DEFAULT_MAX_RETRIES`](crate::filter::DEFAULT_MAX_RETRIES)
+/// and is available as the `max_retries` field of the surprise factor. How many values
+/// were rejected is tracked by its `filter_stats` field, which isn't (de)serialized.
+///
+/// Derived [`Shrink`](crate::Shrink) and [`Enumerate`](crate::Enumerate) implementations
+/// skip values that the predicate rejects.
+///
+/// ```rust
+/// # use surprise_me::Surprise;
+/// #
+/// fn is_sorted(range: &Range) -> bool {
+///     range.start <= range.end
+/// }
+///
+/// #[derive(Surprise)]
+/// #[surprise(filter = "is_sorted", max_retries = 100)]
+/// struct Range {
+///     start: u8,
+///     end: u8,
+/// }
+///
+/// let factor = RangeSurprise::default();
+/// let range = Range::generate_with_factor(&mut surprise_me::rand::thread_rng(), &factor);
+/// assert!(range.start <= range.end);
+/// assert_eq!(factor.filter_stats.accepted(), 1);
+/// ```
+///
 /// # Recursive types
 ///
-/// In case of recursive types, a custom surprise factor is required.
+/// Types that recurse through fields of type `Box<Self>`, `Option<Box<Self>>`, or `Vec<Self>`
+/// can be annotated with `#[surprise(recursive(max_depth = number))]`. The generated surprise
+/// factor then keeps track of the depth and once `max_depth` is reached, `Option<Box<Self>>`
+/// fields will be `None`, `Vec<Self>` fields will be empty, and enum variants containing
+/// `Box<Self>` won't be generated anymore.
+///
+/// ```rust
+/// # use surprise_me::Surprise;
+/// #
+/// #[derive(Surprise)]
+/// #[surprise(recursive(max_depth = 5))]
+/// struct Tree {
+///     value: u32,
+///     // the surprise factor is `RecursiveOptionSurprise`
+///     #[factor(chance = 0.3)]
+///     parent: Option<Box<Tree>>,
+///     // the surprise factor is `RecursiveVecSurprise`
+///     #[factor(max_len = 3)]
+///     children: Vec<Tree>,
+/// }
+///
+/// #[derive(Surprise)]
+/// #[surprise(recursive(max_depth = 3))]
+/// enum Expr {
+///     Literal(i64),
+///     // won't be generated at the maximum depth
+///     Add(Box<Expr>, Box<Expr>),
+/// }
+/// ```
+///
+/// Enum variants without recursion points are leaves. The recursion can be steered toward
+/// them with `decay`: The weights of all other variants are multiplied by `decay` with each
+/// level of depth, which is available as the `weight_decay` field of the surprise factor.
+/// Variants can also be denoted as leaves explicitly with `#[surprise(leaf)]`, in which case
+/// only the denoted variants are considered leaves.
+///
+/// ```rust
+/// # use surprise_me::Surprise;
+/// #
+/// #[derive(Surprise)]
+/// #[surprise(recursive(max_depth = 8, decay = 0.5))]
+/// enum Json {
+///     Null,
+///     Number(f64),
+///     // still nests further, but is treated as a leaf anyway
+///     #[surprise(leaf)]
+///     Array(Vec<Json>),
+///     Wrapped(Box<Json>),
+/// }
+/// ```
+///
+/// For anything more sophisticated, e.g. mutually recursive types, a custom surprise factor is required.
 ///
 /// ```rust
 /// use surprise_me::{Distribution, Rng, Surprise};
-/// use surprise_me::factors::{OptionSurprise, VecSurprise};
+/// use surprise_me::factors::{ItemOrder, LenDistribution, OptionSurprise, VecSurprise};
 ///
 /// #[derive(Surprise)]
 /// // Instead of creating a new surprise factor, use the specified one.
@@ -180,6 +286,8 @@ use rand::{prelude::Distribution, Rng};
 ///             let vec_factor = VecSurprise {
 ///                 min_len: self.min_len,
 ///                 max_len: 8, // can also be hardcoded here instead of a field
+///                 len: LenDistribution::Uniform,
+///                 order: ItemOrder::Any,
 ///                 items: RecursedSurprise { depth: self.depth + 1, min_len: self.min_len },
 ///             };
 ///
@@ -221,6 +329,45 @@ pub trait Surprise: Sized {
         <Self as Surprise>::generate_with_factor(rng, &factor)
     }
 
+    /// Generate a random instance of the type by using a default surprise factor
+    /// that was created with the given size, see [`size`](crate::size).
+    #[inline]
+    fn generate_sized<R>(rng: &mut R, size: usize) -> Self
+    where
+        R: Rng + ?Sized,
+        SurpriseFactor<Self>: Default,
+    {
+        let factor = with_size(size, SurpriseFactor::<Self>::default);
+
+        <Self as Surprise>::generate_with_factor(rng, &factor)
+    }
+
+    /// Generate a random instance of the type by using a default surprise factor
+    /// while limiting its total size to the given budget, see [`budget`](crate::budget).
+    #[inline]
+    fn generate_with_budget<R>(rng: &mut R, budget: Budget) -> Self
+    where
+        R: Rng + ?Sized,
+        SurpriseFactor<Self>: Default,
+    {
+        with_budget(budget, || <Self as Surprise>::generate(rng))
+    }
+
+    /// Generate a random instance of the type by using a default surprise factor
+    /// and a [`PortableRng`] seeded with `seed`.
+    ///
+    /// The same seed always generates the same value, regardless of the platform,
+    /// unless the value contains `usize` or `isize` numbers, see [`seed`](crate::seed).
+    ///
+    /// [`PortableRng`]: crate::seed::PortableRng
+    #[inline]
+    fn generate_seeded(seed: u64) -> Self
+    where
+        SurpriseFactor<Self>: Default,
+    {
+        <Self as Surprise>::generate(&mut portable_rng(seed))
+    }
+
     /// Generate a random instance of the type by using the given surprise factor.
     #[inline]
     fn generate_with_factor<R, F>(rng: &mut R, factor: &F) -> Self
@@ -230,6 +377,129 @@ pub trait Surprise: Sized {
     {
         rng.sample(factor)
     }
+
+    /// Generate a random instance of the type by using a default surprise factor,
+    /// or return an error if the factor's settings are invalid.
+    #[inline]
+    fn try_generate<R>(rng: &mut R) -> Result<Self, SurpriseError>
+    where
+        R: Rng + ?Sized,
+        SurpriseFactor<Self>: Default,
+    {
+        let factor = SurpriseFactor::<Self>::default();
+
+        <Self as Surprise>::try_generate_with_factor(rng, &factor)
+    }
+
+    /// Generate a random instance of the type by using the given surprise factor,
+    /// or return an error if the factor's settings are invalid.
+    ///
+    /// By default, the first of the [`factor_problems`](Surprise::factor_problems) is
+    /// returned and the surprise factor is otherwise assumed to never fail. Types whose
+    /// surprise factor implements [`TryDistribution`] should delegate to it instead,
+    /// which the derive macro does for the surprise factors it creates.
+    #[inline]
+    fn try_generate_with_factor<R>(
+        rng: &mut R,
+        factor: &SurpriseFactor<Self>,
+    ) -> Result<Self, SurpriseError>
+    where
+        R: Rng + ?Sized,
+    {
+        match <Self as Surprise>::factor_problems(factor)
+            .into_iter()
+            .next()
+        {
+            Some(problem) => Err(problem),
+            None => Ok(rng.sample(factor)),
+        }
+    }
+
+    /// Generate a random instance of the type by using the given surprise factor
+    /// whose [`factor_problems`](Surprise::factor_problems) were already checked.
+    ///
+    /// Collections check the surprise factor of their items once and then generate
+    /// each item through this method. By default, it's the same as
+    /// [`try_generate_with_factor`](Surprise::try_generate_with_factor), types whose
+    /// surprise factor can skip its validation may override it.
+    #[inline]
+    fn try_generate_with_valid_factor<R>(
+        rng: &mut R,
+        factor: &SurpriseFactor<Self>,
+    ) -> Result<Self, SurpriseError>
+    where
+        R: Rng + ?Sized,
+    {
+        <Self as Surprise>::try_generate_with_factor(rng, factor)
+    }
+
+    /// Returns all problems of the given surprise factor, see [`Validate`].
+    ///
+    /// By default, the surprise factor is assumed to be always valid. Types whose
+    /// surprise factor implements [`Validate`] should delegate to it instead, which
+    /// the derive macro does for the surprise factors it creates.
+    ///
+    /// [`Validate`]: crate::Validate
+    #[inline]
+    fn factor_problems(factor: &SurpriseFactor<Self>) -> Vec<SurpriseError> {
+        let _ = factor;
+
+        Vec::new()
+    }
+}
+
+/// Keys of ordered maps and sets, i.e. of [`BTreeMapSurprise`] and [`BTreeSetSurprise`].
+///
+/// All methods have defaults, so keys that can only be generated and compared
+/// implement the trait with an empty `impl SurpriseKey for MyKey {}`.
+///
+/// [`BTreeMapSurprise`]: crate::factors::BTreeMapSurprise
+/// [`BTreeSetSurprise`]: crate::factors::BTreeSetSurprise
+pub trait SurpriseKey: Surprise + Ord {
+    /// Returns a copy of the given surprise factor that only generates keys between
+    /// `min` and `max`, e.g. for the key ranges of [`BTreeMapSurprise`].
+    ///
+    /// By default, surprise factors cannot be narrowed and `None` is returned, in which
+    /// case generated keys outside of the range are discarded. Integers narrow the
+    /// `min` and `max` of their [`NumberSurprise`].
+    ///
+    /// [`BTreeMapSurprise`]: crate::factors::BTreeMapSurprise
+    /// [`NumberSurprise`]: crate::factors::NumberSurprise
+    #[inline]
+    fn bounded_factor(
+        factor: &SurpriseFactor<Self>,
+        min: Option<&Self>,
+        max: Option<&Self>,
+    ) -> Option<SurpriseFactor<Self>> {
+        let _ = (factor, min, max);
+
+        None
+    }
+
+    /// Returns the key right after `self`, e.g. for contiguous keys of [`BTreeMapSurprise`].
+    ///
+    /// By default, keys have no successor. Integers return `self + 1` unless it overflows.
+    ///
+    /// [`BTreeMapSurprise`]: crate::factors::BTreeMapSurprise
+    #[inline]
+    fn successor(&self) -> Option<Self> {
+        None
+    }
+
+    /// Returns the key right before `self`, the counterpart of [`SurpriseKey::successor`].
+    #[inline]
+    fn predecessor(&self) -> Option<Self> {
+        None
+    }
+}
+
+/// Fallible counterpart of [`Distribution`].
+///
+/// Instead of panicking on invalid settings, e.g. a `chance` outside of `0.0..=1.0`,
+/// a [`SurpriseError`] naming the invalid field is returned.
+pub trait TryDistribution<T> {
+    /// Generate a random value of `T`, or return an error if the settings of `self` are invalid.
+    fn try_sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Result<T, SurpriseError>;
 }
 
 /// The surprise factor on which the random generation of `T` depends on.
@@ -0,0 +1,67 @@
+use crate::{Surprise, SurpriseError, SurpriseFactor};
+
+/// Trait to check surprise factors for invalid settings before generating anything,
+/// e.g. when they are built from a configuration at runtime.
+///
+/// Unlike [`Surprise::try_generate_with_factor`], which stops at the first invalid
+/// field it runs into, validation reports every problem of the surprise factor.
+///
+/// # Derive macro
+///
+/// Surprise factors created by `#[derive(Surprise)]` implement [`Validate`] by
+/// validating the surprise factors of all fields. Fields with a custom surprise
+/// factor through `#[factor = "..."]` are assumed to be valid.
+///
+/// ```rust
+/// # use surprise_me::{Surprise, Validate};
+/// #
+/// #[derive(Surprise)]
+/// struct MyStruct {
+///     #[factor(min = 10, max = 5)]
+///     a: u32,
+///     #[factor(items(chance = 1.3))]
+///     my_field: Vec<Option<bool>>,
+/// }
+///
+/// let problems = MyStructSurprise::default().validate().unwrap_err();
+/// let fields: Vec<_> = problems.iter().map(|problem| problem.field()).collect();
+///
+/// assert_eq!(fields, ["a.min", "my_field.items.chance"]);
+/// ```
+pub trait Validate {
+    /// Returns all problems of the settings, each naming the path of its invalid field
+    fn problems(&self) -> Vec<SurpriseError>;
+
+    /// Returns all problems of the settings if there are any
+    fn validate(&self) -> Result<(), Vec<SurpriseError>> {
+        let problems = self.problems();
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+}
+
+/// Problems of the surprise factor `factor` of `T` with `field` prepended to their paths
+pub(crate) fn nested_problems<'a, T: Surprise>(
+    field: &'a str,
+    factor: &SurpriseFactor<T>,
+) -> impl Iterator<Item = SurpriseError> + 'a {
+    T::factor_problems(factor)
+        .into_iter()
+        .map(move |problem| problem.in_field(field))
+}
+
+/// Checks the surprise factor `factor` of `T` once so that its values can be generated
+/// through [`Surprise::try_generate_with_valid_factor`]
+pub(crate) fn check_nested<T: Surprise>(
+    field: &str,
+    factor: &SurpriseFactor<T>,
+) -> Result<(), SurpriseError> {
+    match nested_problems::<T>(field, factor).next() {
+        Some(problem) => Err(problem),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,57 @@
+//! (De)serialization of the field `variants_weight` of derived enum factors.
+//!
+//! serde only implements its traits for arrays of up to 32 elements so
+//! enums with more variants need to be handled manually.
+
+use std::{fmt::Formatter, fmt::Result as FmtResult};
+
+use serde::{
+    de::{Error, SeqAccess, Visitor},
+    ser::SerializeTuple,
+    Deserializer, Serializer,
+};
+
+pub fn serialize<S: Serializer, const N: usize>(
+    weights: &[f64; N],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut tuple = serializer.serialize_tuple(N)?;
+
+    for weight in weights {
+        tuple.serialize_element(weight)?;
+    }
+
+    tuple.end()
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+    deserializer: D,
+) -> Result<Box<[f64; N]>, D::Error> {
+    struct WeightsVisitor<const N: usize>;
+
+    impl<'de, const N: usize> Visitor<'de> for WeightsVisitor<N> {
+        type Value = Box<[f64; N]>;
+
+        fn expecting(&self, f: &mut Formatter<'_>) -> FmtResult {
+            write!(f, "a sequence of {N} weights")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut weights = Box::new([0.0; N]);
+
+            for (i, weight) in weights.iter_mut().enumerate() {
+                *weight = seq
+                    .next_element()?
+                    .ok_or_else(|| Error::invalid_length(i, &self))?;
+            }
+
+            if seq.next_element::<f64>()?.is_some() {
+                return Err(Error::invalid_length(N + 1, &self));
+            }
+
+            Ok(weights)
+        }
+    }
+
+    deserializer.deserialize_tuple(N, WeightsVisitor)
+}